@@ -0,0 +1,45 @@
+use alloy_token_program::state::{ AlloyComposition, TOTAL_BPS };
+
+/// Converts a mass percentage such as `1.1` into basis points, clamped to 0–100%.
+pub fn bps_from_percent(percent: f64) -> u16 {
+    (percent * 100.0).round().clamp(0.0, TOTAL_BPS as f64) as u16
+}
+
+/// Requires the measured share of `symbol` to lie within `min_bps..=max_bps`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementFilter {
+    pub symbol: String,
+    pub min_bps: u16,
+    pub max_bps: u16,
+}
+
+/// Filters alloy compositions by the actual percentages of their elements. An alloy matches
+/// when every filtered element is present and within its range.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompositionQuery {
+    pub filters: Vec<ElementFilter>,
+}
+
+impl CompositionQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn element_bps(mut self, symbol: &str, min_bps: u16, max_bps: u16) -> Self {
+        self.filters.push(ElementFilter { symbol: symbol.to_string(), min_bps, max_bps });
+        self
+    }
+
+    /// Same as `element_bps` with the bounds given in percent, e.g. `element("Cr", 0.8, 1.1)`.
+    pub fn element(self, symbol: &str, min_percent: f64, max_percent: f64) -> Self {
+        self.element_bps(symbol, bps_from_percent(min_percent), bps_from_percent(max_percent))
+    }
+
+    pub fn matches(&self, composition: &AlloyComposition) -> bool {
+        self.filters.iter().all(|filter| {
+            composition.element(&filter.symbol)
+                .map(|element| (filter.min_bps..=filter.max_bps).contains(&element.actual_bps))
+                .unwrap_or(false)
+        })
+    }
+}
@@ -0,0 +1,46 @@
+use alloy_token_program::state::{ CustodyEntry, CustodyLog };
+use solana_sdk::pubkey::Pubkey;
+
+/// Why a custody log read back from the chain does not form one unbroken chain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustodyChainError {
+    /// Fewer or more entries were found than the log header counts, e.g. a missing page.
+    EntryCountMismatch { expected: u32, found: usize },
+    /// Entry `index` was handed over by someone other than the previous holder.
+    Gap { index: usize, expected: Pubkey, found: Pubkey },
+    /// Entry `index` is timestamped before the entry preceding it.
+    OutOfOrder { index: usize },
+    /// The last entry does not end with the custodian the log header names.
+    HeadMismatch { expected: Pubkey, found: Pubkey },
+}
+
+/// Checks that `entries`, read from the pages of `log` in order, form a contiguous chain:
+/// the first handoff comes from nobody, every later one from the previous receiver, in
+/// non-decreasing time, ending with the log's current custodian.
+pub fn validate_custody_chain(log: &CustodyLog, entries: &[CustodyEntry]) -> Result<(), CustodyChainError> {
+    if entries.len() != log.entry_count as usize {
+        return Err(CustodyChainError::EntryCountMismatch { expected: log.entry_count, found: entries.len() });
+    }
+
+    let mut holder = Pubkey::default();
+    let mut last_timestamp = i64::MIN;
+
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.from != holder {
+            return Err(CustodyChainError::Gap { index, expected: holder, found: entry.from });
+        }
+
+        if entry.timestamp < last_timestamp {
+            return Err(CustodyChainError::OutOfOrder { index });
+        }
+
+        holder = entry.to;
+        last_timestamp = entry.timestamp;
+    }
+
+    if holder != log.current_custodian {
+        return Err(CustodyChainError::HeadMismatch { expected: log.current_custodian, found: holder });
+    }
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
+use alloy_token_program::error::CustomError as AlloyError;
+use crate::cl_custody::CustodyChainError;
+use num_traits::FromPrimitive;
 use solana_client::client_error::ClientError;
+use solana_sdk::{ instruction::InstructionError, transaction::TransactionError };
 
 #[derive(Debug, PartialEq)]
 pub enum CustomError {
@@ -9,6 +13,9 @@ pub enum CustomError {
     SerializationError,
     ClientError,
     KeyDerivationError,
+    MetadataFetchError(String),
+    BrokenCustodyChain(CustodyChainError),
+    Program(AlloyError),
     Custom(String)
 }
 
@@ -16,4 +23,23 @@ impl From<ClientError> for CustomError {
     fn from(client_error: ClientError) -> Self {
         CustomError::Custom(client_error.to_string())
     }
+}
+
+/// Maps a failed transaction back to the alloy program error that caused it.
+///
+/// Custom error codes are only unique per program, so this must only be used on transactions
+/// whose failing instruction targets the alloy program.
+pub fn decode_alloy_error(error: &TransactionError) -> Option<AlloyError> {
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => AlloyError::from_u32(*code),
+        _ => None,
+    }
+}
+
+/// Converts a failed RPC call, surfacing alloy program errors as `CustomError::Program`.
+pub fn alloy_client_error(client_error: ClientError) -> CustomError {
+    match client_error.get_transaction_error().as_ref().and_then(decode_alloy_error) {
+        Some(alloy_error) => CustomError::Program(alloy_error),
+        None => client_error.into(),
+    }
 }
\ No newline at end of file
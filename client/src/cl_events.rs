@@ -0,0 +1,49 @@
+use alloy_token_program::event::AlloyEvent;
+
+use base64::{ engine::general_purpose::STANDARD, Engine };
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Decodes every `AlloyEvent` emitted by `program_id` from a transaction's log messages.
+///
+/// Invocations are tracked through the `invoke` / `success` / `failed` log lines so that
+/// `Program data:` entries logged by other programs (or by CPIs made from ours) are ignored.
+pub fn decode_alloy_events(program_id: &Pubkey, log_messages: &[String]) -> Vec<AlloyEvent> {
+    let program_id = program_id.to_string();
+    let mut invocation_stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in log_messages {
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if invocation_stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+
+            for field in data.split_whitespace() {
+                let event = STANDARD
+                    .decode(field)
+                    .ok()
+                    .and_then(|bytes| AlloyEvent::try_from_slice(&bytes).ok());
+
+                if let Some(event) = event {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut parts = rest.split_whitespace();
+            let invoked = parts.next();
+
+            match parts.next() {
+                Some("invoke") => invocation_stack.extend(invoked),
+                Some("success") | Some("failed:") => {
+                    invocation_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
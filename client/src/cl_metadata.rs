@@ -0,0 +1,118 @@
+use std::{ fs, path::PathBuf };
+
+use alloy_token_program::{ metadata::ALLOWED_URI_SCHEMES, state::AlloyData };
+use solana_sdk::hash::{ hash, Hash };
+
+use crate::cl_errors::CustomError;
+use crate::client::ClientResult;
+
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+pub const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+
+/// The SHA-256 of a metadata document, as stored in `AlloyData::uri_hash`.
+pub fn metadata_hash(document: &[u8]) -> [u8; 32] {
+    hash(document).to_bytes()
+}
+
+/// Somewhere the metadata document behind an alloy URI can be read from.
+pub trait MetadataSource {
+    fn fetch(&self, uri: &str) -> ClientResult<Vec<u8>>;
+}
+
+/// Fetches documents over HTTP, resolving `ipfs://` and `ar://` URIs through gateways.
+pub struct HttpMetadataSource {
+    client: reqwest::blocking::Client,
+    pub ipfs_gateway: String,
+    pub arweave_gateway: String,
+}
+
+impl HttpMetadataSource {
+    pub fn new() -> Self {
+        Self::with_gateways(DEFAULT_IPFS_GATEWAY, DEFAULT_ARWEAVE_GATEWAY)
+    }
+
+    pub fn with_gateways(ipfs_gateway: &str, arweave_gateway: &str) -> Self {
+        HttpMetadataSource {
+            client: reqwest::blocking::Client::new(),
+            ipfs_gateway: ipfs_gateway.to_string(),
+            arweave_gateway: arweave_gateway.to_string(),
+        }
+    }
+
+    /// The HTTP URL a metadata URI is fetched from.
+    pub fn resolve(&self, uri: &str) -> ClientResult<String> {
+        if uri.starts_with("https://") {
+            Ok(uri.to_string())
+        } else if let Some(cid) = uri.strip_prefix("ipfs://") {
+            Ok(format!("{}{}", self.ipfs_gateway, cid))
+        } else if let Some(id) = uri.strip_prefix("ar://") {
+            Ok(format!("{}{}", self.arweave_gateway, id))
+        } else {
+            Err(CustomError::MetadataFetchError(format!("unsupported metadata URI {}", uri)))
+        }
+    }
+}
+
+impl Default for HttpMetadataSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataSource for HttpMetadataSource {
+    fn fetch(&self, uri: &str) -> ClientResult<Vec<u8>> {
+        let url = self.resolve(uri)?;
+        let fetch_error = |error: reqwest::Error| CustomError::MetadataFetchError(format!("{}: {}", url, error));
+
+        let response = self.client.get(&url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(fetch_error)?;
+
+        Ok(response.bytes().map_err(fetch_error)?.to_vec())
+    }
+}
+
+/// Reads documents from a local directory, mapping `<scheme>://<location>` to
+/// `<root>/<location>`. Useful for checking metadata before it is uploaded.
+pub struct FileMetadataSource {
+    pub root: PathBuf,
+}
+
+impl FileMetadataSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileMetadataSource { root: root.into() }
+    }
+}
+
+impl MetadataSource for FileMetadataSource {
+    fn fetch(&self, uri: &str) -> ClientResult<Vec<u8>> {
+        let location = ALLOWED_URI_SCHEMES.iter()
+            .find_map(|scheme| uri.strip_prefix(scheme))
+            .ok_or_else(|| CustomError::MetadataFetchError(format!("unsupported metadata URI {}", uri)))?;
+        let path = self.root.join(location);
+
+        fs::read(&path).map_err(|error| CustomError::MetadataFetchError(format!("{}: {}", path.display(), error)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MetadataVerification {
+    Verified,
+    Mismatch { expected: Hash, actual: Hash },
+}
+
+/// Fetches the document behind an alloy's URI and checks it against the on-chain `uri_hash`.
+pub fn verify_metadata(source: &impl MetadataSource, alloy_data: &AlloyData) -> ClientResult<MetadataVerification> {
+    let document = source.fetch(alloy_data.uri())?;
+    let actual = metadata_hash(&document);
+
+    if actual == alloy_data.uri_hash {
+        Ok(MetadataVerification::Verified)
+    } else {
+        Ok(MetadataVerification::Mismatch {
+            expected: Hash::new_from_array(alloy_data.uri_hash),
+            actual: Hash::new_from_array(actual),
+        })
+    }
+}
@@ -2,7 +2,7 @@ use solana_client::{
     rpc_client::RpcClient,
     rpc_config::{ RpcAccountInfoConfig, RpcProgramAccountsConfig },
     rpc_filter::{ RpcFilterType, MemcmpEncodedBytes, Memcmp },
-    rpc_request::TokenAccountsFilter,
+    rpc_request::{ TokenAccountsFilter, MAX_MULTIPLE_ACCOUNTS },
     rpc_response::RpcKeyedAccount,
     client_error::ClientError
 };
@@ -17,22 +17,42 @@ use solana_sdk::{
     },
     signature::Signature,
     transaction::Transaction,
-    borsh::try_from_slice_unchecked,
-    program_pack::Pack
+    program_pack::Pack,
+    account::from_account,
+    clock::Clock,
+    sysvar,
 };
 
 use solana_account_decoder::UiAccountEncoding;
-
-use std::str::FromStr;
+use solana_transaction_status::UiTransactionEncoding;
 
 use alloy_token_program::{
-    instruction::NftInstruction,
-    state::{AlloyData, PREFIX},
+    accounts::{ CancelBundleLotAccounts, CreateBundleLotAccounts, PurchaseBundleLotAccounts },
+    instruction::{ NftInstruction, ProvenanceArgs },
+    state::{
+        AlloyComposition, AlloyData, Attestation, ElementComposition,
+        ATTESTATION_DATA_SIZE, ATTESTATION_SEED, COMPOSITION_DATA_SIZE, COMPOSITION_SEED, CONFIG_SEED, MAX_DATA_SIZE, PREFIX, REGISTRY_SEED, ROLE_ATTESTER, ROLE_INSPECTOR,
+        Redemption, ESCROW_SEED, REDEMPTION_DATA_SIZE, REDEMPTION_SEED, ROLE_CUSTODIAN,
+        CustodyEntry, CustodyLog, CustodyLogPage, CUSTODY_ENTRIES_PER_PAGE, CUSTODY_SEED,
+        pad_str, HeatIndex, HEAT_SEED, MAX_PROVENANCE_CODE_LENGTH,
+        Vault, VAULT_SEED,
+        Rental, RENTAL_SEED,
+        InstallmentPlan, INSTALLMENT_SEED,
+        Bundle, BUNDLE_SEED,
+        Swap, SWAP_SEED,
+    },
+    attestation::{ PropertyKind, PropertyUnit },
+    redemption::RedemptionStatus,
+    event::AlloyEvent,
 };
 
 use spl_token::state::{ Account, Mint };
 
-use crate::cl_errors::CustomError;
+use crate::cl_errors::{ alloy_client_error, CustomError };
+use crate::cl_events::decode_alloy_events;
+use crate::cl_metadata::{ verify_metadata, MetadataSource, MetadataVerification };
+use crate::cl_composition::CompositionQuery;
+use crate::cl_custody::validate_custody_chain;
 
 pub type ClientResult<T> = Result<T, CustomError>;
 
@@ -71,9 +91,18 @@ impl NftClient {
         Ok(signature)
     }
 
+    pub fn get_alloy_events(&self, signature: &Signature) -> ClientResult<Vec<AlloyEvent>> {
+        let transaction = self.client.get_transaction(signature, UiTransactionEncoding::Json)?;
+
+        let log_messages: Vec<String> = transaction.transaction.meta
+            .and_then(|meta| meta.log_messages.into())
+            .unwrap_or_default();
+
+        Ok(decode_alloy_events(&alloy_token_program::id(), &log_messages))
+    }
+
     pub fn get_total_nfts(&self, ) -> ClientResult<usize> {
-        let program_key = alloy_token_program::id();
-        Ok(self.client.get_program_accounts(&program_key).unwrap().len())
+        Ok(self.get_program_accounts_of_size(MAX_DATA_SIZE)?.len())
     }
 
     pub fn create_mint_account(
@@ -109,7 +138,7 @@ impl NftClient {
         let latest_blockhash = self.client.get_latest_blockhash().unwrap();
 
         let transaction: Transaction = Transaction::new_signed_with_payer(
-            &vec![create_account_instruction, initialize_mint_instruction],
+            &[create_account_instruction, initialize_mint_instruction],
             Some(&wallet_keypair.pubkey()),
             &[mint_account, wallet_keypair],
             latest_blockhash
@@ -148,7 +177,7 @@ impl NftClient {
         let initialize_account2_instruction: Instruction = spl_token::instruction::initialize_account2(
             &spl_token::id(),
             &acc_mint_to.pubkey(),
-            &mint_account_pk,
+            mint_account_pk,
             &wallet_keypair.pubkey(),
         )
         .unwrap();
@@ -156,7 +185,7 @@ impl NftClient {
         let latest_blockhash = self.client.get_latest_blockhash().unwrap();
 
         let transaction: Transaction = Transaction::new_signed_with_payer(
-            &vec![create_account_instruction, initialize_account2_instruction],
+            &[create_account_instruction, initialize_account2_instruction],
             Some(&wallet_keypair.pubkey()),
             &[wallet_keypair, acc_mint_to],
             latest_blockhash
@@ -174,46 +203,55 @@ impl NftClient {
 
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_alloy_data_accounts(
         &self,
         name: String,
         symbol: String,
         uri: String,
+        uri_hash: [u8; 32],
         listed_price: f64,
+        quantity_grams: u64,
+        provenance: ProvenanceArgs,
         wallet_keypair: &Keypair,
         &mint_account_pubkey: &Pubkey,
     ) -> ClientResult<(AlloyData, Pubkey)> {
         let program_key = alloy_token_program::id();
         println!("--> Program ID: {}", &program_key);
 
-        let accounts = self.client.get_program_accounts(&program_key).unwrap();
+        let accounts = self.get_program_accounts_of_size(MAX_DATA_SIZE).unwrap();
         println!("--> Saved alloy accounts: {}", accounts.len());
 
         let id = accounts.len() as u8 + 1;
 
-        let last_price = 0 as u64;
+        let last_price = 0_u64;
         let listed_price = (listed_price * DEFAULT_LAMPORTS_PER_SOL as f64) as u64;
-        let alloy_data_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(),&[id]];
+        let alloy_data_seeds = &[PREFIX.as_bytes(), program_key.as_ref(),&[id]];
         let (alloy_data_key, _) = Pubkey::find_program_address(alloy_data_seeds, &program_key);
         println!("--> Alloy Data Key: {}", &alloy_data_key);
+        let heat_index_key = self.heat_index_key(&provenance.mill, &provenance.heat_number);
 
         let new_alloy_data_instruction = NftInstruction::create_alloy_data_accounts(
             &program_key,
             &alloy_data_key,
+            &heat_index_key,
             &wallet_keypair.pubkey(),
             id,
             name,
             symbol,
             uri,
+            uri_hash,
             last_price,
             listed_price,
+            quantity_grams,
+            provenance,
             &mint_account_pubkey,
         );
 
         let latest_blockhash = self.client.get_latest_blockhash().unwrap();
 
         let transaction = Transaction::new_signed_with_payer(
-            &vec![new_alloy_data_instruction], 
+            &[new_alloy_data_instruction], 
             Some(&wallet_keypair.pubkey()),
             &vec![wallet_keypair],
             latest_blockhash
@@ -221,23 +259,22 @@ impl NftClient {
 
         let result = self.client.send_and_confirm_transaction_with_spinner(&transaction);
         println!("{:#?}", &result);
-        if result.is_ok() {
-            println!(
-                "Successfully created an Alloy Data Account with Pubkey: {:?}",
-                alloy_data_key
-            )
-        };
+        result.map_err(alloy_client_error)?;
+        println!(
+            "Successfully created an Alloy Data Account with Pubkey: {:?}",
+            alloy_data_key
+        );
 
         let account_data = self.client.get_account_data(&alloy_data_key).unwrap();
-        let alloy_data = try_from_slice_unchecked(&account_data);
+        let alloy_data = AlloyData::unpack(&account_data);
         println!("Alloy Data: {:#?}", &alloy_data);
-        if !alloy_data.is_err() {
-            return Ok((alloy_data.unwrap(), alloy_data_key));
-        } else {
-            return Err(CustomError::Custom("Unxpected Length Of Input".to_string()));
+        match alloy_data {
+            Ok(alloy_data) => Ok((alloy_data, alloy_data_key)),
+            Err(_) => Err(CustomError::Custom("Unxpected Length Of Input".to_string())),
         }
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn get_tokens_by_owner(&self, owner: &Pubkey) -> Result<Vec<RpcKeyedAccount>, ClientError> {
         let token_account_filter = TokenAccountsFilter::Mint(*owner);
 
@@ -251,14 +288,14 @@ impl NftClient {
         let program_key = alloy_token_program::id();
         println!("---> Program ID: {}\n", program_key);
 
-        let accounts = self.client.get_program_accounts(&program_key).unwrap();
+        let accounts = self.get_program_accounts_of_size(MAX_DATA_SIZE).unwrap();
         println!("--> Saved program accounts: {}", accounts.len());
 
         let mut all_owners: Vec<Pubkey> = Vec::new();
 
         for (_, account) in accounts {
-            let alloy_data: AlloyData = try_from_slice_unchecked(&account.data).unwrap();
-            if alloy_data.uri == alloy_uri {
+            let alloy_data = AlloyData::unpack(&account.data).unwrap();
+            if alloy_data.uri() == alloy_uri {
                 all_owners.push(alloy_data.owner_address);
             }
         }
@@ -278,16 +315,20 @@ impl NftClient {
         let program_key = alloy_token_program::id();
         println!("---> Program ID: {}\n", program_key);
 
-        let alloy_data_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(),&[id]];
+        let alloy_data_seeds = &[PREFIX.as_bytes(), program_key.as_ref(),&[id]];
         let (alloy_data_key, _) = Pubkey::find_program_address(alloy_data_seeds, &program_key);
 
+        let account = self.client.get_account(&alloy_data_key).unwrap();
+        let alloy_data = AlloyData::unpack(&account.data).unwrap();
+        let (owner_nft_token_account, _) = self.find_nft_holder(&alloy_data.owner_address).unwrap();
+
         let new_alloy_data_instruction = NftInstruction::update_alloy_price(
             &program_key,
             &alloy_data_key,
             id,
-            listed_price.try_into().unwrap(),
-            &payer.pubkey(),
-            &owner.pubkey()
+            listed_price,
+            &owner.pubkey(),
+            &owner_nft_token_account,
         );
 
         let latest_blockhash = self.client.get_latest_blockhash().unwrap();
@@ -309,22 +350,81 @@ impl NftClient {
         };
     
         let account = self.client.get_account(&alloy_data_key).unwrap();
-        let alloy_data: AlloyData = try_from_slice_unchecked(&account.data).unwrap();
-        println!("Updated Alloy Data: name-{} new_price-{}", alloy_data.name, alloy_data.listed_price);
+        let alloy_data = AlloyData::unpack(&account.data).unwrap();
+        println!("Updated Alloy Data: name-{} new_price-{}", alloy_data.name(), alloy_data.listed_price);
         (alloy_data, alloy_data_key)
     }
 
+    /// Fetches alloy `id` and checks its off-chain metadata document against the stored hash.
+    pub fn verify_alloy_metadata(&self, source: &impl MetadataSource, id: u8) -> ClientResult<MetadataVerification> {
+        let program_key = alloy_token_program::id();
+        let alloy_data_seeds = &[PREFIX.as_bytes(), program_key.as_ref(), &[id]];
+        let (alloy_data_key, _) = Pubkey::find_program_address(alloy_data_seeds, &program_key);
+
+        let account_data = self.client.get_account_data(&alloy_data_key)?;
+        let alloy_data = AlloyData::unpack(&account_data).map_err(|_| CustomError::SerializationError)?;
+
+        verify_metadata(source, &alloy_data)
+    }
+
+    /// Finds the token account currently holding the NFT minted as `mint`.
+    pub fn find_nft_holder(&self, mint: &Pubkey) -> ClientResult<(Pubkey, Account)> {
+        self.get_token_accounts_of_mint(mint)?
+            .into_iter()
+            .find(|(_, token_account)| token_account.amount > 0)
+            .ok_or_else(|| CustomError::Custom(format!("No holder found for mint {}", mint)))
+    }
+
+    /// Finds a token account of `owner` for `mint`, whether or not it holds the NFT.
+    fn find_token_account(&self, mint: &Pubkey, owner: &Pubkey) -> ClientResult<Pubkey> {
+        self.get_token_accounts_of_mint(mint)?
+            .into_iter()
+            .find(|(_, token_account)| token_account.owner == *owner)
+            .map(|(key, _)| key)
+            .ok_or_else(|| CustomError::Custom(format!("No token account of {} found for mint {}", owner, mint)))
+    }
+
+    fn get_token_accounts_of_mint(&self, mint: &Pubkey) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let filter1 = Memcmp::new(
+            0,
+            MemcmpEncodedBytes::Base58(mint.to_string()),
+        );
+        let filter2 = RpcFilterType::DataSize(Account::LEN as u64);
+
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: None,
+            commitment: Some(CommitmentConfig {
+                commitment: CommitmentLevel::Confirmed,
+            }),
+            min_context_slot: None,
+        };
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(filter1), filter2]),
+            account_config,
+            with_context: None,
+        };
+
+        let token_accounts = self.client.get_program_accounts_with_config(&spl_token::id(), config)?;
+
+        Ok(token_accounts
+            .into_iter()
+            .filter_map(|(key, account)| Account::unpack(&account.data).ok().map(|token_account| (key, token_account)))
+            .collect())
+    }
+
     pub fn get_all_alloys(&self) -> Vec<AlloyData> {
         let program_key = alloy_token_program::id();
         println!("---> Program ID: {}\n", program_key);
 
-        let accounts = self.client.get_program_accounts(&program_key).unwrap();
+        let accounts = self.get_program_accounts_of_size(MAX_DATA_SIZE).unwrap();
         println!("--> Saved program accounts: {}", accounts.len());
 
         let mut all_alloys: Vec<AlloyData> = Vec::new();
 
         for (_, account) in accounts {
-            let alloy_data: AlloyData = try_from_slice_unchecked(&account.data).unwrap();
+            let alloy_data = AlloyData::unpack(&account.data).unwrap();
             all_alloys.push(alloy_data);
         }
         println!("{:#?}", &all_alloys);
@@ -335,7 +435,7 @@ impl NftClient {
         let program_key = alloy_token_program::id();
         println!("---> Program ID: {}\n", program_key);
 
-        let accounts = self.client.get_program_accounts(&program_key).unwrap();
+        let accounts = self.get_program_accounts_of_size(MAX_DATA_SIZE).unwrap();
         println!("--> Saved program accounts: {}", accounts.len());
 
         let mut all_alloys: Vec<AlloyData> = Vec::new();
@@ -343,53 +443,34 @@ impl NftClient {
         for (_, account) in accounts {
 
             if owner.pubkey() == account.owner {
-                let alloy_data: AlloyData = try_from_slice_unchecked(&account.data).unwrap();
+                let alloy_data = AlloyData::unpack(&account.data).unwrap();
                 all_alloys.push(alloy_data);
             }
         }
         all_alloys
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn purchase_alloy(
         &self,
         payer: &Keypair,
         id: u8,
         new_name: Option<String>,
         new_uri: Option<String>,
+        new_uri_hash: Option<[u8; 32]>,
         new_price: Option<u64>,
+        require_certification: bool,
     ) -> (AlloyData, Pubkey) {
         let program_key = alloy_token_program::id();
         
-        let alloy_data_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(),&[id]];
+        let alloy_data_seeds = &[PREFIX.as_bytes(), program_key.as_ref(),&[id]];
         let (alloy_data_key, _) = Pubkey::find_program_address(alloy_data_seeds, &program_key);
         
         let account = self.client.get_account(&alloy_data_key).unwrap();
-        let alloy_data: AlloyData = try_from_slice_unchecked(&account.data).unwrap();
-        
-        let filter1 = Memcmp{
-            offset: 0,
-            bytes: MemcmpEncodedBytes::Base58(alloy_data.owner_address.to_string()),
-            encoding: None,
-        };
-        let filter2 = RpcFilterType::DataSize(165);
-        
-        let account_config = RpcAccountInfoConfig {
-            encoding: Some(UiAccountEncoding::Base64),
-            data_slice: None,
-            commitment: Some(CommitmentConfig {
-                commitment: CommitmentLevel::Confirmed,
-            }),
-        };
-    
-        let config = RpcProgramAccountsConfig {
-            filters: Some(vec![solana_client::rpc_filter::RpcFilterType::Memcmp(filter1), filter2]),
-            account_config,
-            with_context: None,
-        };
-    
-        let holders = self.client.get_program_accounts_with_config(&spl_token::id(), config).unwrap();
-    
-        println!("--> Holder {}", holders[0].0.to_string());
+        let alloy_data = AlloyData::unpack(&account.data).unwrap();
+
+        let (nft_token_account, holder) = self.find_nft_holder(&alloy_data.owner_address).unwrap();
+        println!("--> Holder {}", holder.owner);
     
         let new_alloy_data_instruction = NftInstruction::purchase_alloy(
             &program_key,
@@ -397,9 +478,12 @@ impl NftClient {
             id,
             new_name,
             new_uri,
+            new_uri_hash,
             new_price,
+            require_certification,
             &payer.pubkey(),
-            &alloy_data.owner_address,
+            &holder.owner,
+            &nft_token_account,
         );
     
         let latest_blockhash = self.client.get_latest_blockhash().unwrap();
@@ -414,8 +498,1110 @@ impl NftClient {
         let _ = self.client.send_and_confirm_transaction_with_spinner(&transaction);
 
         let account = self.client.get_account(&alloy_data_key).unwrap();
-        let alloy_data: AlloyData = try_from_slice_unchecked(&account.data).unwrap();
-        println!("Purchased Alloy Data: name-{} price-{} owner-{}", alloy_data.name, alloy_data.listed_price, alloy_data.owner_address);
+        let alloy_data = AlloyData::unpack(&account.data).unwrap();
+        println!("Purchased Alloy Data: name-{} price-{} owner-{}", alloy_data.name(), alloy_data.listed_price, alloy_data.owner_address);
         (alloy_data, alloy_data_key)
     }
+
+    /// Accounts of the alloy program with exactly `size` bytes of data, which tells the
+    /// account types apart.
+    fn get_program_accounts_of_size(&self, size: usize) -> ClientResult<Vec<(Pubkey, solana_sdk::account::Account)>> {
+        self.get_program_accounts_matching(size, vec![])
+    }
+
+    /// Like `get_program_accounts_of_size`, additionally applying `filters`.
+    fn get_program_accounts_matching(&self, size: usize, mut filters: Vec<RpcFilterType>) -> ClientResult<Vec<(Pubkey, solana_sdk::account::Account)>> {
+        filters.insert(0, RpcFilterType::DataSize(size as u64));
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            with_context: None,
+        };
+
+        Ok(self.client.get_program_accounts_with_config(&alloy_token_program::id(), config)?)
+    }
+
+    fn alloy_keys(&self, id: u8) -> (Pubkey, Pubkey) {
+        let program_key = alloy_token_program::id();
+        let (alloy_data_key, _) = Pubkey::find_program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id]], &program_key);
+        let (composition_key, _) = Pubkey::find_program_address(
+            &[PREFIX.as_bytes(), program_key.as_ref(), &[id], COMPOSITION_SEED.as_bytes()],
+            &program_key,
+        );
+
+        (alloy_data_key, composition_key)
+    }
+
+    /// Records the chemical composition of alloy `id`. `owner` must hold the alloy's NFT.
+    pub fn create_alloy_composition(
+        &self,
+        payer: &Keypair,
+        owner: &Keypair,
+        id: u8,
+        elements: Vec<ElementComposition>,
+    ) -> ClientResult<Signature> {
+        let (alloy_data_key, composition_key) = self.alloy_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let (owner_nft_token_account, _) = self.find_nft_holder(&alloy_data.owner_address)?;
+
+        let instruction = NftInstruction::create_alloy_composition(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &composition_key,
+            id,
+            elements,
+            &owner.pubkey(),
+            &owner_nft_token_account,
+            &payer.pubkey(),
+        );
+
+        self.send_alloy_instruction(instruction, payer, owner)
+    }
+
+    /// Replaces the recorded chemical composition of alloy `id`.
+    pub fn update_alloy_composition(
+        &self,
+        payer: &Keypair,
+        owner: &Keypair,
+        id: u8,
+        elements: Vec<ElementComposition>,
+    ) -> ClientResult<Signature> {
+        let (alloy_data_key, composition_key) = self.alloy_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let (owner_nft_token_account, _) = self.find_nft_holder(&alloy_data.owner_address)?;
+
+        let instruction = NftInstruction::update_alloy_composition(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &composition_key,
+            id,
+            elements,
+            &owner.pubkey(),
+            &owner_nft_token_account,
+        );
+
+        self.send_alloy_instruction(instruction, payer, owner)
+    }
+
+    fn send_alloy_instruction(&self, instruction: Instruction, payer: &Keypair, owner: &Keypair) -> ClientResult<Signature> {
+        let latest_blockhash = self.client.get_latest_blockhash()?;
+
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[payer, owner],
+            latest_blockhash,
+        );
+
+        self.client.send_and_confirm_transaction_with_spinner(&transaction).map_err(alloy_client_error)
+    }
+
+    pub fn get_alloy_composition(&self, id: u8) -> ClientResult<AlloyComposition> {
+        let (_, composition_key) = self.alloy_keys(id);
+        let account_data = self.client.get_account_data(&composition_key)?;
+
+        AlloyComposition::unpack(&account_data).map_err(|_| CustomError::SerializationError)
+    }
+
+    pub fn get_all_compositions(&self) -> ClientResult<Vec<AlloyComposition>> {
+        self.get_program_accounts_of_size(COMPOSITION_DATA_SIZE)?
+            .into_iter()
+            .map(|(_, account)| AlloyComposition::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .collect()
+    }
+
+    /// Alloys whose recorded composition matches `query`, e.g. every alloy with 0.8–1.1% Cr:
+    /// `CompositionQuery::new().element("Cr", 0.8, 1.1)`.
+    pub fn find_alloys_by_composition(&self, query: &CompositionQuery) -> ClientResult<Vec<(AlloyData, AlloyComposition)>> {
+        let alloys = self.get_all_alloys();
+
+        Ok(self.get_all_compositions()?
+            .into_iter()
+            .filter(|composition| query.matches(composition))
+            .filter_map(|composition| {
+                alloys.iter()
+                    .find(|alloy_data| alloy_data.id == composition.id)
+                    .map(|alloy_data| (*alloy_data, composition))
+            })
+            .collect())
+    }
+
+    fn program_address(&self, seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &alloy_token_program::id()).0
+    }
+
+    fn registry_entry_key(&self, role: u8, member: &Pubkey) -> Pubkey {
+        let program_key = alloy_token_program::id();
+        self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), REGISTRY_SEED.as_bytes(), &[role], member.as_ref()])
+    }
+
+    /// Enrols `member` in the registry for `role`. `authority` must be the program config
+    /// authority.
+    fn add_registry_entry(&self, payer: &Keypair, authority: &Keypair, role: u8, member: &Pubkey) -> ClientResult<Signature> {
+        let program_key = alloy_token_program::id();
+        let config_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), CONFIG_SEED.as_bytes()]);
+
+        let instruction = NftInstruction::add_registry_entry(
+            &program_key,
+            &config_key,
+            &authority.pubkey(),
+            &self.registry_entry_key(role, member),
+            role,
+            member,
+            &payer.pubkey(),
+        );
+
+        self.send_alloy_instruction(instruction, payer, authority)
+    }
+
+    /// Enrols `attester` in the attester registry, signed by the program config authority.
+    pub fn register_attester(&self, payer: &Keypair, authority: &Keypair, attester: &Pubkey) -> ClientResult<Signature> {
+        self.add_registry_entry(payer, authority, ROLE_ATTESTER, attester)
+    }
+
+    /// Enrols `inspector` in the inspector registry, signed by the program config authority.
+    pub fn register_inspector(&self, payer: &Keypair, authority: &Keypair, inspector: &Pubkey) -> ClientResult<Signature> {
+        self.add_registry_entry(payer, authority, ROLE_INSPECTOR, inspector)
+    }
+
+    /// Enrols `custodian` in the custodian registry, signed by the program config authority.
+    pub fn register_custodian(&self, payer: &Keypair, authority: &Keypair, custodian: &Pubkey) -> ClientResult<Signature> {
+        self.add_registry_entry(payer, authority, ROLE_CUSTODIAN, custodian)
+    }
+
+    /// Records `inspector`'s certificate on alloy `id`, valid until the unix timestamp
+    /// `expires_at`.
+    pub fn certify_alloy(
+        &self,
+        payer: &Keypair,
+        inspector: &Keypair,
+        id: u8,
+        certificate_hash: [u8; 32],
+        expires_at: i64,
+    ) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+
+        let instruction = NftInstruction::certify_alloy(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &inspector.pubkey(),
+            &self.registry_entry_key(ROLE_INSPECTOR, &inspector.pubkey()),
+            id,
+            certificate_hash,
+            expires_at,
+        );
+
+        self.send_alloy_instruction(instruction, payer, inspector)
+    }
+
+    /// Whether alloy `id` holds a certificate that has not expired by the cluster's clock.
+    pub fn is_alloy_certified(&self, id: u8) -> ClientResult<bool> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?)
+            .map_err(|_| CustomError::SerializationError)?;
+        let clock: Clock = from_account(&self.client.get_account(&sysvar::clock::id())?)
+            .ok_or(CustomError::SerializationError)?;
+
+        Ok(alloy_data.is_certified(clock.unix_timestamp))
+    }
+
+    /// Records a measured property of alloy `id`, signed by a registered `attester`. `nonce`
+    /// must be unique among the attester's attestations for this alloy.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_attestation(
+        &self,
+        payer: &Keypair,
+        attester: &Keypair,
+        id: u8,
+        nonce: u64,
+        kind: PropertyKind,
+        unit: PropertyUnit,
+        value_milli: i64,
+        document_hash: [u8; 32],
+    ) -> ClientResult<(Signature, Pubkey)> {
+        let program_key = alloy_token_program::id();
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let attestation_key = self.program_address(&[
+            PREFIX.as_bytes(),
+            program_key.as_ref(),
+            &[id],
+            ATTESTATION_SEED.as_bytes(),
+            attester.pubkey().as_ref(),
+            &nonce.to_le_bytes(),
+        ]);
+        let entry_key = self.registry_entry_key(ROLE_ATTESTER, &attester.pubkey());
+
+        let instruction = NftInstruction::create_attestation(
+            &program_key,
+            &alloy_data_key,
+            &attestation_key,
+            &attester.pubkey(),
+            &entry_key,
+            &payer.pubkey(),
+            id,
+            nonce,
+            kind,
+            unit,
+            value_milli,
+            document_hash,
+        );
+
+        Ok((self.send_alloy_instruction(instruction, payer, attester)?, attestation_key))
+    }
+
+    /// Every attestation recorded for alloy `id`, oldest first.
+    pub fn get_alloy_attestations(&self, id: u8) -> ClientResult<Vec<(Pubkey, Attestation)>> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let alloy_filter = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Base58(alloy_data_key.to_string())));
+
+        let mut attestations = self.get_program_accounts_matching(ATTESTATION_DATA_SIZE, vec![alloy_filter])?
+            .into_iter()
+            .map(|(key, account)| {
+                Attestation::unpack(&account.data)
+                    .map(|attestation| (key, attestation))
+                    .map_err(|_| CustomError::SerializationError)
+            })
+            .collect::<ClientResult<Vec<_>>>()?;
+
+        attestations.sort_by_key(|(_, attestation)| attestation.timestamp);
+        Ok(attestations)
+    }
+
+    fn redemption_keys(&self, id: u8) -> (Pubkey, Pubkey) {
+        let program_key = alloy_token_program::id();
+        let redemption_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], REDEMPTION_SEED.as_bytes()]);
+        let escrow_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], REDEMPTION_SEED.as_bytes(), ESCROW_SEED.as_bytes()]);
+
+        (redemption_key, escrow_key)
+    }
+
+    /// Asks for the metal behind alloy `id`, moving `holder`'s NFT into escrow.
+    /// `shipping_hash` is the SHA-256 of the shipping details given to the custodian.
+    pub fn request_redemption(&self, payer: &Keypair, holder: &Keypair, id: u8, shipping_hash: [u8; 32]) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (redemption_key, escrow_key) = self.redemption_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let (holder_token_account, _) = self.find_nft_holder(&alloy_data.owner_address)?;
+
+        let instruction = NftInstruction::request_redemption(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &redemption_key,
+            &escrow_key,
+            &alloy_data.owner_address,
+            &holder.pubkey(),
+            &holder_token_account,
+            &payer.pubkey(),
+            id,
+            shipping_hash,
+        );
+
+        self.send_alloy_instruction(instruction, payer, holder)
+    }
+
+    pub fn acknowledge_redemption(&self, payer: &Keypair, custodian: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (redemption_key, _) = self.redemption_keys(id);
+        let custodian_entry = self.registry_entry_key(ROLE_CUSTODIAN, &custodian.pubkey());
+
+        let instruction = NftInstruction::acknowledge_redemption(&alloy_token_program::id(), &redemption_key, &custodian.pubkey(), &custodian_entry, id);
+
+        self.send_alloy_instruction(instruction, payer, custodian)
+    }
+
+    pub fn mark_shipped(&self, payer: &Keypair, custodian: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (redemption_key, _) = self.redemption_keys(id);
+        let custodian_entry = self.registry_entry_key(ROLE_CUSTODIAN, &custodian.pubkey());
+
+        let instruction = NftInstruction::mark_shipped(&alloy_token_program::id(), &redemption_key, &custodian.pubkey(), &custodian_entry, id);
+
+        self.send_alloy_instruction(instruction, payer, custodian)
+    }
+
+    /// Completes the redemption of alloy `id`, burning its NFT.
+    pub fn confirm_delivery(&self, payer: &Keypair, custodian: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (redemption_key, escrow_key) = self.redemption_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let redemption = self.get_redemption(id)?;
+
+        let instruction = NftInstruction::confirm_delivery(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &redemption_key,
+            &escrow_key,
+            &alloy_data.owner_address,
+            &redemption.holder,
+            &custodian.pubkey(),
+            &self.registry_entry_key(ROLE_CUSTODIAN, &custodian.pubkey()),
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, custodian)
+    }
+
+    /// Withdraws the redemption of alloy `id`, returning the NFT to `holder`.
+    pub fn cancel_redemption(&self, payer: &Keypair, holder: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (redemption_key, escrow_key) = self.redemption_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let holder_token_account = self.find_token_account(&alloy_data.owner_address, &holder.pubkey())?;
+
+        let instruction = NftInstruction::cancel_redemption(
+            &alloy_token_program::id(),
+            &redemption_key,
+            &escrow_key,
+            &holder.pubkey(),
+            &holder_token_account,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, holder)
+    }
+
+    pub fn get_redemption(&self, id: u8) -> ClientResult<Redemption> {
+        let (redemption_key, _) = self.redemption_keys(id);
+        let account_data = self.client.get_account_data(&redemption_key)?;
+
+        Redemption::unpack(&account_data).map_err(|_| CustomError::SerializationError)
+    }
+
+    /// Every redemption not yet delivered, for custodians to work through.
+    pub fn get_open_redemptions(&self) -> ClientResult<Vec<Redemption>> {
+        let redemptions = self.get_program_accounts_of_size(REDEMPTION_DATA_SIZE)?
+            .into_iter()
+            .map(|(_, account)| Redemption::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .collect::<ClientResult<Vec<_>>>()?;
+
+        Ok(redemptions.into_iter().filter(|redemption| redemption.status() != Some(RedemptionStatus::Delivered)).collect())
+    }
+
+    fn custody_keys(&self, id: u8, page: u32) -> (Pubkey, Pubkey) {
+        let program_key = alloy_token_program::id();
+        let custody_log_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], CUSTODY_SEED.as_bytes()]);
+        let custody_page_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], CUSTODY_SEED.as_bytes(), &page.to_le_bytes()]);
+
+        (custody_log_key, custody_page_key)
+    }
+
+    /// Records that `custodian` took over the metal behind alloy `id` at `location`, a site
+    /// code such as the UN/LOCODE `DEHAM`. `document_hash` is the SHA-256 of the handoff
+    /// paperwork.
+    pub fn record_custody(
+        &self,
+        payer: &Keypair,
+        custodian: &Keypair,
+        id: u8,
+        location: &str,
+        document_hash: [u8; 32],
+    ) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let page = self.get_custody_log(id)?.map_or(0, |custody_log| custody_log.next_page());
+        let (custody_log_key, custody_page_key) = self.custody_keys(id, page);
+
+        let instruction = NftInstruction::record_custody(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &custody_log_key,
+            &custody_page_key,
+            &custodian.pubkey(),
+            &self.registry_entry_key(ROLE_CUSTODIAN, &custodian.pubkey()),
+            &payer.pubkey(),
+            id,
+            location,
+            document_hash,
+        );
+
+        self.send_alloy_instruction(instruction, payer, custodian)
+    }
+
+    /// The custody log of alloy `id`, or `None` before its first handoff.
+    pub fn get_custody_log(&self, id: u8) -> ClientResult<Option<CustodyLog>> {
+        let (custody_log_key, _) = self.custody_keys(id, 0);
+
+        self.client.get_account_with_commitment(&custody_log_key, self.client.commitment())?
+            .value
+            .map(|account| CustodyLog::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .transpose()
+    }
+
+    /// Reconstructs the full chain of custody of alloy `id` from its log pages, oldest
+    /// handoff first, failing with `BrokenCustodyChain` unless it is contiguous.
+    pub fn get_custody_chain(&self, id: u8) -> ClientResult<Vec<CustodyEntry>> {
+        let Some(custody_log) = self.get_custody_log(id)? else {
+            return Ok(Vec::new());
+        };
+
+        let page_count = (custody_log.entry_count as usize).div_ceil(CUSTODY_ENTRIES_PER_PAGE) as u32;
+        let page_keys: Vec<Pubkey> = (0..page_count).map(|page| self.custody_keys(id, page).1).collect();
+        let mut entries = Vec::with_capacity(custody_log.entry_count as usize);
+
+        for keys in page_keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            // A missing page leaves the chain short, which the validation below reports.
+            for account in self.client.get_multiple_accounts(keys)?.into_iter().flatten() {
+                let page = CustodyLogPage::unpack(&account.data).map_err(|_| CustomError::SerializationError)?;
+                entries.extend_from_slice(page.entries());
+            }
+        }
+
+        validate_custody_chain(&custody_log, &entries).map_err(CustomError::BrokenCustodyChain)?;
+
+        Ok(entries)
+    }
+
+    /// Splits `quantity_grams` off alloy `id` into a new child lot listed at `listed_price`
+    /// lamports, returning the child's id. The program mints the child's NFT to `holder` from
+    /// a fresh mint created in the same instruction.
+    pub fn split_alloy(
+        &self,
+        payer: &Keypair,
+        holder: &Keypair,
+        id: u8,
+        quantity_grams: u64,
+        listed_price: u64,
+    ) -> ClientResult<(Signature, u8)> {
+        let child_id = self.get_total_nfts()? as u8 + 1;
+        let (alloy_data_key, composition_key) = self.alloy_keys(id);
+        let (child_alloy_data_key, child_composition_key) = self.alloy_keys(child_id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let holder_token_account = self.find_token_account(&alloy_data.owner_address, &holder.pubkey())?;
+        let child_mint = Keypair::new();
+        let child_token_account = Keypair::new();
+
+        let instruction = NftInstruction::split_alloy(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &child_alloy_data_key,
+            &composition_key,
+            &child_composition_key,
+            &child_mint.pubkey(),
+            &holder.pubkey(),
+            &holder_token_account,
+            &child_token_account.pubkey(),
+            &payer.pubkey(),
+            id,
+            child_id,
+            quantity_grams,
+            listed_price,
+        );
+
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[payer, holder, &child_mint, &child_token_account],
+            self.client.get_latest_blockhash()?,
+        );
+        let signature = self.client.send_and_confirm_transaction_with_spinner(&transaction).map_err(alloy_client_error)?;
+
+        Ok((signature, child_id))
+    }
+
+    /// Merges every gram of alloy `source_id` into alloy `id`, burning the source's NFT.
+    /// `holder` must hold both lots, which must be of the same grade under the same standard
+    /// and allow the same range for every element.
+    pub fn merge_alloys(&self, payer: &Keypair, holder: &Keypair, id: u8, source_id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, composition_key) = self.alloy_keys(id);
+        let (source_alloy_data_key, source_composition_key) = self.alloy_keys(source_id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let source = AlloyData::unpack(&self.client.get_account_data(&source_alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+        let instruction = NftInstruction::merge_alloys(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &source_alloy_data_key,
+            &composition_key,
+            &source_composition_key,
+            &source.owner_address,
+            &holder.pubkey(),
+            &self.find_token_account(&alloy_data.owner_address, &holder.pubkey())?,
+            &self.find_token_account(&source.owner_address, &holder.pubkey())?,
+            id,
+            source_id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, holder)
+    }
+
+    /// The lots alloy `id` descends from through splits, nearest first.
+    pub fn get_alloy_lineage(&self, id: u8) -> ClientResult<Vec<AlloyData>> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let mut alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let mut lineage = Vec::new();
+
+        while alloy_data.parent != Pubkey::default() {
+            alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data.parent)?).map_err(|_| CustomError::SerializationError)?;
+            lineage.push(alloy_data);
+        }
+
+        Ok(lineage)
+    }
+    fn heat_index_key(&self, mill: &str, heat_number: &str) -> Pubkey {
+        let program_key = alloy_token_program::id();
+        let mill: [u8; MAX_PROVENANCE_CODE_LENGTH] = pad_str(mill);
+        let heat_number: [u8; MAX_PROVENANCE_CODE_LENGTH] = pad_str(heat_number);
+
+        self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), HEAT_SEED.as_bytes(), &mill, &heat_number])
+    }
+
+    /// The alloy the heat `heat_number` of `mill` was tokenized as, if any. Lots split off
+    /// that alloy share its heat; `get_alloy_lineage` leads back to it from them.
+    pub fn find_alloy_by_heat(&self, mill: &str, heat_number: &str) -> ClientResult<Option<(Pubkey, AlloyData)>> {
+        let heat_index_key = self.heat_index_key(mill, heat_number);
+        let Some(account) = self.client.get_account_with_commitment(&heat_index_key, self.client.commitment())?.value else {
+            return Ok(None);
+        };
+
+        let heat_index = HeatIndex::unpack(&account.data).map_err(|_| CustomError::SerializationError)?;
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&heat_index.alloy)?).map_err(|_| CustomError::SerializationError)?;
+
+        Ok(Some((heat_index.alloy, alloy_data)))
+    }
+    fn vault_keys(&self, id: u8) -> (Pubkey, Pubkey) {
+        let program_key = alloy_token_program::id();
+        let vault_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], VAULT_SEED.as_bytes()]);
+        let escrow_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], VAULT_SEED.as_bytes(), ESCROW_SEED.as_bytes()]);
+
+        (vault_key, escrow_key)
+    }
+
+    /// The token account of `owner` holding the most shares of `share_mint`.
+    fn find_share_account(&self, share_mint: &Pubkey, owner: &Pubkey) -> ClientResult<Pubkey> {
+        self.get_token_accounts_of_mint(share_mint)?
+            .into_iter()
+            .filter(|(_, token_account)| token_account.owner == *owner)
+            .max_by_key(|(_, token_account)| token_account.amount)
+            .map(|(key, _)| key)
+            .ok_or_else(|| CustomError::Custom(format!("No share account of {} found for mint {}", owner, share_mint)))
+    }
+
+    pub fn get_vault(&self, id: u8) -> ClientResult<Option<Vault>> {
+        let (vault_key, _) = self.vault_keys(id);
+
+        self.client.get_account_with_commitment(&vault_key, self.client.commitment())?
+            .value
+            .map(|account| Vault::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .transpose()
+    }
+
+    /// Locks the NFT of alloy `id` held by `curator` in a vault and mints `share_supply` shares
+    /// of a new mint with `share_decimals` decimals to them. Anyone may buy the NFT out for
+    /// `reserve_price` lamports. Returns the share mint alongside the signature.
+    pub fn create_vault(
+        &self,
+        payer: &Keypair,
+        curator: &Keypair,
+        id: u8,
+        share_supply: u64,
+        share_decimals: u8,
+        reserve_price: u64,
+    ) -> ClientResult<(Signature, Pubkey)> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (vault_key, escrow_key) = self.vault_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let curator_token_account = self.find_token_account(&alloy_data.owner_address, &curator.pubkey())?;
+        let share_mint = Keypair::new();
+        let share_token_account = Keypair::new();
+
+        let instructions = [
+            solana_sdk::system_instruction::create_account(
+                &payer.pubkey(),
+                &share_mint.pubkey(),
+                self.client.get_minimum_balance_for_rent_exemption(Mint::LEN)?,
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(&spl_token::id(), &share_mint.pubkey(), &vault_key, None, share_decimals)
+                .map_err(|_| CustomError::InvalidInput)?,
+            solana_sdk::system_instruction::create_account(
+                &payer.pubkey(),
+                &share_token_account.pubkey(),
+                self.client.get_minimum_balance_for_rent_exemption(Account::LEN)?,
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::id(), &share_token_account.pubkey(), &share_mint.pubkey(), &curator.pubkey())
+                .map_err(|_| CustomError::InvalidInput)?,
+            NftInstruction::create_vault(
+                &alloy_token_program::id(),
+                &alloy_data_key,
+                &vault_key,
+                &escrow_key,
+                &alloy_data.owner_address,
+                &share_mint.pubkey(),
+                &curator.pubkey(),
+                &curator_token_account,
+                &share_token_account.pubkey(),
+                &payer.pubkey(),
+                id,
+                share_supply,
+                reserve_price,
+            ),
+        ];
+
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[payer, curator, &share_mint, &share_token_account],
+            self.client.get_latest_blockhash()?,
+        );
+        let signature = self.client.send_and_confirm_transaction_with_spinner(&transaction).map_err(alloy_client_error)?;
+
+        Ok((signature, share_mint.pubkey()))
+    }
+
+    /// Hands the NFT of alloy `id` back to `holder`, who must hold every outstanding share.
+    pub fn redeem_vault(&self, payer: &Keypair, holder: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (vault_key, escrow_key) = self.vault_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let vault = self.get_vault(id)?.ok_or_else(|| CustomError::Custom(format!("Alloy {} is not in a vault", id)))?;
+
+        let instruction = NftInstruction::redeem_vault(
+            &alloy_token_program::id(),
+            &vault_key,
+            &escrow_key,
+            &vault.share_mint,
+            &holder.pubkey(),
+            &self.find_share_account(&vault.share_mint, &holder.pubkey())?,
+            &self.find_token_account(&alloy_data.owner_address, &holder.pubkey())?,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, holder)
+    }
+
+    /// Buys the NFT of alloy `id` out of its vault at the reserve price, into a new token
+    /// account of `buyer`.
+    pub fn buyout_vault(&self, buyer: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (vault_key, escrow_key) = self.vault_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let buyer_token_account = Keypair::new();
+
+        let instructions = [
+            solana_sdk::system_instruction::create_account(
+                &buyer.pubkey(),
+                &buyer_token_account.pubkey(),
+                self.client.get_minimum_balance_for_rent_exemption(Account::LEN)?,
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::id(), &buyer_token_account.pubkey(), &alloy_data.owner_address, &buyer.pubkey())
+                .map_err(|_| CustomError::InvalidInput)?,
+            NftInstruction::buyout_vault(
+                &alloy_token_program::id(),
+                &vault_key,
+                &escrow_key,
+                &buyer.pubkey(),
+                &buyer_token_account.pubkey(),
+                id,
+            ),
+        ];
+
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&buyer.pubkey()),
+            &[buyer, &buyer_token_account],
+            self.client.get_latest_blockhash()?,
+        );
+
+        self.client.send_and_confirm_transaction_with_spinner(&transaction).map_err(alloy_client_error)
+    }
+
+    /// Burns the shares `holder` holds of the bought-out vault of alloy `id` for their part
+    /// of the reserve price.
+    pub fn claim_vault_proceeds(&self, payer: &Keypair, holder: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (vault_key, _) = self.vault_keys(id);
+        let vault = self.get_vault(id)?.ok_or_else(|| CustomError::Custom(format!("Alloy {} has no vault", id)))?;
+
+        let instruction = NftInstruction::claim_vault_proceeds(
+            &alloy_token_program::id(),
+            &vault_key,
+            &vault.share_mint,
+            &holder.pubkey(),
+            &self.find_share_account(&vault.share_mint, &holder.pubkey())?,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, holder)
+    }
+    fn rental_keys(&self, id: u8) -> (Pubkey, Pubkey) {
+        let program_key = alloy_token_program::id();
+        let rental_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], RENTAL_SEED.as_bytes()]);
+        let escrow_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], RENTAL_SEED.as_bytes(), ESCROW_SEED.as_bytes()]);
+
+        (rental_key, escrow_key)
+    }
+
+    /// The rental offer of alloy `id`, including its current or most recent renter.
+    pub fn get_rental(&self, id: u8) -> ClientResult<Option<Rental>> {
+        let (rental_key, _) = self.rental_keys(id);
+
+        self.client.get_account_with_commitment(&rental_key, self.client.commitment())?
+            .value
+            .map(|account| Rental::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .transpose()
+    }
+
+    /// Offers alloy `id`, whose NFT `lessor` holds, for rent at `price_per_day` lamports for up
+    /// to `max_days` days. The NFT is escrowed from the first rental until the offer is ended.
+    pub fn create_rental(&self, payer: &Keypair, lessor: &Keypair, id: u8, price_per_day: u64, max_days: u16) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (rental_key, escrow_key) = self.rental_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+        let instruction = NftInstruction::create_rental(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &rental_key,
+            &escrow_key,
+            &alloy_data.owner_address,
+            &lessor.pubkey(),
+            &self.find_token_account(&alloy_data.owner_address, &lessor.pubkey())?,
+            &payer.pubkey(),
+            id,
+            price_per_day,
+            max_days,
+        );
+
+        self.send_alloy_instruction(instruction, payer, lessor)
+    }
+
+    /// Rents alloy `id` for `days` days, paid by `renter` to the lessor.
+    pub fn rent_alloy(&self, payer: &Keypair, renter: &Keypair, id: u8, days: u16) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (rental_key, escrow_key) = self.rental_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let rental = self.get_rental(id)?.ok_or_else(|| CustomError::Custom(format!("Alloy {} is not offered for rent", id)))?;
+
+        let instruction = NftInstruction::rent_alloy(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &rental_key,
+            &escrow_key,
+            &rental.lessor,
+            &self.find_token_account(&alloy_data.owner_address, &rental.lessor)?,
+            &renter.pubkey(),
+            id,
+            days,
+        );
+
+        self.send_alloy_instruction(instruction, payer, renter)
+    }
+
+    /// Closes the rental offer of alloy `id` and returns an escrowed NFT to the lessor. Signed
+    /// by the renter while a rental runs, by anyone once it has expired and by the lessor if
+    /// the alloy was never rented.
+    pub fn end_rental(&self, payer: &Keypair, authority: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (rental_key, escrow_key) = self.rental_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let rental = self.get_rental(id)?.ok_or_else(|| CustomError::Custom(format!("Alloy {} is not offered for rent", id)))?;
+
+        let instruction = NftInstruction::end_rental(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &rental_key,
+            &escrow_key,
+            &authority.pubkey(),
+            &rental.lessor,
+            &self.find_token_account(&alloy_data.owner_address, &rental.lessor)?,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, authority)
+    }
+
+    fn installment_keys(&self, id: u8) -> (Pubkey, Pubkey) {
+        let program_key = alloy_token_program::id();
+        let plan_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], INSTALLMENT_SEED.as_bytes()]);
+        let escrow_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], INSTALLMENT_SEED.as_bytes(), ESCROW_SEED.as_bytes()]);
+
+        (plan_key, escrow_key)
+    }
+
+    pub fn get_installment_plan(&self, id: u8) -> ClientResult<Option<InstallmentPlan>> {
+        let (plan_key, _) = self.installment_keys(id);
+
+        self.client.get_account_with_commitment(&plan_key, self.client.commitment())?
+            .value
+            .map(|account| InstallmentPlan::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .transpose()
+    }
+
+    /// Escrows the NFT of alloy `id` held by `seller` for `buyer`, who pays `total_price`
+    /// lamports in `payments` tranches, the first due by `first_due_at` and each further one
+    /// `interval` seconds later. On default the seller keeps `forfeit_bps` of what was paid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_installment_plan(
+        &self,
+        payer: &Keypair,
+        seller: &Keypair,
+        id: u8,
+        buyer: &Pubkey,
+        total_price: u64,
+        payments: u8,
+        first_due_at: i64,
+        interval: i64,
+        forfeit_bps: u16,
+    ) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (plan_key, escrow_key) = self.installment_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+        let instruction = NftInstruction::create_installment_plan(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &plan_key,
+            &escrow_key,
+            &alloy_data.owner_address,
+            &seller.pubkey(),
+            &self.find_token_account(&alloy_data.owner_address, &seller.pubkey())?,
+            &payer.pubkey(),
+            id,
+            buyer,
+            total_price,
+            payments,
+            first_due_at,
+            interval,
+            forfeit_bps,
+        );
+
+        self.send_alloy_instruction(instruction, payer, seller)
+    }
+
+    /// Pays the next installment of alloy `id`. The buyer needs a token account for the
+    /// alloy's mint, which receives the NFT with the final payment.
+    pub fn pay_installment(&self, payer: &Keypair, buyer: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (plan_key, escrow_key) = self.installment_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let plan = self.get_installment_plan(id)?.ok_or_else(|| CustomError::Custom(format!("Alloy {} has no installment plan", id)))?;
+
+        let instruction = NftInstruction::pay_installment(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &plan_key,
+            &escrow_key,
+            &plan.seller,
+            &buyer.pubkey(),
+            &self.find_token_account(&alloy_data.owner_address, &buyer.pubkey())?,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, buyer)
+    }
+
+    /// Takes the NFT of alloy `id` back from a buyer who missed a deadline.
+    pub fn reclaim_installment_plan(&self, payer: &Keypair, seller: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (plan_key, escrow_key) = self.installment_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let plan = self.get_installment_plan(id)?.ok_or_else(|| CustomError::Custom(format!("Alloy {} has no installment plan", id)))?;
+
+        let instruction = NftInstruction::reclaim_installment_plan(
+            &alloy_token_program::id(),
+            &plan_key,
+            &escrow_key,
+            &seller.pubkey(),
+            &self.find_token_account(&alloy_data.owner_address, &seller.pubkey())?,
+            &plan.buyer,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, seller)
+    }
+    fn bundle_key(&self, seller: &Pubkey, nonce: u64) -> Pubkey {
+        let program_key = alloy_token_program::id();
+
+        self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), BUNDLE_SEED.as_bytes(), seller.as_ref(), &nonce.to_le_bytes()])
+    }
+
+    fn bundle_escrow_key(&self, id: u8) -> Pubkey {
+        let program_key = alloy_token_program::id();
+
+        self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], BUNDLE_SEED.as_bytes(), ESCROW_SEED.as_bytes()])
+    }
+
+    pub fn get_bundle(&self, seller: &Pubkey, nonce: u64) -> ClientResult<Option<Bundle>> {
+        self.client.get_account_with_commitment(&self.bundle_key(seller, nonce), self.client.commitment())?
+            .value
+            .map(|account| Bundle::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .transpose()
+    }
+
+    /// Escrows the NFTs of alloys `ids` held by `seller` under one listing for `price`
+    /// lamports. `nonce` tells apart the seller's bundles.
+    pub fn create_bundle(&self, payer: &Keypair, seller: &Keypair, nonce: u64, ids: &[u8], price: u64) -> ClientResult<Signature> {
+        let mut lots = Vec::with_capacity(ids.len());
+
+        for &id in ids {
+            let (alloy_data_key, _) = self.alloy_keys(id);
+            let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+            lots.push(CreateBundleLotAccounts {
+                alloy_data: alloy_data_key,
+                mint: alloy_data.owner_address,
+                seller_token_account: self.find_token_account(&alloy_data.owner_address, &seller.pubkey())?,
+                escrow: self.bundle_escrow_key(id),
+            });
+        }
+
+        let instruction = NftInstruction::create_bundle(
+            &alloy_token_program::id(),
+            &self.bundle_key(&seller.pubkey(), nonce),
+            &seller.pubkey(),
+            &payer.pubkey(),
+            &lots,
+            nonce,
+            ids.to_vec(),
+            price,
+        );
+
+        self.send_alloy_instruction(instruction, payer, seller)
+    }
+
+    /// Buys every alloy in the bundle `nonce` of `seller`. The buyer needs a token account
+    /// for each alloy's mint.
+    pub fn purchase_bundle(&self, payer: &Keypair, buyer: &Keypair, seller: &Pubkey, nonce: u64) -> ClientResult<Signature> {
+        let bundle = self.get_bundle(seller, nonce)?.ok_or_else(|| CustomError::Custom(format!("{} has no bundle {}", seller, nonce)))?;
+        let mut lots = Vec::with_capacity(bundle.ids().len());
+
+        for &id in bundle.ids() {
+            let (alloy_data_key, _) = self.alloy_keys(id);
+            let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+            lots.push(PurchaseBundleLotAccounts {
+                alloy_data: alloy_data_key,
+                escrow: self.bundle_escrow_key(id),
+                buyer_token_account: self.find_token_account(&alloy_data.owner_address, &buyer.pubkey())?,
+            });
+        }
+
+        let instruction = NftInstruction::purchase_bundle(
+            &alloy_token_program::id(),
+            &self.bundle_key(seller, nonce),
+            seller,
+            &buyer.pubkey(),
+            &lots,
+            nonce,
+        );
+
+        self.send_alloy_instruction(instruction, payer, buyer)
+    }
+
+    /// Takes every NFT in the unsold bundle `nonce` back to `seller`.
+    pub fn cancel_bundle(&self, payer: &Keypair, seller: &Keypair, nonce: u64) -> ClientResult<Signature> {
+        let bundle = self.get_bundle(&seller.pubkey(), nonce)?.ok_or_else(|| CustomError::Custom(format!("{} has no bundle {}", seller.pubkey(), nonce)))?;
+        let mut lots = Vec::with_capacity(bundle.ids().len());
+
+        for &id in bundle.ids() {
+            let (alloy_data_key, _) = self.alloy_keys(id);
+            let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+            lots.push(CancelBundleLotAccounts {
+                escrow: self.bundle_escrow_key(id),
+                seller_token_account: self.find_token_account(&alloy_data.owner_address, &seller.pubkey())?,
+            });
+        }
+
+        let instruction = NftInstruction::cancel_bundle(
+            &alloy_token_program::id(),
+            &self.bundle_key(&seller.pubkey(), nonce),
+            &seller.pubkey(),
+            &lots,
+            nonce,
+        );
+
+        self.send_alloy_instruction(instruction, payer, seller)
+    }
+    fn swap_keys(&self, id: u8) -> (Pubkey, Pubkey) {
+        let program_key = alloy_token_program::id();
+        let swap_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], SWAP_SEED.as_bytes()]);
+        let escrow_key = self.program_address(&[PREFIX.as_bytes(), program_key.as_ref(), &[id], SWAP_SEED.as_bytes(), ESCROW_SEED.as_bytes()]);
+
+        (swap_key, escrow_key)
+    }
+
+    pub fn get_swap(&self, id: u8) -> ClientResult<Option<Swap>> {
+        let (swap_key, _) = self.swap_keys(id);
+
+        self.client.get_account_with_commitment(&swap_key, self.client.commitment())?
+            .value
+            .map(|account| Swap::unpack(&account.data).map_err(|_| CustomError::SerializationError))
+            .transpose()
+    }
+
+    /// Escrows the NFT of alloy `id` held by `proposer` in exchange for alloy `wanted_id`
+    /// plus `top_up` lamports.
+    pub fn propose_swap(&self, payer: &Keypair, proposer: &Keypair, id: u8, wanted_id: u8, top_up: u64) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (wanted_alloy_data_key, _) = self.alloy_keys(wanted_id);
+        let (swap_key, escrow_key) = self.swap_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+        let instruction = NftInstruction::propose_swap(
+            &alloy_token_program::id(),
+            &alloy_data_key,
+            &wanted_alloy_data_key,
+            &swap_key,
+            &escrow_key,
+            &alloy_data.owner_address,
+            &proposer.pubkey(),
+            &self.find_token_account(&alloy_data.owner_address, &proposer.pubkey())?,
+            &payer.pubkey(),
+            id,
+            wanted_id,
+            top_up,
+        );
+
+        self.send_alloy_instruction(instruction, payer, proposer)
+    }
+
+    /// Trades the wanted alloy held by `counterparty` and the top-up for the NFT escrowed by
+    /// the swap of alloy `id`. Both parties need a token account for the mint they receive.
+    pub fn accept_swap(&self, payer: &Keypair, counterparty: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (swap_key, escrow_key) = self.swap_keys(id);
+        let swap = self.get_swap(id)?.ok_or_else(|| CustomError::Custom(format!("Alloy {} is not offered for a swap", id)))?;
+        let (wanted_alloy_data_key, _) = self.alloy_keys(swap.wanted_id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+        let wanted_alloy_data = AlloyData::unpack(&self.client.get_account_data(&wanted_alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+        let instruction = NftInstruction::accept_swap(
+            &alloy_token_program::id(),
+            &wanted_alloy_data_key,
+            &swap_key,
+            &escrow_key,
+            &swap.proposer,
+            &self.find_token_account(&wanted_alloy_data.owner_address, &swap.proposer)?,
+            &counterparty.pubkey(),
+            &self.find_nft_holder(&wanted_alloy_data.owner_address)?.0,
+            &self.find_token_account(&alloy_data.owner_address, &counterparty.pubkey())?,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, counterparty)
+    }
+
+    /// Withdraws the swap offer of alloy `id`, returning its NFT to `proposer`.
+    pub fn cancel_swap(&self, payer: &Keypair, proposer: &Keypair, id: u8) -> ClientResult<Signature> {
+        let (alloy_data_key, _) = self.alloy_keys(id);
+        let (swap_key, escrow_key) = self.swap_keys(id);
+        let alloy_data = AlloyData::unpack(&self.client.get_account_data(&alloy_data_key)?).map_err(|_| CustomError::SerializationError)?;
+
+        let instruction = NftInstruction::cancel_swap(
+            &alloy_token_program::id(),
+            &swap_key,
+            &escrow_key,
+            &proposer.pubkey(),
+            &self.find_token_account(&alloy_data.owner_address, &proposer.pubkey())?,
+            id,
+        );
+
+        self.send_alloy_instruction(instruction, payer, proposer)
+    }
 }
\ No newline at end of file
@@ -1,133 +1,189 @@
 pub mod client;
 pub mod cl_errors;
+pub mod cl_events;
+pub mod cl_metadata;
+pub mod cl_composition;
+pub mod cl_custody;
 
 #[cfg(test)]
 mod tests {
-
-	use solana_sdk::signer::{
-		Signer,
-		keypair::{
-			read_keypair_file,
-			write_keypair_file,
-			Keypair
-		},
-	};
 	use solana_sdk::pubkey::Pubkey;
-	use std::str::FromStr;
-	use crate::client::NftClient;
-	
-	pub const WALLET_FILE_PATH: &'static str = "wallet.keypair";
-
-	// pub fn get_wallet() -> Keypair {
-    // 	let wallet_keypair: Keypair = if let Ok(keypair) = read_keypair_file(WALLET_FILE_PATH) {
-    //     	keypair
-    // 	} else {
-    //     	let new_keypair = Keypair::new();
-    //     	write_keypair_file(&new_keypair, WALLET_FILE_PATH).unwrap();
-    //     	new_keypair
-    // 	};
-
-    // 	return wallet_keypair;
-	// }
-
-	// #[test]
-	// fn test_program_id() {
-	// 	let got = alloy_token_program::id().to_string();
-
-	// 	let want = "D7RRT9SPTuFxXtEJQSP1kSN5me9Q56UsDYAhFsbmXpsa".to_string();
-
-	// 	assert_eq!(want, got, "Alloy Token Program Key: {}, but got: {}", want, got);
-	// }
-
-	// #[test]
-	// fn test_create_mint_account() {
-	// 	let rpc_client: NftClient = NftClient::new().unwrap();
-		
-	// 	let wallet_keypair = get_wallet();
-
-	// 	let mint_acc = Keypair::new();
-
-	// 	let acc = rpc_client.create_mint_account(&wallet_keypair, &mint_acc, None).unwrap();
-
-	// 	let acc_balance = rpc_client.get_balance(&acc);
-
-	// 	assert_eq!(acc_balance, Ok(1461600));
-	// }
-
-	// #[test]
-	// fn test_create_token_account() {
-	// 	let rpc_client: NftClient = NftClient::new().unwrap();
-		
-	// 	let wallet_keypair = get_wallet();
-
-	// 	let account_mint_to = Keypair::new();
-
-	// 	let mint_acc = Keypair::new();
-
-	// 	let mint_acc_pubkey = rpc_client.create_mint_account(&wallet_keypair, &mint_acc, None).unwrap();
-	// 	let token_acc_pubkey = rpc_client.create_token_account(&wallet_keypair, &account_mint_to, &mint_acc_pubkey).unwrap();
 
-	// 	let token_balance = rpc_client.get_balance(&token_acc_pubkey);
-
-	// 	assert_eq!(token_balance, Ok(2039280));
-	// }
-
-	// #[test]
-	// fn test_create_alloy_data_accounts() {
-	// 	let rpc_client = NftClient::new().unwrap();
+	#[test]
+	fn test_decode_alloy_events() {
+		use alloy_token_program::event::AlloyEvent;
+		use base64::{ engine::general_purpose::STANDARD, Engine };
+		use crate::cl_events::decode_alloy_events;
+
+		let program_id = alloy_token_program::id();
+		let other_program_id = Pubkey::new_unique();
+
+		let created = AlloyEvent::Created {
+			id: 1,
+			alloy: Pubkey::new_unique(),
+			owner_address: Pubkey::new_unique(),
+			listed_price: 1_500_000_000,
+		};
+		let price_updated = AlloyEvent::PriceUpdated { id: 1, old_price: 1_500_000_000, new_price: 2_000_000_000 };
+		let foreign = AlloyEvent::Burned { id: 1 };
+
+		let data = |event: &AlloyEvent| format!("Program data: {}", STANDARD.encode(borsh::to_vec(event).unwrap()));
+
+		let logs = vec![
+			format!("Program {} invoke [1]", program_id),
+			"Program log: Instruction: Create Alloy Data Accounts".to_string(),
+			format!("Program {} invoke [2]", other_program_id),
+			data(&foreign),
+			format!("Program {} success", other_program_id),
+			data(&created),
+			format!("Program {} consumed 12345 of 200000 compute units", program_id),
+			format!("Program {} success", program_id),
+			data(&foreign),
+			format!("Program {} invoke [1]", program_id),
+			"Program data: not-base64".to_string(),
+			data(&price_updated),
+			format!("Program {} success", program_id),
+		];
+
+		assert_eq!(decode_alloy_events(&program_id, &logs), vec![created, price_updated]);
+	}
+	#[test]
+	fn test_decode_alloy_error() {
+		use alloy_token_program::error::CustomError as AlloyError;
+		use solana_sdk::{ instruction::InstructionError, transaction::TransactionError };
+		use crate::cl_errors::decode_alloy_error;
 
-	// 	let mint_acc = Keypair::new();
-	// 	println!("--> Mint Account: {}", &mint_acc.pubkey());
+		let error = TransactionError::InstructionError(0, InstructionError::Custom(AlloyError::InvalidAlloyDataKey as u32));
+		assert_eq!(decode_alloy_error(&error), Some(AlloyError::InvalidAlloyDataKey));
 
-	// 	let _ = rpc_client.airdrop(&mint_acc.pubkey(), 2_000_000_000);
+		let error = TransactionError::InstructionError(1, InstructionError::Custom(AlloyError::AccountNotWritable as u32));
+		assert_eq!(decode_alloy_error(&error), Some(AlloyError::AccountNotWritable));
 
-	// 	let wallet_keypair = get_wallet();
-	// 	let _ = rpc_client.airdrop(&wallet_keypair.pubkey(), 2_000_000_000);
-	// 	let name = "20MnCr5".to_string();
-	// 	let symbol = "ALLOY".to_string();
-	// 	let uri = "20MnCr5".to_string();
-	// 	let listed_price = 1.5;
+		let unknown = TransactionError::InstructionError(0, InstructionError::Custom(u32::MAX));
+		assert_eq!(decode_alloy_error(&unknown), None);
 
-	// 	let got = rpc_client.create_alloy_data_accounts(
-	// 		name,
-	// 		symbol,
-	// 		uri,
-	// 		listed_price,
-	// 		&wallet_keypair,
-	// 		&mint_acc.pubkey(),
-	// 	);
+		let not_custom = TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature);
+		assert_eq!(decode_alloy_error(&not_custom), None);
 
-	// 	let got_latest_id = got.unwrap().0.id as usize;
+		assert_eq!(decode_alloy_error(&TransactionError::AccountInUse), None);
+	}
 
-	// 	let wanted_latest_id = rpc_client.get_total_nfts().unwrap();
+	fn alloy_with_metadata(uri: &str, document: &[u8]) -> alloy_token_program::state::AlloyData {
+		use alloy_token_program::state::{ AlloyData, MAX_DATA_SIZE };
+		use crate::cl_metadata::metadata_hash;
 
-	// 	assert_eq!(got_latest_id, wanted_latest_id);
-	// }
+		let mut alloy_data = AlloyData::unpack(&[0; MAX_DATA_SIZE]).unwrap();
+		alloy_data.set_uri(uri).unwrap();
+		alloy_data.uri_hash = metadata_hash(document);
+		alloy_data
+	}
 
-	// #[test]
-	// fn test_get_tokens_by_owner() {
-	// 	let rpc_client = NftClient::new().unwrap();
+	#[test]
+	fn test_verify_metadata_from_file() {
+		use crate::cl_metadata::{ verify_metadata, FileMetadataSource, MetadataVerification };
 
-	// 	let got = rpc_client.get_tokens_by_owner(&Pubkey::from_str("4fctzAy7Ay37bz5KfaEfJMVMdRyPXRaU76pWyeiPvTxi").unwrap()).unwrap();
+		let root = std::env::temp_dir().join(format!("alloy-metadata-{}", std::process::id()));
+		std::fs::create_dir_all(root.join("alloys")).unwrap();
+		std::fs::write(root.join("alloys/20MnCr5.json"), br#"{"name":"20MnCr5"}"#).unwrap();
+		let source = FileMetadataSource::new(&root);
 
-	// 	assert_eq!(2, 5);
-	// }
+		let alloy_data = alloy_with_metadata("https://alloys/20MnCr5.json", br#"{"name":"20MnCr5"}"#);
+		assert_eq!(verify_metadata(&source, &alloy_data), Ok(MetadataVerification::Verified));
 
-	// #[test]
-	// fn test_get_all_alloys() {
-	// 	let rpc_client = NftClient::new().unwrap();
+		let tampered = alloy_with_metadata("https://alloys/20MnCr5.json", br#"{"name":"SAE 8620"}"#);
+		assert!(matches!(verify_metadata(&source, &tampered), Ok(MetadataVerification::Mismatch { .. })));
 
-	// 	let got = rpc_client.get_all_alloys();
+		let missing = alloy_with_metadata("https://alloys/missing.json", b"");
+		assert!(verify_metadata(&source, &missing).is_err());
 
-	// 	assert_eq!(2, 5);
-	// }
+		std::fs::remove_dir_all(root).unwrap();
+	}
 
 	#[test]
-	fn test_get_all_owners_by_alloy_uri() {
-		let rpc_client = NftClient::new().unwrap();
-
-		let got = rpc_client.get_all_owners_by_alloy_uri("sae8620".to_string());
+	fn test_verify_metadata_over_http() {
+		use std::{ io::{ Read, Write }, net::TcpListener };
+		use solana_sdk::hash::Hash;
+		use crate::cl_metadata::{ metadata_hash, verify_metadata, HttpMetadataSource, MetadataVerification };
+
+		let document = br#"{"name":"20MnCr5"}"#;
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let gateway = format!("http://{}/ipfs/", listener.local_addr().unwrap());
+
+		let server = std::thread::spawn(move || {
+			for _ in 0..2 {
+				let (mut stream, _) = listener.accept().unwrap();
+				let mut request = [0; 1024];
+				let len = stream.read(&mut request).unwrap();
+				assert!(String::from_utf8_lossy(&request[..len]).starts_with("GET /ipfs/bafy20MnCr5 "));
+
+				write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", document.len()).unwrap();
+				stream.write_all(document).unwrap();
+			}
+		});
+
+		let source = HttpMetadataSource::with_gateways(&gateway, "http://127.0.0.1:0/");
+
+		let alloy_data = alloy_with_metadata("ipfs://bafy20MnCr5", document);
+		assert_eq!(verify_metadata(&source, &alloy_data), Ok(MetadataVerification::Verified));
+
+		let tampered = alloy_with_metadata("ipfs://bafy20MnCr5", br#"{"name":"SAE 8620"}"#);
+		assert_eq!(verify_metadata(&source, &tampered), Ok(MetadataVerification::Mismatch {
+			expected: Hash::new_from_array(tampered.uri_hash),
+			actual: Hash::new_from_array(metadata_hash(document)),
+		}));
+
+		server.join().unwrap();
+	}
 
-		assert_eq!(2, 5);
+	#[test]
+	fn test_composition_query() {
+		use alloy_token_program::state::{ AlloyComposition, ElementComposition, COMPOSITION_DATA_SIZE };
+		use crate::cl_composition::{ bps_from_percent, CompositionQuery };
+
+		let mut composition = AlloyComposition::unpack(&[0; COMPOSITION_DATA_SIZE]).unwrap();
+		composition.set_elements(&[
+			ElementComposition::new("C", 17, 22, 20),
+			ElementComposition::new("Mn", 110, 140, 125),
+			ElementComposition::new("Cr", 80, 130, 95),
+		]).unwrap();
+
+		assert_eq!(bps_from_percent(1.1), 110);
+		assert_eq!(bps_from_percent(120.0), 10_000);
+
+		assert!(CompositionQuery::new().matches(&composition));
+		assert!(CompositionQuery::new().element("Cr", 0.8, 1.1).matches(&composition));
+		assert!(CompositionQuery::new().element("Cr", 0.8, 1.1).element_bps("C", 20, 20).matches(&composition));
+		assert!(!CompositionQuery::new().element("Cr", 1.0, 1.3).matches(&composition));
+		assert!(!CompositionQuery::new().element("Ni", 0.0, 100.0).matches(&composition));
+	}
+	#[test]
+	fn test_validate_custody_chain() {
+		use alloy_token_program::state::{ CustodyEntry, CustodyLog, CUSTODY_LOG_DATA_SIZE };
+		use crate::cl_custody::{ validate_custody_chain, CustodyChainError };
+
+		let (vault, refiner, carrier) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+		let handoff = |from, to, timestamp| CustodyEntry { from, to, timestamp, ..CustodyEntry::default() };
+		let mut entries = vec![
+			handoff(Pubkey::default(), refiner, 100),
+			handoff(refiner, carrier, 200),
+			handoff(carrier, vault, 300),
+		];
+
+		let mut log = CustodyLog::unpack(&[0; CUSTODY_LOG_DATA_SIZE]).unwrap();
+		log.entry_count = 3;
+		log.current_custodian = vault;
+
+		assert_eq!(validate_custody_chain(&log, &entries), Ok(()));
+		assert_eq!(validate_custody_chain(&log, &entries[..2]), Err(CustodyChainError::EntryCountMismatch { expected: 3, found: 2 }));
+
+		log.current_custodian = carrier;
+		assert_eq!(validate_custody_chain(&log, &entries), Err(CustodyChainError::HeadMismatch { expected: carrier, found: vault }));
+		log.current_custodian = vault;
+
+		entries[2].timestamp = 150;
+		assert_eq!(validate_custody_chain(&log, &entries), Err(CustodyChainError::OutOfOrder { index: 2 }));
+
+		entries[2] = handoff(refiner, vault, 300);
+		assert_eq!(validate_custody_chain(&log, &entries), Err(CustodyChainError::Gap { index: 2, expected: carrier, found: refiner }));
 	}
 }
\ No newline at end of file
@@ -0,0 +1,1384 @@
+use solana_program::{
+	account_info::AccountInfo,
+	instruction::AccountMeta,
+	program_error::ProgramError,
+	pubkey::Pubkey,
+	system_program,
+	sysvar,
+};
+use crate::{
+	state::{
+		AlloyComposition,
+		AlloyData,
+		Bundle,
+		CustodyLog,
+		InstallmentPlan,
+		Redemption,
+		Rental,
+		Swap,
+		Vault,
+		ATTESTATION_SEED,
+		BUNDLE_SEED,
+		CONFIG_SEED,
+		CUSTODY_SEED,
+		ESCROW_SEED,
+		HEAT_SEED,
+		INSTALLMENT_SEED,
+		MAX_PROVENANCE_CODE_LENGTH,
+		PREFIX,
+		REDEMPTION_SEED,
+		REGISTRY_SEED,
+		RENTAL_SEED,
+		ROLE_ATTESTER,
+		ROLE_CUSTODIAN,
+		ROLE_INSPECTOR,
+		SWAP_SEED,
+		VAULT_SEED,
+	},
+	validation::{
+		next_validated_account,
+		assert_alloy_data_key,
+		assert_canonical_alloy_data_key,
+		assert_canonical_composition_key,
+		assert_composition_key,
+		assert_canonical_program_address,
+		assert_program_address,
+		assert_authority,
+		assert_registered,
+		assert_uninitialized,
+		Constraint,
+		Constraint::*,
+	},
+	error::CustomError,
+};
+
+/// Declares the account list of an instruction once.
+///
+/// The generated struct is generic over the account type: instruction builders fill it with
+/// `Pubkey`s and call `to_account_metas`, while processors get it back from
+/// `from_account_infos`, which takes the accounts in the same order and checks each one against
+/// its constraints. Signer and writable flags of the metas are derived from those constraints.
+macro_rules! instruction_accounts {
+	(
+		$(#[$meta:meta])*
+		pub struct $name:ident($program_id:ident) {
+			$(
+				$(#[$field_meta:meta])*
+				$field:ident: [$($constraint:expr),* $(,)?]
+			),* $(,)?
+		}
+	) => {
+		$(#[$meta])*
+		#[derive(Debug, Default, Clone, Copy, PartialEq)]
+		pub struct $name<T> {
+			$(
+				$(#[$field_meta])*
+				pub $field: T,
+			)*
+		}
+
+		impl $name<Pubkey> {
+			/// Field names in account order.
+			pub const NAMES: &'static [&'static str] = &[$( stringify!($field) ),*];
+
+			#[allow(unused_variables)]
+			pub fn to_account_metas(&self, $program_id: &Pubkey) -> Vec<AccountMeta> {
+				vec![
+					$( account_meta(self.$field, &[$($constraint),*]), )*
+				]
+			}
+		}
+
+		impl<'a, 'info> $name<&'a AccountInfo<'info>> {
+			#[allow(unused_variables)]
+			pub fn from_account_infos($program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+				let account_iter = &mut accounts.iter();
+
+				Ok(Self {
+					$( $field: next_validated_account(account_iter, &[$($constraint),*])?, )*
+				})
+			}
+
+			pub fn keys(&self) -> $name<Pubkey> {
+				$name {
+					$( $field: *self.$field.key, )*
+				}
+			}
+		}
+	};
+}
+
+fn account_meta(pubkey: Pubkey, constraints: &[Constraint]) -> AccountMeta {
+	let is_signer = constraints.iter().any(|constraint| matches!(constraint, Signer));
+	let is_writable = constraints.iter().any(|constraint| matches!(constraint, Writable));
+
+	if is_writable {
+		AccountMeta::new(pubkey, is_signer)
+	} else {
+		AccountMeta::new_readonly(pubkey, is_signer)
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CreateAlloyDataAccount`.
+	pub struct CreateAlloyDataAccountAccounts(program_id) {
+		alloy_data: [Writable, Uninitialized],
+		heat_index: [Writable],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::UpdateAlloyPrice`.
+	pub struct UpdateAlloyPriceAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		owner: [Signer],
+		owner_nft_token_account: [Owner(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::PurchaseAlloy`.
+	pub struct PurchaseAlloyAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		payer: [Signer, Writable],
+		nft_owner: [Writable],
+		nft_token_account: [Owner(&spl_token::id())],
+		system_program: [Program(&system_program::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CreateAlloyComposition`. The alloy's NFT holder acts as
+	/// its update authority.
+	pub struct CreateAlloyCompositionAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		composition: [Writable, Uninitialized],
+		authority: [Signer],
+		authority_nft_token_account: [Owner(&spl_token::id())],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::UpdateAlloyComposition`.
+	pub struct UpdateAlloyCompositionAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		composition: [Writable, Owner(program_id)],
+		authority: [Signer],
+		authority_nft_token_account: [Owner(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::InitializeConfig`.
+	pub struct InitializeConfigAccounts(program_id) {
+		config: [Writable, Uninitialized],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::AddRegistryEntry`.
+	pub struct AddRegistryEntryAccounts(program_id) {
+		config: [Owner(program_id)],
+		authority: [Signer],
+		entry: [Writable, Uninitialized],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::RemoveRegistryEntry`. The entry's lamports go to
+	/// `recipient`.
+	pub struct RemoveRegistryEntryAccounts(program_id) {
+		config: [Owner(program_id)],
+		authority: [Signer],
+		entry: [Writable, Owner(program_id)],
+		recipient: [Writable],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CreateAttestation`. `attester_entry` enrols the attester
+	/// in the attester registry.
+	pub struct CreateAttestationAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		attestation: [Writable, Uninitialized],
+		attester: [Signer],
+		attester_entry: [],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CertifyAlloy`. `inspector_entry` enrols the inspector in
+	/// the inspector registry.
+	pub struct CertifyAlloyAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		inspector: [Signer],
+		inspector_entry: [],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::RequestRedemption`. The holder's NFT moves into
+	/// `escrow`, a token account owned by the redemption PDA.
+	pub struct RequestRedemptionAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		redemption: [Writable, Uninitialized],
+		escrow: [Writable, Uninitialized],
+		mint: [Owner(&spl_token::id())],
+		holder: [Signer],
+		holder_token_account: [Writable, Owner(&spl_token::id())],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for the custodian's `NftInstruction::AcknowledgeRedemption` and
+	/// `NftInstruction::MarkShipped`. `custodian_entry` enrols the custodian in the custodian
+	/// registry.
+	pub struct RedemptionStepAccounts(program_id) {
+		redemption: [Writable, Owner(program_id)],
+		custodian: [Signer],
+		custodian_entry: [],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::ConfirmDelivery`. The escrowed NFT is burned and the
+	/// escrow's rent returned to `holder`.
+	pub struct ConfirmDeliveryAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		redemption: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		mint: [Writable, Owner(&spl_token::id())],
+		holder: [Writable],
+		custodian: [Signer],
+		custodian_entry: [],
+		token_program: [Program(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CancelRedemption`. The NFT goes back to
+	/// `holder_token_account` and the rent of both closed accounts to `holder`.
+	pub struct CancelRedemptionAccounts(program_id) {
+		redemption: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		holder: [Signer, Writable],
+		holder_token_account: [Writable, Owner(&spl_token::id())],
+		token_program: [Program(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::RecordCustody`. `custody_log` and `custody_page` are
+	/// created on first use, so they carry no owner constraint; `parse` checks them instead.
+	pub struct RecordCustodyAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		custody_log: [Writable],
+		custody_page: [Writable],
+		custodian: [Signer],
+		custodian_entry: [],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::SplitAlloy`. The parent's composition is copied into
+	/// `child_composition` when it exists; both keys are checked either way. `child_mint` and
+	/// `holder_child_token_account` are fresh keypairs the instruction creates, so the child
+	/// NFT cannot already back another alloy.
+	pub struct SplitAlloyAccounts(program_id) {
+		parent_alloy_data: [Writable, Owner(program_id)],
+		child_alloy_data: [Writable, Uninitialized],
+		parent_composition: [],
+		child_composition: [Writable, Uninitialized],
+		child_mint: [Writable, Signer, Uninitialized],
+		holder: [Signer],
+		holder_token_account: [Owner(&spl_token::id())],
+		holder_child_token_account: [Writable, Signer, Uninitialized],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::MergeAlloys`. The holder must hold both NFTs; the
+	/// source's is burned. Both compositions are compared when they exist; their keys are
+	/// checked either way.
+	pub struct MergeAlloysAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		source_alloy_data: [Writable, Owner(program_id)],
+		composition: [],
+		source_composition: [],
+		source_mint: [Writable, Owner(&spl_token::id())],
+		holder: [Signer],
+		holder_token_account: [Owner(&spl_token::id())],
+		holder_source_token_account: [Writable, Owner(&spl_token::id())],
+		token_program: [Program(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CreateVault`. `share_mint` is created by the client
+	/// beforehand with the vault as its mint authority; every share is minted to
+	/// `curator_share_token_account`.
+	pub struct CreateVaultAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		vault: [Writable, Uninitialized],
+		escrow: [Writable, Uninitialized],
+		mint: [Owner(&spl_token::id())],
+		share_mint: [Writable, Owner(&spl_token::id())],
+		curator: [Signer],
+		curator_token_account: [Writable, Owner(&spl_token::id())],
+		curator_share_token_account: [Writable, Owner(&spl_token::id())],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::RedeemVault`. The NFT goes to `holder_token_account` and
+	/// the rent of the vault and its escrow to `holder`.
+	pub struct RedeemVaultAccounts(program_id) {
+		vault: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		share_mint: [Writable, Owner(&spl_token::id())],
+		holder: [Signer, Writable],
+		holder_share_token_account: [Writable, Owner(&spl_token::id())],
+		holder_token_account: [Writable, Owner(&spl_token::id())],
+		token_program: [Program(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::BuyoutVault`. The NFT goes to `buyer_token_account` and
+	/// the escrow's rent to `buyer`.
+	pub struct BuyoutVaultAccounts(program_id) {
+		vault: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		buyer: [Signer, Writable],
+		buyer_token_account: [Writable, Owner(&spl_token::id())],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::ClaimVaultProceeds`. Whoever claims the last shares also
+	/// receives the vault's rent.
+	pub struct ClaimVaultProceedsAccounts(program_id) {
+		vault: [Writable, Owner(program_id)],
+		share_mint: [Writable, Owner(&spl_token::id())],
+		holder: [Signer, Writable],
+		holder_share_token_account: [Writable, Owner(&spl_token::id())],
+		token_program: [Program(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CreateRental`. The lessor must hold the alloy's NFT and
+	/// approves `rental` as delegate for it; `escrow` is created empty, owned by `rental`.
+	pub struct CreateRentalAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		rental: [Writable, Uninitialized],
+		escrow: [Writable, Uninitialized],
+		mint: [Owner(&spl_token::id())],
+		lessor: [Signer],
+		lessor_token_account: [Writable, Owner(&spl_token::id())],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::RentAlloy`. The renter pays `lessor`, whose NFT moves
+	/// from `lessor_token_account` into `escrow` unless an earlier rental left it there.
+	pub struct RentAlloyAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		rental: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		lessor: [Writable],
+		lessor_token_account: [Writable, Owner(&spl_token::id())],
+		renter: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::EndRental`. An escrowed NFT goes back to
+	/// `lessor_token_account` and the rent of `rental` and `escrow` to `lessor`.
+	pub struct EndRentalAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		rental: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		authority: [Signer],
+		lessor: [Writable],
+		lessor_token_account: [Writable, Owner(&spl_token::id())],
+		token_program: [Program(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CreateInstallmentPlan`. The seller's NFT moves into
+	/// `escrow`, a token account owned by the plan.
+	pub struct CreateInstallmentPlanAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		plan: [Writable, Uninitialized],
+		escrow: [Writable, Uninitialized],
+		mint: [Owner(&spl_token::id())],
+		seller: [Signer],
+		seller_token_account: [Writable, Owner(&spl_token::id())],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::PayInstallment`. The final payment sends the NFT to
+	/// `buyer_token_account` and everything the plan holds to `seller`.
+	pub struct PayInstallmentAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		plan: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		seller: [Writable],
+		buyer: [Signer, Writable],
+		buyer_token_account: [Writable, Owner(&spl_token::id())],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::ReclaimInstallmentPlan`. The NFT goes back to
+	/// `seller_token_account`, the refund to `buyer` and the rest to `seller`.
+	pub struct ReclaimInstallmentPlanAccounts(program_id) {
+		plan: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		seller: [Signer, Writable],
+		seller_token_account: [Writable, Owner(&spl_token::id())],
+		buyer: [Writable],
+		token_program: [Program(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CreateBundle`, followed by one
+	/// `CreateBundleLotAccounts` per alloy in the order of its id in the args.
+	pub struct CreateBundleAccounts(program_id) {
+		bundle: [Writable, Uninitialized],
+		seller: [Signer],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts of one alloy in `NftInstruction::CreateBundle`. The seller's NFT moves into
+	/// `escrow`, a token account owned by the bundle.
+	pub struct CreateBundleLotAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		mint: [Owner(&spl_token::id())],
+		seller_token_account: [Writable, Owner(&spl_token::id())],
+		escrow: [Writable, Uninitialized],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::PurchaseBundle`, followed by one
+	/// `PurchaseBundleLotAccounts` per alloy in the bundle's order.
+	pub struct PurchaseBundleAccounts(program_id) {
+		bundle: [Writable, Owner(program_id)],
+		seller: [Writable],
+		buyer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts of one alloy in `NftInstruction::PurchaseBundle`. The NFT goes to
+	/// `buyer_token_account`.
+	pub struct PurchaseBundleLotAccounts(program_id) {
+		alloy_data: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		buyer_token_account: [Writable, Owner(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CancelBundle`, followed by one
+	/// `CancelBundleLotAccounts` per alloy in the bundle's order.
+	pub struct CancelBundleAccounts(program_id) {
+		bundle: [Writable, Owner(program_id)],
+		seller: [Signer, Writable],
+		token_program: [Program(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts of one alloy in `NftInstruction::CancelBundle`. The NFT goes back to
+	/// `seller_token_account`.
+	pub struct CancelBundleLotAccounts(program_id) {
+		escrow: [Writable, Owner(&spl_token::id())],
+		seller_token_account: [Writable, Owner(&spl_token::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::ProposeSwap`. The proposer's NFT moves into `escrow`, a
+	/// token account owned by the swap.
+	pub struct ProposeSwapAccounts(program_id) {
+		alloy_data: [Owner(program_id)],
+		wanted_alloy_data: [Owner(program_id)],
+		swap: [Writable, Uninitialized],
+		escrow: [Writable, Uninitialized],
+		mint: [Owner(&spl_token::id())],
+		proposer: [Signer],
+		proposer_token_account: [Writable, Owner(&spl_token::id())],
+		payer: [Signer, Writable],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		rent: [Sysvar(&sysvar::rent::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::AcceptSwap`. The wanted NFT moves from
+	/// `counterparty_token_account` to `proposer_token_account` and the escrowed one to
+	/// `counterparty_offered_token_account`.
+	pub struct AcceptSwapAccounts(program_id) {
+		wanted_alloy_data: [Owner(program_id)],
+		swap: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		proposer: [Writable],
+		proposer_token_account: [Writable, Owner(&spl_token::id())],
+		counterparty: [Signer, Writable],
+		counterparty_token_account: [Writable, Owner(&spl_token::id())],
+		counterparty_offered_token_account: [Writable, Owner(&spl_token::id())],
+		system_program: [Program(&system_program::id())],
+		token_program: [Program(&spl_token::id())],
+		clock: [Sysvar(&sysvar::clock::id())],
+	}
+}
+
+instruction_accounts! {
+	/// Accounts for `NftInstruction::CancelSwap`. The NFT goes back to
+	/// `proposer_token_account`.
+	pub struct CancelSwapAccounts(program_id) {
+		swap: [Writable, Owner(program_id)],
+		escrow: [Writable, Owner(&spl_token::id())],
+		proposer: [Signer, Writable],
+		proposer_token_account: [Writable, Owner(&spl_token::id())],
+		token_program: [Program(&spl_token::id())],
+	}
+}
+
+impl<'a, 'info> CreateAlloyDataAccountAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts, checks `alloy_data` is the canonical PDA for `id` and
+	/// `heat_index` the canonical PDA for the zero-padded `mill` and `heat_number`, and that
+	/// the heat has not been tokenized yet. Returns both bumps alongside.
+	pub fn parse(
+		program_id: &Pubkey,
+		accounts: &'a [AccountInfo<'info>],
+		id: u8,
+		mill: &[u8; MAX_PROVENANCE_CODE_LENGTH],
+		heat_number: &[u8; MAX_PROVENANCE_CODE_LENGTH],
+	) -> Result<(Self, u8, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let alloy_data_bump = assert_canonical_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let heat_index_bump = assert_canonical_program_address(
+			program_id,
+			accounts.heat_index,
+			&[PREFIX.as_bytes(), program_id.as_ref(), HEAT_SEED.as_bytes(), mill, heat_number],
+			CustomError::InvalidHeatIndexKey,
+		)?;
+
+		if assert_uninitialized(accounts.heat_index).is_err() {
+			return Err(CustomError::HeatAlreadyTokenized.into());
+		}
+
+		Ok((accounts, alloy_data_bump, heat_index_bump))
+	}
+}
+
+impl<'a, 'info> UpdateAlloyPriceAccounts<&'a AccountInfo<'info>> {
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> PurchaseAlloyAccounts<&'a AccountInfo<'info>> {
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CreateAlloyCompositionAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `composition` is the canonical PDA for `id`, returning
+	/// its bump alongside.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<(Self, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let composition_bump = assert_canonical_composition_key(program_id, accounts.composition, id)?;
+
+		Ok((accounts, composition_bump))
+	}
+}
+
+impl<'a, 'info> UpdateAlloyCompositionAccounts<&'a AccountInfo<'info>> {
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+
+		let bump = AlloyComposition::load(&accounts.composition.data.borrow())?.bump;
+		assert_composition_key(program_id, accounts.composition, id, bump)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> InitializeConfigAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `config` is the config PDA, returning its bump.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<(Self, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let config_bump = assert_canonical_program_address(
+			program_id,
+			accounts.config,
+			&[PREFIX.as_bytes(), program_id.as_ref(), CONFIG_SEED.as_bytes()],
+			CustomError::InvalidConfigKey,
+		)?;
+
+		Ok((accounts, config_bump))
+	}
+}
+
+impl<'a, 'info> AddRegistryEntryAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts, checks the signer is the program authority and `entry` is the
+	/// canonical PDA for `role` and `member`, returning its bump.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], role: u8, member: &Pubkey) -> Result<(Self, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_authority(program_id, accounts.config, accounts.authority)?;
+		let entry_bump = assert_canonical_program_address(
+			program_id,
+			accounts.entry,
+			&[PREFIX.as_bytes(), program_id.as_ref(), REGISTRY_SEED.as_bytes(), &[role], member.as_ref()],
+			CustomError::InvalidRegistryEntryKey,
+		)?;
+
+		Ok((accounts, entry_bump))
+	}
+}
+
+impl<'a, 'info> RemoveRegistryEntryAccounts<&'a AccountInfo<'info>> {
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], role: u8, member: &Pubkey) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_authority(program_id, accounts.config, accounts.authority)?;
+		assert_registered(program_id, accounts.entry, role, member)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CreateAttestationAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts, checks the attester is registered and `attestation` is the
+	/// canonical PDA for the alloy, attester and `nonce`, returning its bump.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8, nonce: u64) -> Result<(Self, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		assert_registered(program_id, accounts.attester_entry, ROLE_ATTESTER, accounts.attester.key)?;
+		let attestation_bump = assert_canonical_program_address(
+			program_id,
+			accounts.attestation,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], ATTESTATION_SEED.as_bytes(), accounts.attester.key.as_ref(), &nonce.to_le_bytes()],
+			CustomError::InvalidAttestationKey,
+		)?;
+
+		Ok((accounts, attestation_bump))
+	}
+}
+
+impl<'a, 'info> CertifyAlloyAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks the inspector is registered.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		assert_registered(program_id, accounts.inspector_entry, ROLE_INSPECTOR, accounts.inspector.key)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> RequestRedemptionAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `redemption` and `escrow` are the canonical PDAs for
+	/// `id`, returning their bumps alongside.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<(Self, u8, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let redemption_bump = assert_canonical_program_address(
+			program_id,
+			accounts.redemption,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], REDEMPTION_SEED.as_bytes()],
+			CustomError::InvalidRedemptionKey,
+		)?;
+		let escrow_bump = assert_canonical_program_address(
+			program_id,
+			accounts.escrow,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], REDEMPTION_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+			CustomError::InvalidEscrowKey,
+		)?;
+
+		Ok((accounts, redemption_bump, escrow_bump))
+	}
+}
+
+impl<'a, 'info> RecordCustodyAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts, checks the custodian is registered and `custody_log` and
+	/// `custody_page` are the canonical PDAs for `id` and the page the next entry falls
+	/// into. Returns the log bump, the page index and the page bump alongside.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<(Self, u8, u32, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		assert_registered(program_id, accounts.custodian_entry, ROLE_CUSTODIAN, accounts.custodian.key)?;
+		let log_bump = assert_canonical_program_address(
+			program_id,
+			accounts.custody_log,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], CUSTODY_SEED.as_bytes()],
+			CustomError::InvalidCustodyLogKey,
+		)?;
+
+		let page = if accounts.custody_log.owner == program_id {
+			CustodyLog::load(&accounts.custody_log.data.borrow())?.next_page()
+		} else {
+			assert_uninitialized(accounts.custody_log)?;
+			0
+		};
+
+		let page_bump = assert_canonical_program_address(
+			program_id,
+			accounts.custody_page,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], CUSTODY_SEED.as_bytes(), &page.to_le_bytes()],
+			CustomError::InvalidCustodyLogKey,
+		)?;
+
+		Ok((accounts, log_bump, page, page_bump))
+	}
+}
+
+impl<'a, 'info> SplitAlloyAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `child_alloy_data` and both compositions are the
+	/// canonical PDAs of their alloys, returning the child alloy and child composition bumps.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8, child_id: u8) -> Result<(Self, u8, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.parent_alloy_data, id)?;
+		let child_alloy_data_bump = assert_canonical_alloy_data_key(program_id, accounts.child_alloy_data, child_id)?;
+		assert_canonical_composition_key(program_id, accounts.parent_composition, id)?;
+		let child_composition_bump = assert_canonical_composition_key(program_id, accounts.child_composition, child_id)?;
+
+		Ok((accounts, child_alloy_data_bump, child_composition_bump))
+	}
+}
+
+impl<'a, 'info> MergeAlloysAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks both alloy data accounts against the bumps they store
+	/// and both compositions are the canonical PDAs of their alloys.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8, source_id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		assert_existing_alloy_data_key(program_id, accounts.source_alloy_data, source_id)?;
+		assert_canonical_composition_key(program_id, accounts.composition, id)?;
+		assert_canonical_composition_key(program_id, accounts.source_composition, source_id)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CreateVaultAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `vault` and `escrow` are the canonical PDAs for `id`,
+	/// returning their bumps alongside.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<(Self, u8, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let vault_bump = assert_canonical_program_address(
+			program_id,
+			accounts.vault,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], VAULT_SEED.as_bytes()],
+			CustomError::InvalidVaultKey,
+		)?;
+		let escrow_bump = assert_canonical_program_address(
+			program_id,
+			accounts.escrow,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], VAULT_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+			CustomError::InvalidEscrowKey,
+		)?;
+
+		Ok((accounts, vault_bump, escrow_bump))
+	}
+}
+
+impl<'a, 'info> RedeemVaultAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow` and `share_mint` belong to the vault.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let vault = load_vault(program_id, accounts.vault, id)?;
+		assert_vault_escrow_key(program_id, accounts.escrow, &vault)?;
+		assert_share_mint_key(accounts.share_mint, &vault)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> BuyoutVaultAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow` belongs to the vault.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let vault = load_vault(program_id, accounts.vault, id)?;
+		assert_vault_escrow_key(program_id, accounts.escrow, &vault)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> ClaimVaultProceedsAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `share_mint` belongs to the vault.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let vault = load_vault(program_id, accounts.vault, id)?;
+		assert_share_mint_key(accounts.share_mint, &vault)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CreateRentalAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `rental` and `escrow` are the canonical PDAs for `id`,
+	/// returning both bumps alongside.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<(Self, u8, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let rental_bump = assert_canonical_program_address(
+			program_id,
+			accounts.rental,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], RENTAL_SEED.as_bytes()],
+			CustomError::InvalidRentalKey,
+		)?;
+		let escrow_bump = assert_canonical_program_address(
+			program_id,
+			accounts.escrow,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], RENTAL_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+			CustomError::InvalidEscrowKey,
+		)?;
+
+		Ok((accounts, rental_bump, escrow_bump))
+	}
+}
+
+impl<'a, 'info> RentAlloyAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow` and `lessor` belong to the rental.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let rental = load_rental(program_id, accounts.rental, id)?;
+		assert_rental_escrow_key(program_id, accounts.escrow, &rental)?;
+		assert_lessor(accounts.lessor, &rental)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> EndRentalAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow` and `lessor` belong to the rental.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let rental = load_rental(program_id, accounts.rental, id)?;
+		assert_rental_escrow_key(program_id, accounts.escrow, &rental)?;
+		assert_lessor(accounts.lessor, &rental)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CreateInstallmentPlanAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `plan` and `escrow` are the canonical PDAs for `id`,
+	/// returning both bumps alongside.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<(Self, u8, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let plan_bump = assert_canonical_program_address(
+			program_id,
+			accounts.plan,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], INSTALLMENT_SEED.as_bytes()],
+			CustomError::InvalidInstallmentPlanKey,
+		)?;
+		let escrow_bump = assert_canonical_program_address(
+			program_id,
+			accounts.escrow,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], INSTALLMENT_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+			CustomError::InvalidEscrowKey,
+		)?;
+
+		Ok((accounts, plan_bump, escrow_bump))
+	}
+}
+
+impl<'a, 'info> PayInstallmentAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow`, `seller` and `buyer` belong to the plan.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let plan = load_installment_plan(program_id, accounts.plan, id)?;
+		assert_installment_escrow_key(program_id, accounts.escrow, &plan)?;
+		assert_installment_parties(accounts.seller, accounts.buyer, &plan)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> ReclaimInstallmentPlanAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow`, `seller` and `buyer` belong to the plan.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let plan = load_installment_plan(program_id, accounts.plan, id)?;
+		assert_installment_escrow_key(program_id, accounts.escrow, &plan)?;
+		assert_installment_parties(accounts.seller, accounts.buyer, &plan)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CreateBundleAccounts<&'a AccountInfo<'info>> {
+	/// Parses the fixed accounts and one lot per id, checks `bundle` is the canonical PDA for
+	/// the seller and `nonce` and every lot's `alloy_data` and `escrow` are the PDAs for its
+	/// id. Returns the bundle's bump and the escrows' bumps alongside.
+	#[allow(clippy::type_complexity)]
+	pub fn parse(
+		program_id: &Pubkey,
+		accounts: &'a [AccountInfo<'info>],
+		nonce: u64,
+		ids: &[u8],
+	) -> Result<(Self, Vec<CreateBundleLotAccounts<&'a AccountInfo<'info>>>, u8, Vec<u8>), ProgramError> {
+		let fixed = Self::from_account_infos(program_id, accounts)?;
+		let bundle_bump = assert_canonical_program_address(
+			program_id,
+			fixed.bundle,
+			&[PREFIX.as_bytes(), program_id.as_ref(), BUNDLE_SEED.as_bytes(), fixed.seller.key.as_ref(), &nonce.to_le_bytes()],
+			CustomError::InvalidBundleKey,
+		)?;
+		let mut lots = Vec::with_capacity(ids.len());
+		let mut escrow_bumps = Vec::with_capacity(ids.len());
+
+		for (lot, &id) in lot_chunks(accounts, CreateBundleAccounts::<Pubkey>::NAMES.len(), CreateBundleLotAccounts::<Pubkey>::NAMES.len(), ids.len())?.zip(ids) {
+			let lot = CreateBundleLotAccounts::from_account_infos(program_id, lot)?;
+			assert_existing_alloy_data_key(program_id, lot.alloy_data, id)?;
+			escrow_bumps.push(assert_canonical_program_address(
+				program_id,
+				lot.escrow,
+				&[PREFIX.as_bytes(), program_id.as_ref(), &[id], BUNDLE_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+				CustomError::InvalidEscrowKey,
+			)?);
+			lots.push(lot);
+		}
+
+		Ok((fixed, lots, bundle_bump, escrow_bumps))
+	}
+}
+
+impl<'a, 'info> PurchaseBundleAccounts<&'a AccountInfo<'info>> {
+	/// Parses the fixed accounts and the bundle's lots, checks `bundle` belongs to `seller`
+	/// and every lot's `alloy_data` and `escrow` belong to the bundle.
+	#[allow(clippy::type_complexity)]
+	pub fn parse(
+		program_id: &Pubkey,
+		accounts: &'a [AccountInfo<'info>],
+		nonce: u64,
+	) -> Result<(Self, Vec<PurchaseBundleLotAccounts<&'a AccountInfo<'info>>>), ProgramError> {
+		let fixed = Self::from_account_infos(program_id, accounts)?;
+		let bundle = load_bundle(program_id, fixed.bundle, fixed.seller.key, nonce)?;
+		let mut lots = Vec::with_capacity(bundle.ids().len());
+
+		for (index, lot) in lot_chunks(accounts, PurchaseBundleAccounts::<Pubkey>::NAMES.len(), PurchaseBundleLotAccounts::<Pubkey>::NAMES.len(), bundle.ids().len())?.enumerate() {
+			let lot = PurchaseBundleLotAccounts::from_account_infos(program_id, lot)?;
+			assert_existing_alloy_data_key(program_id, lot.alloy_data, bundle.ids[index])?;
+			assert_bundle_escrow_key(program_id, lot.escrow, &bundle, index)?;
+			lots.push(lot);
+		}
+
+		Ok((fixed, lots))
+	}
+}
+
+impl<'a, 'info> CancelBundleAccounts<&'a AccountInfo<'info>> {
+	/// Parses the fixed accounts and the bundle's lots, checks `bundle` belongs to `seller`
+	/// and every lot's `escrow` belongs to the bundle.
+	#[allow(clippy::type_complexity)]
+	pub fn parse(
+		program_id: &Pubkey,
+		accounts: &'a [AccountInfo<'info>],
+		nonce: u64,
+	) -> Result<(Self, Vec<CancelBundleLotAccounts<&'a AccountInfo<'info>>>), ProgramError> {
+		let fixed = Self::from_account_infos(program_id, accounts)?;
+		let bundle = load_bundle(program_id, fixed.bundle, fixed.seller.key, nonce)?;
+		let mut lots = Vec::with_capacity(bundle.ids().len());
+
+		for (index, lot) in lot_chunks(accounts, CancelBundleAccounts::<Pubkey>::NAMES.len(), CancelBundleLotAccounts::<Pubkey>::NAMES.len(), bundle.ids().len())?.enumerate() {
+			let lot = CancelBundleLotAccounts::from_account_infos(program_id, lot)?;
+			assert_bundle_escrow_key(program_id, lot.escrow, &bundle, index)?;
+			lots.push(lot);
+		}
+
+		Ok((fixed, lots))
+	}
+}
+
+impl<'a, 'info> ProposeSwapAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts, checks both alloy data accounts against their ids and `swap` and
+	/// `escrow` are the canonical PDAs for `id`, returning both bumps alongside.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8, wanted_id: u8) -> Result<(Self, u8, u8), ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		assert_existing_alloy_data_key(program_id, accounts.wanted_alloy_data, wanted_id)?;
+		let swap_bump = assert_canonical_program_address(
+			program_id,
+			accounts.swap,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], SWAP_SEED.as_bytes()],
+			CustomError::InvalidSwapKey,
+		)?;
+		let escrow_bump = assert_canonical_program_address(
+			program_id,
+			accounts.escrow,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], SWAP_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+			CustomError::InvalidEscrowKey,
+		)?;
+
+		Ok((accounts, swap_bump, escrow_bump))
+	}
+}
+
+impl<'a, 'info> AcceptSwapAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `wanted_alloy_data`, `escrow` and `proposer` belong to
+	/// the swap.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let swap = load_swap(program_id, accounts.swap, id)?;
+		assert_existing_alloy_data_key(program_id, accounts.wanted_alloy_data, swap.wanted_id)?;
+		assert_swap_escrow_key(program_id, accounts.escrow, &swap)?;
+		assert_proposer(accounts.proposer, &swap)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CancelSwapAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow` and the signing `proposer` belong to the swap.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let swap = load_swap(program_id, accounts.swap, id)?;
+		assert_swap_escrow_key(program_id, accounts.escrow, &swap)?;
+		assert_proposer(accounts.proposer, &swap)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> RedemptionStepAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks the custodian is registered.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		load_redemption(program_id, accounts.redemption, id)?;
+		assert_registered(program_id, accounts.custodian_entry, ROLE_CUSTODIAN, accounts.custodian.key)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> ConfirmDeliveryAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts, checks the custodian is registered and `escrow` and `holder`
+	/// belong to the redemption.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		assert_existing_alloy_data_key(program_id, accounts.alloy_data, id)?;
+		let redemption = load_redemption(program_id, accounts.redemption, id)?;
+		assert_escrow_key(program_id, accounts.escrow, &redemption)?;
+		assert_redemption_holder(accounts.holder, &redemption)?;
+		assert_registered(program_id, accounts.custodian_entry, ROLE_CUSTODIAN, accounts.custodian.key)?;
+
+		Ok(accounts)
+	}
+}
+
+impl<'a, 'info> CancelRedemptionAccounts<&'a AccountInfo<'info>> {
+	/// Parses the accounts and checks `escrow` and the signing `holder` belong to the
+	/// redemption.
+	pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], id: u8) -> Result<Self, ProgramError> {
+		let accounts = Self::from_account_infos(program_id, accounts)?;
+		let redemption = load_redemption(program_id, accounts.redemption, id)?;
+		assert_escrow_key(program_id, accounts.escrow, &redemption)?;
+		assert_redemption_holder(accounts.holder, &redemption)?;
+
+		Ok(accounts)
+	}
+}
+
+/// Loads a redemption, checking it is the PDA for `id` under the bump it stores.
+fn load_redemption(program_id: &Pubkey, redemption_info: &AccountInfo, id: u8) -> Result<Redemption, ProgramError> {
+	let redemption = *Redemption::load(&redemption_info.data.borrow())?;
+
+	assert_program_address(
+		program_id,
+		redemption_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], REDEMPTION_SEED.as_bytes()],
+		redemption.bump,
+		CustomError::InvalidRedemptionKey,
+	)?;
+
+	Ok(redemption)
+}
+
+fn assert_escrow_key(program_id: &Pubkey, escrow: &AccountInfo, redemption: &Redemption) -> Result<(), ProgramError> {
+	assert_program_address(
+		program_id,
+		escrow,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[redemption.id], REDEMPTION_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+		redemption.escrow_bump,
+		CustomError::InvalidEscrowKey,
+	)
+}
+
+fn assert_redemption_holder(holder: &AccountInfo, redemption: &Redemption) -> Result<(), ProgramError> {
+	if *holder.key != redemption.holder {
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	Ok(())
+}
+
+/// Loads a vault, checking it is the PDA for `id` under the bump it stores.
+fn load_vault(program_id: &Pubkey, vault_info: &AccountInfo, id: u8) -> Result<Vault, ProgramError> {
+	let vault = *Vault::load(&vault_info.data.borrow())?;
+
+	assert_program_address(
+		program_id,
+		vault_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], VAULT_SEED.as_bytes()],
+		vault.bump,
+		CustomError::InvalidVaultKey,
+	)?;
+
+	Ok(vault)
+}
+
+fn assert_vault_escrow_key(program_id: &Pubkey, escrow: &AccountInfo, vault: &Vault) -> Result<(), ProgramError> {
+	assert_program_address(
+		program_id,
+		escrow,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[vault.id], VAULT_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+		vault.escrow_bump,
+		CustomError::InvalidEscrowKey,
+	)
+}
+
+fn assert_share_mint_key(share_mint: &AccountInfo, vault: &Vault) -> Result<(), ProgramError> {
+	if *share_mint.key != vault.share_mint {
+		return Err(CustomError::InvalidShareMint.into());
+	}
+
+	Ok(())
+}
+
+/// Loads a rental, checking it is the PDA for `id` under the bump it stores.
+fn load_rental(program_id: &Pubkey, rental_info: &AccountInfo, id: u8) -> Result<Rental, ProgramError> {
+	let rental = *Rental::load(&rental_info.data.borrow())?;
+
+	assert_program_address(
+		program_id,
+		rental_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], RENTAL_SEED.as_bytes()],
+		rental.bump,
+		CustomError::InvalidRentalKey,
+	)?;
+
+	Ok(rental)
+}
+
+fn assert_rental_escrow_key(program_id: &Pubkey, escrow: &AccountInfo, rental: &Rental) -> Result<(), ProgramError> {
+	assert_program_address(
+		program_id,
+		escrow,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[rental.id], RENTAL_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+		rental.escrow_bump,
+		CustomError::InvalidEscrowKey,
+	)
+}
+
+fn assert_lessor(lessor: &AccountInfo, rental: &Rental) -> Result<(), ProgramError> {
+	if *lessor.key != rental.lessor {
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	Ok(())
+}
+
+/// Loads an installment plan, checking it is the PDA for `id` under the bump it stores.
+fn load_installment_plan(program_id: &Pubkey, plan_info: &AccountInfo, id: u8) -> Result<InstallmentPlan, ProgramError> {
+	let plan = *InstallmentPlan::load(&plan_info.data.borrow())?;
+
+	assert_program_address(
+		program_id,
+		plan_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], INSTALLMENT_SEED.as_bytes()],
+		plan.bump,
+		CustomError::InvalidInstallmentPlanKey,
+	)?;
+
+	Ok(plan)
+}
+
+fn assert_installment_escrow_key(program_id: &Pubkey, escrow: &AccountInfo, plan: &InstallmentPlan) -> Result<(), ProgramError> {
+	assert_program_address(
+		program_id,
+		escrow,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[plan.id], INSTALLMENT_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+		plan.escrow_bump,
+		CustomError::InvalidEscrowKey,
+	)
+}
+
+fn assert_installment_parties(seller: &AccountInfo, buyer: &AccountInfo, plan: &InstallmentPlan) -> Result<(), ProgramError> {
+	if *seller.key != plan.seller || *buyer.key != plan.buyer {
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	Ok(())
+}
+
+/// Loads a bundle, checking it is the PDA for `seller` and `nonce` under the bump it stores.
+fn load_bundle(program_id: &Pubkey, bundle_info: &AccountInfo, seller: &Pubkey, nonce: u64) -> Result<Bundle, ProgramError> {
+	let bundle = *Bundle::load(&bundle_info.data.borrow())?;
+
+	assert_program_address(
+		program_id,
+		bundle_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), BUNDLE_SEED.as_bytes(), seller.as_ref(), &nonce.to_le_bytes()],
+		bundle.bump,
+		CustomError::InvalidBundleKey,
+	)?;
+
+	Ok(bundle)
+}
+
+fn assert_bundle_escrow_key(program_id: &Pubkey, escrow: &AccountInfo, bundle: &Bundle, index: usize) -> Result<(), ProgramError> {
+	assert_program_address(
+		program_id,
+		escrow,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[bundle.ids[index]], BUNDLE_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+		bundle.escrow_bumps[index],
+		CustomError::InvalidEscrowKey,
+	)
+}
+
+/// Loads a swap, checking it is the PDA for `id` under the bump it stores.
+fn load_swap(program_id: &Pubkey, swap_info: &AccountInfo, id: u8) -> Result<Swap, ProgramError> {
+	let swap = *Swap::load(&swap_info.data.borrow())?;
+
+	assert_program_address(
+		program_id,
+		swap_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], SWAP_SEED.as_bytes()],
+		swap.bump,
+		CustomError::InvalidSwapKey,
+	)?;
+
+	Ok(swap)
+}
+
+fn assert_swap_escrow_key(program_id: &Pubkey, escrow: &AccountInfo, swap: &Swap) -> Result<(), ProgramError> {
+	assert_program_address(
+		program_id,
+		escrow,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[swap.id], SWAP_SEED.as_bytes(), ESCROW_SEED.as_bytes()],
+		swap.escrow_bump,
+		CustomError::InvalidEscrowKey,
+	)
+}
+
+fn assert_proposer(proposer: &AccountInfo, swap: &Swap) -> Result<(), ProgramError> {
+	if *proposer.key != swap.proposer {
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	Ok(())
+}
+
+/// Splits the accounts after the first `fixed` into `count` lots of `lot_len` accounts each.
+fn lot_chunks<'a, 'info>(
+	accounts: &'a [AccountInfo<'info>],
+	fixed: usize,
+	lot_len: usize,
+	count: usize,
+) -> Result<std::slice::Chunks<'a, AccountInfo<'info>>, ProgramError> {
+	let lots = accounts.get(fixed..fixed + lot_len * count).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+	Ok(lots.chunks(lot_len))
+}
+
+/// Checks an alloy data account already owned by the program against the bump it stores.
+fn assert_existing_alloy_data_key(program_id: &Pubkey, alloy_data: &AccountInfo, id: u8) -> Result<(), ProgramError> {
+	let bump = AlloyData::load(&alloy_data.data.borrow())?.bump;
+
+	assert_alloy_data_key(program_id, alloy_data, id, bump)
+}
@@ -0,0 +1,60 @@
+//! Kinds of alloy properties labs can attest to, and the units each may be reported in.
+
+use borsh::{ BorshSerialize, BorshDeserialize, BorshSchema };
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use solana_program::entrypoint::ProgramResult;
+use crate::{
+	error::CustomError,
+	state::Attestation,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, FromPrimitive, BorshSerialize, BorshDeserialize, BorshSchema)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum PropertyKind {
+	TensileStrength = 0,
+	YieldStrength = 1,
+	Hardness = 2,
+	Elongation = 3,
+	/// One step of the heat-treatment history, valued at its holding temperature.
+	HeatTreatment = 4,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, FromPrimitive, BorshSerialize, BorshDeserialize, BorshSchema)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum PropertyUnit {
+	Megapascal = 0,
+	Vickers = 1,
+	RockwellC = 2,
+	Brinell = 3,
+	Percent = 4,
+	Celsius = 5,
+}
+
+/// Checks that `unit` is one `kind` can be measured in.
+pub fn validate_property_unit(kind: PropertyKind, unit: PropertyUnit) -> ProgramResult {
+	let valid = match kind {
+		PropertyKind::TensileStrength | PropertyKind::YieldStrength => unit == PropertyUnit::Megapascal,
+		PropertyKind::Hardness => matches!(unit, PropertyUnit::Vickers | PropertyUnit::RockwellC | PropertyUnit::Brinell),
+		PropertyKind::Elongation => unit == PropertyUnit::Percent,
+		PropertyKind::HeatTreatment => unit == PropertyUnit::Celsius,
+	};
+
+	if !valid {
+		return Err(CustomError::InvalidAttestation.into());
+	}
+
+	Ok(())
+}
+
+impl Attestation {
+	pub fn kind(&self) -> Option<PropertyKind> {
+		PropertyKind::from_u8(self.kind)
+	}
+
+	pub fn unit(&self) -> Option<PropertyUnit> {
+		PropertyUnit::from_u8(self.unit)
+	}
+}
@@ -0,0 +1,5 @@
+//! Prints the program IDL: `cargo run --features idl --bin idl > idl/alloy_token_program.json`.
+
+fn main() {
+	print!("{}", alloy_token_program::idl::idl_json());
+}
@@ -0,0 +1,52 @@
+//! Composition of bundle listings and the split of their price across the alloys.
+
+use solana_program::program_error::ProgramError;
+use crate::{
+	error::CustomError,
+	state::{ Bundle, MAX_BUNDLE_SIZE },
+};
+
+/// Checks a bundle holds between two and `MAX_BUNDLE_SIZE` distinct alloys and has a price.
+pub fn validate_bundle(ids: &[u8], price: u64) -> Result<(), ProgramError> {
+	let distinct = ids.iter().enumerate().all(|(index, id)| !ids[..index].contains(id));
+
+	if ids.len() < 2 || ids.len() > MAX_BUNDLE_SIZE || !distinct || price == 0 {
+		return Err(CustomError::InvalidBundle.into());
+	}
+
+	Ok(())
+}
+
+/// Splits `price` across lots in proportion to `weights`, such as their grams. The last lot
+/// takes the rounding remainder so the parts always add up to `price`; lots share equally
+/// when no lot has any weight.
+pub fn split_price(price: u64, weights: &[u64]) -> Vec<u64> {
+	let total: u128 = weights.iter().map(|&weight| weight as u128).sum();
+	let mut remaining = price;
+
+	weights.iter()
+		.enumerate()
+		.map(|(index, &weight)| {
+			let part = if index + 1 == weights.len() {
+				remaining
+			} else {
+				(price as u128 * weight as u128)
+					.checked_div(total)
+					.map_or(price / weights.len() as u64, |part| part as u64)
+			};
+
+			remaining -= part;
+			part
+		})
+		.collect()
+}
+
+impl Bundle {
+	pub fn ids(&self) -> &[u8] {
+		&self.ids[..self.len as usize]
+	}
+
+	pub fn escrow_bumps(&self) -> &[u8] {
+		&self.escrow_bumps[..self.len as usize]
+	}
+}
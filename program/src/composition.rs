@@ -0,0 +1,51 @@
+//! Validation of alloy chemical compositions before they are written on-chain.
+
+use solana_program::entrypoint::ProgramResult;
+use crate::{
+	error::CustomError,
+	state::{ ElementComposition, MAX_COMPOSITION_ELEMENTS, TOTAL_BPS },
+};
+
+/// Checks every element and that the actual percentages add up to at most 100%.
+pub fn validate_composition(elements: &[ElementComposition]) -> ProgramResult {
+	if elements.len() > MAX_COMPOSITION_ELEMENTS {
+		return Err(CustomError::TooManyElements.into());
+	}
+
+	let mut total_bps: u32 = 0;
+
+	for (index, element) in elements.iter().enumerate() {
+		validate_element(element)?;
+
+		if elements[..index].iter().any(|previous| previous.symbol == element.symbol) {
+			return Err(CustomError::DuplicateElement.into());
+		}
+
+		total_bps += element.actual_bps as u32;
+	}
+
+	if total_bps > TOTAL_BPS as u32 {
+		return Err(CustomError::CompositionExceedsTotal.into());
+	}
+
+	Ok(())
+}
+
+/// An element has a chemical-style symbol (`C`, `Cr`) and satisfies
+/// `min_bps <= actual_bps <= max_bps <= TOTAL_BPS`.
+pub fn validate_element(element: &ElementComposition) -> ProgramResult {
+	let valid_symbol = match element.symbol {
+		[first, 0] => first.is_ascii_uppercase(),
+		[first, second] => first.is_ascii_uppercase() && second.is_ascii_lowercase(),
+	};
+
+	if !valid_symbol {
+		return Err(CustomError::InvalidElementSymbol.into());
+	}
+
+	if element.min_bps > element.actual_bps || element.actual_bps > element.max_bps || element.max_bps > TOTAL_BPS {
+		return Err(CustomError::InvalidElementRange.into());
+	}
+
+	Ok(())
+}
@@ -0,0 +1,28 @@
+//! Location codes recorded with each chain-of-custody handoff.
+
+use solana_program::entrypoint::ProgramResult;
+use crate::{
+	error::CustomError,
+	state::MAX_LOCATION_CODE_LENGTH,
+};
+
+/// A location code is 1 to `MAX_LOCATION_CODE_LENGTH` uppercase ASCII letters, digits or
+/// dashes, wide enough for a UN/LOCODE or an internal site code such as `DEHAM-3`.
+pub fn validate_location_code(location: &str) -> ProgramResult {
+	let valid_byte = |byte: u8| byte.is_ascii_uppercase() || byte.is_ascii_digit() || byte == b'-';
+
+	if location.is_empty() || location.len() > MAX_LOCATION_CODE_LENGTH || !location.bytes().all(valid_byte) {
+		return Err(CustomError::InvalidLocationCode.into());
+	}
+
+	Ok(())
+}
+
+/// Zero-pads a validated location code to its on-chain width.
+pub fn pad_location_code(location: &str) -> [u8; MAX_LOCATION_CODE_LENGTH] {
+	let mut padded = [0; MAX_LOCATION_CODE_LENGTH];
+	let len = location.len().min(MAX_LOCATION_CODE_LENGTH);
+	padded[..len].copy_from_slice(&location.as_bytes()[..len]);
+
+	padded
+}
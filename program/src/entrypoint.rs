@@ -3,9 +3,10 @@ use solana_program::{
     entrypoint::ProgramResult,
     pubkey::Pubkey,
     account_info::AccountInfo,
+    program_error::PrintProgramError,
 };
 
-use crate::processor::Processor;
+use crate::{ error::CustomError, processor::Processor };
 
 entrypoint!(process_instruction);
 
@@ -14,5 +15,10 @@ fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    Processor::process_instruction(program_id, accounts, instruction_data)
+    if let Err(error) = Processor::process_instruction(program_id, accounts, instruction_data) {
+        error.print::<CustomError>();
+        return Err(error);
+    }
+
+    Ok(())
 }
\ No newline at end of file
@@ -1,85 +1,236 @@
-use solana_program::program_error::ProgramError;
+use num_derive::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{ PrintProgramError, ProgramError },
+};
+use thiserror::Error;
 use borsh::{ BorshSerialize, BorshDeserialize };
 
-#[derive(Clone, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+/// Errors raised by the alloy token program.
+///
+/// Discriminants are part of the on-chain interface: they are what clients receive as
+/// `custom program error: 0x..`, so existing values must never be renumbered or reused.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Error, FromPrimitive, BorshSerialize, BorshDeserialize)]
+#[borsh(use_discriminant = true)]
+#[repr(u32)]
 pub enum CustomError {
-    InstructionUnpackError,
-    InstructionPackError,
-    NotRentExempt,
-    AlreadyInitialized,
-    Uninitialized,
-    InvalidAlloyDataKey,
-    InvalidEditionKey,
-    UpdateAuthorityIncorrect,
-    UpdateAuthorityIsNotSigner,
-    NotMintAuthority,
-    InvalidMintAuthority,
-    NameTooLong,
-    SymbolTooLong,
-    UriTooLong,
-    UpdateAuthorityMustBeEqualToAlloyDataAuthorityAndSigner,
-    MintMismatch,
-    EditionsMustHaveExactlyOneToken,
-    MaxEditionsMintedAlready,
-    TokenMintToFailed,
-    MasterRecordMismatch,
-    DestinationMintMismatch,
-    EditionAlreadyMinted,
-    PrintingMintDecimalsShouldBeZero,
-    OneTimePrintingAuthorizationMintDecimalsShouldBeZero,
-    EditionMintDecimalsShouldBeZero,
-    TokenBurnFailed,
-    TokenAccountOneTimeAuthMintMismatch,
-    DerivedKeyInvalid,
-    PrintingMintMismatch,
-    OneTimePrintingAuthMintMismatch,
-    TokenAccountMintMismatch,
-    TokenAccountMintMismatchV2,
-    NotEnoughTokens,
-    PrintingMintAuthorizationAccountMismatch,
-    AuthorizationTokenAccountOwnerMismatch,
-    Disabled,
-    CreatorsTooLong,
-    CreatorsMustBeAtleastOne,
-    MustBeOneOfCreators,
-    NoCreatorsPresentOnAlloyData,
-    CreatorNotFound,
-    InvalidBasisPoints,
-    PrimarySaleCanOnlyBeFlippedToTrue,
-    OwnerMismatch,
-    NoBalanceInAccountForAuthorization,
-    ShareTotalMustBe100,
-    ReservationExists,
-    ReservationDoesNotExist,
-    ReservationNotSet,
-    ReservationAlreadyMade,
-    BeyondMaxAddressSize,
-    NumericalOverflowError,
-    ReservationBreachesMaximumSupply,
-    AddressNotInReservation,
-    CannotVerifyAnotherCreator,
-    CannotUnverifyAnotherCreator,
-    SpotMismatch,
-    IncorrectOwner,
-    PrintingWouldBreachMaximumSupply,
-    DataIsImmutable,
-    DuplicateCreatorAddress,
-    ReservationSpotsRemainingShouldMatchTotalSpotsAtStart,
-    InvalidTokenProgram,
-    DataTypeMismatch,
-    BeyondAlottedAddressSize,
-    ReservationNotComplete,
-    TriedToReplaceAnExistingReservation,
-    InvalidOperation,
-    InvalidOwner,
-    PrintingMintSupplyMustBeZeroForConversion,
-    OneTimeAuthMintSupplyMustBeZeroForConversion,
-    InvalidEditionIndex,
-    ReservationArrayShouldBeSizeOne,
+    #[error("Account is already initialized")]
+    AlreadyInitialized = 3,
+
+    #[error("Account is not initialized")]
+    Uninitialized = 4,
+
+    #[error("Alloy data account is not the PDA for this alloy id")]
+    InvalidAlloyDataKey = 5,
+
+    #[error("Name too long")]
+    NameTooLong = 11,
+
+    #[error("Symbol too long")]
+    SymbolTooLong = 12,
+
+    #[error("URI too long")]
+    UriTooLong = 13,
+
+    #[error("Token account holds no tokens")]
+    NotEnoughTokens = 32,
+
+    #[error("Token account is not for the alloy's NFT mint")]
+    OwnerMismatch = 43,
+
+    #[error("Account is not owned by the expected program")]
+    IncorrectOwner = 57,
+
+    #[error("Account data does not match the expected type")]
+    DataTypeMismatch = 63,
+
+    #[error("Token account is not owned by the expected wallet")]
+    InvalidOwner = 68,
+
+    #[error("Account must be writable")]
+    AccountNotWritable = 73,
+
+    #[error("Unknown instruction discriminator or args version")]
+    UnknownInstruction = 74,
+
+    #[error("Name is empty or contains control characters")]
+    InvalidName = 75,
+
+    #[error("Symbol must be uppercase ASCII letters or digits")]
+    InvalidSymbol = 76,
+
+    #[error("URI is missing a location or contains whitespace or control characters")]
+    InvalidUri = 77,
+
+    #[error("URI scheme must be https, ipfs or ar")]
+    UnsupportedUriScheme = 78,
+
+    #[error("Metadata URI hash is missing")]
+    MissingUriHash = 79,
+
+    #[error("Composition lists more elements than an account can hold")]
+    TooManyElements = 80,
+
+    #[error("Element symbol must be one uppercase letter optionally followed by a lowercase one")]
+    InvalidElementSymbol = 81,
+
+    #[error("Element is listed more than once")]
+    DuplicateElement = 82,
+
+    #[error("Element percentages must satisfy min <= actual <= max <= 100%")]
+    InvalidElementRange = 83,
+
+    #[error("Element percentages add up to more than 100%")]
+    CompositionExceedsTotal = 84,
+
+    #[error("Composition account is not the PDA for this alloy id")]
+    InvalidCompositionKey = 85,
+
+    #[error("Config account is not the program config PDA")]
+    InvalidConfigKey = 86,
+
+    #[error("Signer is not the program authority")]
+    InvalidAuthority = 87,
+
+    #[error("Registry entry account is not the PDA for this role and member")]
+    InvalidRegistryEntryKey = 88,
+
+    #[error("Unknown registry role")]
+    UnknownRegistryRole = 89,
+
+    #[error("Signer is not registered for this role")]
+    NotRegistered = 90,
+
+    #[error("Attestation account is not the PDA for this alloy, attester and nonce")]
+    InvalidAttestationKey = 91,
+
+    #[error("Attestation unit does not fit the property kind")]
+    InvalidAttestation = 92,
+
+    #[error("Document hash is missing")]
+    MissingDocumentHash = 93,
+
+    #[error("Certificate hash is missing")]
+    MissingCertificateHash = 94,
+
+    #[error("Certificate expiry is not in the future")]
+    InvalidCertificateExpiry = 95,
+
+    #[error("Alloy certification is missing or expired")]
+    CertificationExpired = 96,
+
+    #[error("Redemption account is not the PDA for this alloy")]
+    InvalidRedemptionKey = 97,
+
+    #[error("Escrow account is not the expected PDA")]
+    InvalidEscrowKey = 98,
+
+    #[error("Redemption is not in a state that allows this step")]
+    InvalidRedemptionState = 99,
+
+    #[error("Alloy has been redeemed")]
+    AlloyRedeemed = 100,
+
+    #[error("Shipping data hash is missing")]
+    MissingShippingHash = 101,
+
+    #[error("Custody log account is not the PDA for this alloy and page")]
+    InvalidCustodyLogKey = 102,
+
+    #[error("Location code must be 1 to 8 uppercase letters, digits or dashes")]
+    InvalidLocationCode = 103,
+
+    #[error("Quantity must be positive and a split must leave grams in the parent lot")]
+    InvalidQuantity = 104,
+
+    #[error("Only lots of the same grade can be merged")]
+    GradeMismatch = 105,
+
+    #[error("Alloy has been merged into another lot")]
+    AlloyMerged = 107,
+
+    #[error("A lot cannot be merged into itself")]
+    MergeIntoSelf = 108,
+
+    #[error("Mill and heat number must be 1 to 16 uppercase letters, digits or dashes, the standard 1 to 16 printable characters and the production date positive")]
+    InvalidProvenance = 109,
+
+    #[error("This heat has already been tokenized")]
+    HeatAlreadyTokenized = 110,
+
+    #[error("Heat index account is not the PDA for this mill and heat number")]
+    InvalidHeatIndexKey = 111,
+
+    #[error("Vault account is not the PDA for this alloy")]
+    InvalidVaultKey = 112,
+
+    #[error("Share mint must be empty, minted by the vault and have no freeze authority")]
+    InvalidShareMint = 113,
+
+    #[error("Share supply and reserve price must be positive")]
+    InvalidVaultTerms = 114,
+
+    #[error("The vault does not allow this action in its current state")]
+    InvalidVaultState = 115,
+
+    #[error("Redeeming the NFT requires every outstanding share")]
+    IncompleteShares = 116,
+
+    #[error("No vault shares to claim proceeds for")]
+    NoShares = 117,
+
+    #[error("Rental account is not the PDA for this alloy")]
+    InvalidRentalKey = 118,
+
+    #[error("Rentals must be priced and last between one day and the offer's maximum")]
+    InvalidRentalTerms = 119,
+
+    #[error("Alloy is rented out and cannot be listed, sold, split or merged until the rental ends")]
+    AlloyRented = 120,
+
+    #[error("Installment plan account is not the PDA for this alloy")]
+    InvalidInstallmentPlanKey = 121,
+
+    #[error("Installment plans need a price of at least one lamport per payment, future deadlines and a forfeit of at most 10000 bps")]
+    InvalidInstallmentTerms = 122,
+
+    #[error("The deadline for this installment has passed")]
+    InstallmentOverdue = 123,
+
+    #[error("The buyer has not missed a deadline")]
+    InstallmentNotOverdue = 124,
+
+    #[error("Bundle account is not the PDA for this seller and nonce")]
+    InvalidBundleKey = 125,
+
+    #[error("A bundle needs 2 to 8 distinct alloys and a positive price")]
+    InvalidBundle = 126,
+
+    #[error("Swap account is not the PDA for this alloy")]
+    InvalidSwapKey = 127,
+
+    #[error("A swap must ask for a different alloy")]
+    InvalidSwap = 128,
+
+    #[error("Custody log page has no room for another entry")]
+    CustodyPageFull = 129,
 }
 
 impl From<CustomError> for ProgramError {
     fn from(e: CustomError) -> Self {
         ProgramError::Custom(e as u32)
     }
-}
\ No newline at end of file
+}
+
+impl<T> DecodeError<T> for CustomError {
+    fn type_of() -> &'static str {
+        "Alloy Error"
+    }
+}
+
+impl PrintProgramError for CustomError {
+    fn print<E>(&self) {
+        msg!(&self.to_string());
+    }
+}
@@ -0,0 +1,214 @@
+use solana_program::{
+	pubkey::Pubkey,
+	log::sol_log_data,
+};
+
+use borsh::{ BorshSerialize, BorshDeserialize, BorshSchema };
+use crate::{
+	attestation::PropertyKind,
+	redemption::RedemptionStatus,
+};
+
+/// Structured events emitted by the program through `sol_log_data`.
+///
+/// Each event is logged as a single Borsh-serialized `AlloyEvent`, which shows up
+/// in the transaction logs as a `Program data: <base64>` line that indexers can
+/// decode back into this enum.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq, Clone)]
+pub enum AlloyEvent {
+	Created {
+		id: u8,
+		alloy: Pubkey,
+		owner_address: Pubkey,
+		listed_price: u64,
+	},
+	PriceUpdated {
+		id: u8,
+		old_price: u64,
+		new_price: u64,
+	},
+	Purchased {
+		id: u8,
+		seller: Pubkey,
+		buyer: Pubkey,
+		price: u64,
+		/// Whether the alloy held a valid certificate at the time of sale.
+		certified: bool,
+	},
+	Listed {
+		id: u8,
+		price: u64,
+	},
+	Delisted {
+		id: u8,
+	},
+	Burned {
+		id: u8,
+	},
+	CompositionUpdated {
+		id: u8,
+		element_count: u8,
+	},
+	ConfigInitialized {
+		authority: Pubkey,
+	},
+	RegistryEntryAdded {
+		role: u8,
+		member: Pubkey,
+	},
+	RegistryEntryRemoved {
+		role: u8,
+		member: Pubkey,
+	},
+	Attested {
+		id: u8,
+		attestation: Pubkey,
+		attester: Pubkey,
+		kind: PropertyKind,
+		value_milli: i64,
+	},
+	Certified {
+		id: u8,
+		inspector: Pubkey,
+		certificate_hash: [u8; 32],
+		expires_at: i64,
+	},
+	RedemptionRequested {
+		id: u8,
+		holder: Pubkey,
+		shipping_hash: [u8; 32],
+	},
+	RedemptionUpdated {
+		id: u8,
+		status: RedemptionStatus,
+	},
+	RedemptionCancelled {
+		id: u8,
+	},
+	Split {
+		id: u8,
+		child_id: u8,
+		quantity_grams: u64,
+	},
+	Merged {
+		id: u8,
+		source_id: u8,
+		/// Grams moved in from the source lot.
+		quantity_grams: u64,
+	},
+	CustodyRecorded {
+		id: u8,
+		/// Position of the entry in the alloy's custody log.
+		index: u32,
+		from: Pubkey,
+		to: Pubkey,
+		location: String,
+		document_hash: [u8; 32],
+	},
+	VaultCreated {
+		id: u8,
+		vault: Pubkey,
+		share_mint: Pubkey,
+		share_supply: u64,
+		reserve_price: u64,
+	},
+	/// Every share was burned and the NFT handed to `holder`.
+	VaultRedeemed {
+		id: u8,
+		holder: Pubkey,
+	},
+	VaultBoughtOut {
+		id: u8,
+		buyer: Pubkey,
+		price: u64,
+	},
+	VaultProceedsClaimed {
+		id: u8,
+		holder: Pubkey,
+		shares: u64,
+		lamports: u64,
+	},
+	RentalOffered {
+		id: u8,
+		lessor: Pubkey,
+		price_per_day: u64,
+		max_days: u16,
+	},
+	Rented {
+		id: u8,
+		renter: Pubkey,
+		price: u64,
+		expires_at: i64,
+	},
+	/// The rental offer was closed, by the renter giving up the rest of a running rental or
+	/// by the lessor between rentals.
+	RentalEnded {
+		id: u8,
+		renter: Pubkey,
+	},
+	InstallmentPlanCreated {
+		id: u8,
+		seller: Pubkey,
+		buyer: Pubkey,
+		total_price: u64,
+		payments: u8,
+	},
+	InstallmentPaid {
+		id: u8,
+		buyer: Pubkey,
+		amount: u64,
+		payments_made: u8,
+	},
+	/// The last installment was paid and the NFT released to the buyer.
+	InstallmentPlanCompleted {
+		id: u8,
+		buyer: Pubkey,
+		total_price: u64,
+	},
+	/// The buyer missed a deadline and the seller took the NFT back.
+	InstallmentPlanDefaulted {
+		id: u8,
+		buyer: Pubkey,
+		refunded: u64,
+		forfeited: u64,
+	},
+	BundleCreated {
+		bundle: Pubkey,
+		seller: Pubkey,
+		ids: Vec<u8>,
+		price: u64,
+	},
+	BundlePurchased {
+		bundle: Pubkey,
+		seller: Pubkey,
+		buyer: Pubkey,
+		price: u64,
+	},
+	/// The seller took the bundle's alloys back unsold.
+	BundleCancelled {
+		bundle: Pubkey,
+	},
+	SwapProposed {
+		id: u8,
+		proposer: Pubkey,
+		wanted_id: u8,
+		top_up: u64,
+	},
+	/// Both alloys changed hands along with the top-up.
+	SwapAccepted {
+		id: u8,
+		wanted_id: u8,
+		proposer: Pubkey,
+		counterparty: Pubkey,
+		top_up: u64,
+	},
+	SwapCancelled {
+		id: u8,
+	},
+}
+
+impl AlloyEvent {
+	pub fn emit(&self) {
+		sol_log_data(&[&borsh::to_vec(self).unwrap()]);
+	}
+}
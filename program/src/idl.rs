@@ -0,0 +1,493 @@
+//! Anchor-compatible IDL describing the program's instructions, accounts, events and errors.
+//!
+//! Everything is derived from the Rust definitions: argument and account layouts from their
+//! `BorshSchema`, instruction accounts from the `instruction_accounts!` structs and errors
+//! from `CustomError`. The checked-in `idl/alloy_token_program.json` is regenerated with
+//! `cargo run --features idl --bin idl > idl/alloy_token_program.json`, and
+//! `tests/idl.rs` fails when it falls out of date.
+
+use borsh::{
+	schema::{ BorshSchemaContainer, Declaration, Definition, Fields },
+	BorshSchema,
+};
+use num_traits::FromPrimitive;
+use serde_json::{ json, Value };
+use solana_program::{ instruction::AccountMeta, pubkey::Pubkey };
+use crate::{
+	accounts::{
+		CreateAlloyDataAccountAccounts,
+		UpdateAlloyPriceAccounts,
+		PurchaseAlloyAccounts,
+		CreateAlloyCompositionAccounts,
+		UpdateAlloyCompositionAccounts,
+		InitializeConfigAccounts,
+		AddRegistryEntryAccounts,
+		RemoveRegistryEntryAccounts,
+		CreateAttestationAccounts,
+		CertifyAlloyAccounts,
+		RequestRedemptionAccounts,
+		RedemptionStepAccounts,
+		ConfirmDeliveryAccounts,
+		CancelRedemptionAccounts,
+		RecordCustodyAccounts,
+		SplitAlloyAccounts,
+		MergeAlloysAccounts,
+		CreateVaultAccounts,
+		RedeemVaultAccounts,
+		BuyoutVaultAccounts,
+		ClaimVaultProceedsAccounts,
+		CreateRentalAccounts,
+		RentAlloyAccounts,
+		EndRentalAccounts,
+		CreateInstallmentPlanAccounts,
+		PayInstallmentAccounts,
+		ReclaimInstallmentPlanAccounts,
+		CreateBundleAccounts,
+		PurchaseBundleAccounts,
+		CancelBundleAccounts,
+		ProposeSwapAccounts,
+		AcceptSwapAccounts,
+		CancelSwapAccounts,
+	},
+	attestation::{ PropertyKind, PropertyUnit },
+	error::CustomError,
+	event::AlloyEvent,
+	instruction::{
+		AlloyCompositionArgs,
+		BundleArgs,
+		CertifyAlloyArgs,
+		CreateAlloyDataAccountArgs,
+		CreateAttestationArgs,
+		CreateBundleArgs,
+		CreateInstallmentPlanArgs,
+		CreateRentalArgs,
+		CreateVaultArgs,
+		InitializeConfigArgs,
+		InstallmentPlanArgs,
+		MergeAlloysArgs,
+		NftInstruction,
+		ProposeSwapArgs,
+		ProvenanceArgs,
+		PurchaseAlloyArgs,
+		RecordCustodyArgs,
+		RedemptionArgs,
+		RegistryEntryArgs,
+		RentAlloyArgs,
+		RentalArgs,
+		RequestRedemptionArgs,
+		SplitAlloyArgs,
+		SwapArgs,
+		UpdateAlloyPriceArgs,
+		VaultArgs,
+	},
+	redemption::RedemptionStatus,
+	vault::VaultStatus,
+	state::{
+		AlloyComposition,
+		AlloyData,
+		Attestation,
+		Bundle,
+		CustodyEntry,
+		CustodyLog,
+		CustodyLogPage,
+		ElementComposition,
+		HeatIndex,
+		InstallmentPlan,
+		ProgramConfig,
+		Redemption,
+		RegistryEntry,
+		Rental,
+		Swap,
+		Vault,
+	},
+};
+
+pub fn idl() -> Value {
+	let program_id = crate::id();
+
+	json!({
+		"version": env!("CARGO_PKG_VERSION"),
+		"name": "alloy_token_program",
+		"instructions": [
+			instruction::<CreateAlloyDataAccountArgs>(
+				"createAlloyDataAccount",
+				NftInstruction::CREATE_ALLOY_DATA_ACCOUNT,
+				NftInstruction::CREATE_ALLOY_DATA_ACCOUNT_VERSION,
+				CreateAlloyDataAccountAccounts::<Pubkey>::NAMES,
+				CreateAlloyDataAccountAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<UpdateAlloyPriceArgs>(
+				"updateAlloyPrice",
+				NftInstruction::UPDATE_ALLOY_PRICE,
+				NftInstruction::UPDATE_ALLOY_PRICE_VERSION,
+				UpdateAlloyPriceAccounts::<Pubkey>::NAMES,
+				UpdateAlloyPriceAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<PurchaseAlloyArgs>(
+				"purchaseAlloy",
+				NftInstruction::PURCHASE_ALLOY,
+				NftInstruction::PURCHASE_ALLOY_VERSION,
+				PurchaseAlloyAccounts::<Pubkey>::NAMES,
+				PurchaseAlloyAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<AlloyCompositionArgs>(
+				"createAlloyComposition",
+				NftInstruction::CREATE_ALLOY_COMPOSITION,
+				NftInstruction::CREATE_ALLOY_COMPOSITION_VERSION,
+				CreateAlloyCompositionAccounts::<Pubkey>::NAMES,
+				CreateAlloyCompositionAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<AlloyCompositionArgs>(
+				"updateAlloyComposition",
+				NftInstruction::UPDATE_ALLOY_COMPOSITION,
+				NftInstruction::UPDATE_ALLOY_COMPOSITION_VERSION,
+				UpdateAlloyCompositionAccounts::<Pubkey>::NAMES,
+				UpdateAlloyCompositionAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<InitializeConfigArgs>(
+				"initializeConfig",
+				NftInstruction::INITIALIZE_CONFIG,
+				NftInstruction::INITIALIZE_CONFIG_VERSION,
+				InitializeConfigAccounts::<Pubkey>::NAMES,
+				InitializeConfigAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RegistryEntryArgs>(
+				"addRegistryEntry",
+				NftInstruction::ADD_REGISTRY_ENTRY,
+				NftInstruction::ADD_REGISTRY_ENTRY_VERSION,
+				AddRegistryEntryAccounts::<Pubkey>::NAMES,
+				AddRegistryEntryAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RegistryEntryArgs>(
+				"removeRegistryEntry",
+				NftInstruction::REMOVE_REGISTRY_ENTRY,
+				NftInstruction::REMOVE_REGISTRY_ENTRY_VERSION,
+				RemoveRegistryEntryAccounts::<Pubkey>::NAMES,
+				RemoveRegistryEntryAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<CreateAttestationArgs>(
+				"createAttestation",
+				NftInstruction::CREATE_ATTESTATION,
+				NftInstruction::CREATE_ATTESTATION_VERSION,
+				CreateAttestationAccounts::<Pubkey>::NAMES,
+				CreateAttestationAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<CertifyAlloyArgs>(
+				"certifyAlloy",
+				NftInstruction::CERTIFY_ALLOY,
+				NftInstruction::CERTIFY_ALLOY_VERSION,
+				CertifyAlloyAccounts::<Pubkey>::NAMES,
+				CertifyAlloyAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RequestRedemptionArgs>(
+				"requestRedemption",
+				NftInstruction::REQUEST_REDEMPTION,
+				NftInstruction::REQUEST_REDEMPTION_VERSION,
+				RequestRedemptionAccounts::<Pubkey>::NAMES,
+				RequestRedemptionAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RedemptionArgs>(
+				"acknowledgeRedemption",
+				NftInstruction::ACKNOWLEDGE_REDEMPTION,
+				NftInstruction::ACKNOWLEDGE_REDEMPTION_VERSION,
+				RedemptionStepAccounts::<Pubkey>::NAMES,
+				RedemptionStepAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RedemptionArgs>(
+				"markShipped",
+				NftInstruction::MARK_SHIPPED,
+				NftInstruction::MARK_SHIPPED_VERSION,
+				RedemptionStepAccounts::<Pubkey>::NAMES,
+				RedemptionStepAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RedemptionArgs>(
+				"confirmDelivery",
+				NftInstruction::CONFIRM_DELIVERY,
+				NftInstruction::CONFIRM_DELIVERY_VERSION,
+				ConfirmDeliveryAccounts::<Pubkey>::NAMES,
+				ConfirmDeliveryAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RedemptionArgs>(
+				"cancelRedemption",
+				NftInstruction::CANCEL_REDEMPTION,
+				NftInstruction::CANCEL_REDEMPTION_VERSION,
+				CancelRedemptionAccounts::<Pubkey>::NAMES,
+				CancelRedemptionAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RecordCustodyArgs>(
+				"recordCustody",
+				NftInstruction::RECORD_CUSTODY,
+				NftInstruction::RECORD_CUSTODY_VERSION,
+				RecordCustodyAccounts::<Pubkey>::NAMES,
+				RecordCustodyAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<SplitAlloyArgs>(
+				"splitAlloy",
+				NftInstruction::SPLIT_ALLOY,
+				NftInstruction::SPLIT_ALLOY_VERSION,
+				SplitAlloyAccounts::<Pubkey>::NAMES,
+				SplitAlloyAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<MergeAlloysArgs>(
+				"mergeAlloys",
+				NftInstruction::MERGE_ALLOYS,
+				NftInstruction::MERGE_ALLOYS_VERSION,
+				MergeAlloysAccounts::<Pubkey>::NAMES,
+				MergeAlloysAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<CreateVaultArgs>(
+				"createVault",
+				NftInstruction::CREATE_VAULT,
+				NftInstruction::CREATE_VAULT_VERSION,
+				CreateVaultAccounts::<Pubkey>::NAMES,
+				CreateVaultAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<VaultArgs>(
+				"redeemVault",
+				NftInstruction::REDEEM_VAULT,
+				NftInstruction::REDEEM_VAULT_VERSION,
+				RedeemVaultAccounts::<Pubkey>::NAMES,
+				RedeemVaultAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<VaultArgs>(
+				"buyoutVault",
+				NftInstruction::BUYOUT_VAULT,
+				NftInstruction::BUYOUT_VAULT_VERSION,
+				BuyoutVaultAccounts::<Pubkey>::NAMES,
+				BuyoutVaultAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<VaultArgs>(
+				"claimVaultProceeds",
+				NftInstruction::CLAIM_VAULT_PROCEEDS,
+				NftInstruction::CLAIM_VAULT_PROCEEDS_VERSION,
+				ClaimVaultProceedsAccounts::<Pubkey>::NAMES,
+				ClaimVaultProceedsAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<CreateRentalArgs>(
+				"createRental",
+				NftInstruction::CREATE_RENTAL,
+				NftInstruction::CREATE_RENTAL_VERSION,
+				CreateRentalAccounts::<Pubkey>::NAMES,
+				CreateRentalAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RentAlloyArgs>(
+				"rentAlloy",
+				NftInstruction::RENT_ALLOY,
+				NftInstruction::RENT_ALLOY_VERSION,
+				RentAlloyAccounts::<Pubkey>::NAMES,
+				RentAlloyAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<RentalArgs>(
+				"endRental",
+				NftInstruction::END_RENTAL,
+				NftInstruction::END_RENTAL_VERSION,
+				EndRentalAccounts::<Pubkey>::NAMES,
+				EndRentalAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<CreateInstallmentPlanArgs>(
+				"createInstallmentPlan",
+				NftInstruction::CREATE_INSTALLMENT_PLAN,
+				NftInstruction::CREATE_INSTALLMENT_PLAN_VERSION,
+				CreateInstallmentPlanAccounts::<Pubkey>::NAMES,
+				CreateInstallmentPlanAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<InstallmentPlanArgs>(
+				"payInstallment",
+				NftInstruction::PAY_INSTALLMENT,
+				NftInstruction::PAY_INSTALLMENT_VERSION,
+				PayInstallmentAccounts::<Pubkey>::NAMES,
+				PayInstallmentAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<InstallmentPlanArgs>(
+				"reclaimInstallmentPlan",
+				NftInstruction::RECLAIM_INSTALLMENT_PLAN,
+				NftInstruction::RECLAIM_INSTALLMENT_PLAN_VERSION,
+				ReclaimInstallmentPlanAccounts::<Pubkey>::NAMES,
+				ReclaimInstallmentPlanAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			// The bundle instructions take the accounts of each alloy after these.
+			instruction::<CreateBundleArgs>(
+				"createBundle",
+				NftInstruction::CREATE_BUNDLE,
+				NftInstruction::CREATE_BUNDLE_VERSION,
+				CreateBundleAccounts::<Pubkey>::NAMES,
+				CreateBundleAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<BundleArgs>(
+				"purchaseBundle",
+				NftInstruction::PURCHASE_BUNDLE,
+				NftInstruction::PURCHASE_BUNDLE_VERSION,
+				PurchaseBundleAccounts::<Pubkey>::NAMES,
+				PurchaseBundleAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<BundleArgs>(
+				"cancelBundle",
+				NftInstruction::CANCEL_BUNDLE,
+				NftInstruction::CANCEL_BUNDLE_VERSION,
+				CancelBundleAccounts::<Pubkey>::NAMES,
+				CancelBundleAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<ProposeSwapArgs>(
+				"proposeSwap",
+				NftInstruction::PROPOSE_SWAP,
+				NftInstruction::PROPOSE_SWAP_VERSION,
+				ProposeSwapAccounts::<Pubkey>::NAMES,
+				ProposeSwapAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<SwapArgs>(
+				"acceptSwap",
+				NftInstruction::ACCEPT_SWAP,
+				NftInstruction::ACCEPT_SWAP_VERSION,
+				AcceptSwapAccounts::<Pubkey>::NAMES,
+				AcceptSwapAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+			instruction::<SwapArgs>(
+				"cancelSwap",
+				NftInstruction::CANCEL_SWAP,
+				NftInstruction::CANCEL_SWAP_VERSION,
+				CancelSwapAccounts::<Pubkey>::NAMES,
+				CancelSwapAccounts::<Pubkey>::default().to_account_metas(&program_id),
+			),
+		],
+		"accounts": [
+			type_definition::<AlloyData>(),
+			type_definition::<AlloyComposition>(),
+			type_definition::<ProgramConfig>(),
+			type_definition::<RegistryEntry>(),
+			type_definition::<Attestation>(),
+			type_definition::<Redemption>(),
+			type_definition::<CustodyLog>(),
+			type_definition::<CustodyLogPage>(),
+			type_definition::<HeatIndex>(),
+			type_definition::<Vault>(),
+			type_definition::<Rental>(),
+			type_definition::<InstallmentPlan>(),
+			type_definition::<Bundle>(),
+			type_definition::<Swap>(),
+		],
+		"types": [
+			type_definition::<AlloyEvent>(),
+			type_definition::<ElementComposition>(),
+			type_definition::<ProvenanceArgs>(),
+			type_definition::<PropertyKind>(),
+			type_definition::<PropertyUnit>(),
+			type_definition::<RedemptionStatus>(),
+			type_definition::<VaultStatus>(),
+			type_definition::<CustodyEntry>(),
+		],
+		"errors": errors(),
+		"metadata": {
+			"address": program_id.to_string(),
+		},
+	})
+}
+
+/// The IDL as pretty-printed JSON, exactly as checked in.
+pub fn idl_json() -> String {
+	serde_json::to_string_pretty(&idl()).unwrap() + "\n"
+}
+
+/// Describes one instruction. The args version byte that follows the discriminator is
+/// modelled as a leading `argsVersion` argument so that standard IDL decoders can read the
+/// payload.
+fn instruction<Args: BorshSchema>(name: &str, discriminator: u8, args_version: u8, account_names: &[&str], account_metas: Vec<AccountMeta>) -> Value {
+	let accounts: Vec<Value> = account_names.iter().zip(account_metas).map(|(name, meta)| json!({
+		"name": camel_case(name),
+		"isMut": meta.is_writable,
+		"isSigner": meta.is_signer,
+	})).collect();
+
+	let mut args = vec![json!({ "name": "argsVersion", "type": "u8" })];
+	args.extend(struct_fields::<Args>());
+
+	json!({
+		"name": name,
+		"docs": [format!("Args version {}.", args_version)],
+		"accounts": accounts,
+		"args": args,
+		"discriminant": { "type": "u8", "value": discriminator },
+	})
+}
+
+fn type_definition<T: BorshSchema>() -> Value {
+	let schema = BorshSchemaContainer::for_type::<T>();
+	let declaration = schema.declaration();
+
+	let ty = match schema.get_definition(declaration) {
+		Some(Definition::Enum { variants, .. }) => json!({
+			"kind": "enum",
+			"variants": variants.iter().map(|(_, variant, variant_declaration)| json!({
+				"name": variant,
+				"fields": fields(&schema, variant_declaration),
+			})).collect::<Vec<_>>(),
+		}),
+		_ => json!({
+			"kind": "struct",
+			"fields": fields(&schema, declaration),
+		}),
+	};
+
+	json!({ "name": declaration, "type": ty })
+}
+
+fn struct_fields<T: BorshSchema>() -> Vec<Value> {
+	let schema = BorshSchemaContainer::for_type::<T>();
+	fields(&schema, schema.declaration())
+}
+
+fn fields(schema: &BorshSchemaContainer, declaration: &Declaration) -> Vec<Value> {
+	match schema.get_definition(declaration) {
+		Some(Definition::Struct { fields: Fields::NamedFields(fields) }) => fields.iter().map(|(name, field)| json!({
+			"name": camel_case(name),
+			"type": idl_type(schema, field),
+		})).collect(),
+		_ => vec![],
+	}
+}
+
+fn idl_type(schema: &BorshSchemaContainer, declaration: &Declaration) -> Value {
+	match declaration.as_str() {
+		"String" => return json!("string"),
+		"Pubkey" => return json!("publicKey"),
+		_ => {},
+	}
+
+	match schema.get_definition(declaration) {
+		Some(Definition::Primitive(_)) | None => json!(declaration),
+		Some(Definition::Sequence { length_width: 0, length_range, elements }) => {
+			json!({ "array": [idl_type(schema, elements), length_range.end()] })
+		},
+		Some(Definition::Sequence { elements, .. }) => json!({ "vec": idl_type(schema, elements) }),
+		Some(Definition::Enum { variants, .. }) if declaration.starts_with("Option<") => {
+			let (_, _, some) = &variants[1];
+			json!({ "option": idl_type(schema, some) })
+		},
+		Some(_) => json!({ "defined": declaration }),
+	}
+}
+
+fn errors() -> Vec<Value> {
+	(0..=u8::MAX as u32)
+		.filter_map(CustomError::from_u32)
+		.map(|error| json!({
+			"code": error as u32,
+			"name": format!("{:?}", error),
+			"msg": error.to_string(),
+		}))
+		.collect()
+}
+
+fn camel_case(name: &str) -> String {
+	let mut words = name.trim_start_matches('_').split('_');
+	let mut camel = words.next().unwrap_or_default().to_string();
+
+	for word in words {
+		let mut chars = word.chars();
+		if let Some(first) = chars.next() {
+			camel.extend(first.to_uppercase());
+			camel.push_str(chars.as_str());
+		}
+	}
+
+	camel
+}
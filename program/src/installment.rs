@@ -0,0 +1,61 @@
+//! Terms, deadlines and settlement of installment plans.
+
+use solana_program::program_error::ProgramError;
+use crate::{
+	error::CustomError,
+	state::{ InstallmentPlan, TOTAL_BPS },
+};
+
+/// Checks an installment plan can be paid off: every payment is at least one lamport, the
+/// first deadline lies after `now`, the last one is representable and the forfeit is at most
+/// `TOTAL_BPS`.
+pub fn validate_installment_terms(
+	total_price: u64,
+	payments: u8,
+	first_due_at: i64,
+	interval: i64,
+	forfeit_bps: u16,
+	now: i64,
+) -> Result<(), ProgramError> {
+	let last_due_at = interval
+		.checked_mul(payments.saturating_sub(1) as i64)
+		.and_then(|span| first_due_at.checked_add(span));
+
+	if payments == 0
+		|| total_price < payments as u64
+		|| first_due_at <= now
+		|| interval <= 0
+		|| last_due_at.is_none()
+		|| forfeit_bps > TOTAL_BPS
+	{
+		return Err(CustomError::InvalidInstallmentTerms.into());
+	}
+
+	Ok(())
+}
+
+impl InstallmentPlan {
+	/// Deadline of the next payment.
+	pub fn next_due_at(&self) -> i64 {
+		self.first_due_at + self.payments_made as i64 * self.interval
+	}
+
+	/// Lamports due with the next payment. Every payment is an equal share of the price but
+	/// the last, which settles whatever rounding left over.
+	pub fn next_payment(&self) -> u64 {
+		if self.payments_made + 1 >= self.payments {
+			self.total_price - self.paid
+		} else {
+			self.total_price / self.payments as u64
+		}
+	}
+
+	pub fn is_paid_off(&self) -> bool {
+		self.payments_made >= self.payments
+	}
+
+	/// Part of the paid lamports handed back to the buyer on default.
+	pub fn refund(&self) -> u64 {
+		(self.paid as u128 * (TOTAL_BPS - self.forfeit_bps) as u128 / TOTAL_BPS as u128) as u64
+	}
+}
@@ -1,77 +1,641 @@
 use solana_program::{
-	sysvar::rent,
+	sysvar::{ clock, rent },
 	system_program,
 	pubkey::Pubkey,
-	instruction::{ Instruction, AccountMeta },
+	instruction::Instruction,
+	program_error::ProgramError,
+};
+use borsh::{ BorshSerialize, BorshDeserialize, BorshSchema };
+use crate::{
+	accounts::{
+		CreateAlloyDataAccountAccounts,
+		UpdateAlloyPriceAccounts,
+		PurchaseAlloyAccounts,
+		CreateAlloyCompositionAccounts,
+		UpdateAlloyCompositionAccounts,
+		InitializeConfigAccounts,
+		AddRegistryEntryAccounts,
+		RemoveRegistryEntryAccounts,
+		CreateAttestationAccounts,
+		CertifyAlloyAccounts,
+		RequestRedemptionAccounts,
+		RedemptionStepAccounts,
+		ConfirmDeliveryAccounts,
+		CancelRedemptionAccounts,
+		RecordCustodyAccounts,
+		SplitAlloyAccounts,
+		MergeAlloysAccounts,
+		CreateVaultAccounts,
+		RedeemVaultAccounts,
+		BuyoutVaultAccounts,
+		ClaimVaultProceedsAccounts,
+		CreateRentalAccounts,
+		RentAlloyAccounts,
+		EndRentalAccounts,
+		CreateInstallmentPlanAccounts,
+		PayInstallmentAccounts,
+		ReclaimInstallmentPlanAccounts,
+		CreateBundleAccounts,
+		CreateBundleLotAccounts,
+		PurchaseBundleAccounts,
+		PurchaseBundleLotAccounts,
+		CancelBundleAccounts,
+		CancelBundleLotAccounts,
+		ProposeSwapAccounts,
+		AcceptSwapAccounts,
+		CancelSwapAccounts,
+	},
+	attestation::{ PropertyKind, PropertyUnit },
+	error::CustomError,
+	state::ElementComposition,
 };
-use borsh::{ BorshSerialize, BorshDeserialize };
-use crate::state::AlloyData;
 
-#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
 pub struct CreateAlloyDataAccountArgs {
-	pub data: AlloyData,
-	pub id: u8
+	pub id: u8,
+	pub name: String,
+	pub symbol: String,
+	pub uri: String,
+	/// SHA-256 of the metadata document `uri` points at.
+	pub uri_hash: [u8; 32],
+	pub last_price: u64,
+	pub listed_price: u64,
+	pub owner_address: Pubkey,
+	pub quantity_grams: u64,
+	pub provenance: ProvenanceArgs,
+}
+
+/// Where and when the steel of a lot was made.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct ProvenanceArgs {
+	pub mill: String,
+	pub heat_number: String,
+	pub production_date: i64,
+	/// Standard the grade is specified under, such as `EN 10084` or `SAE J404`.
+	pub standard: String,
 }
 
-#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
 pub struct UpdateAlloyPriceArgs {
 	pub id: u8,
 	pub price: u64,
 }
 
-#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
 pub struct PurchaseAlloyArgs {
 	pub id: u8,
 	pub new_name: Option<String>,
 	pub new_uri: Option<String>,
+	/// Required whenever `new_uri` is set; may also be set alone when the document behind an
+	/// unchanged URI is replaced.
+	pub new_uri_hash: Option<[u8; 32]>,
 	pub new_price: Option<u64>,
+	/// Refuse the sale unless the alloy holds an unexpired certificate.
+	pub require_certification: bool,
+}
+
+/// Args of both `CreateAlloyComposition` and `UpdateAlloyComposition`; the elements replace
+/// whatever the account held before.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct AlloyCompositionArgs {
+	pub id: u8,
+	pub elements: Vec<ElementComposition>,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct InitializeConfigArgs {
+	pub authority: Pubkey,
+}
+
+/// Args of both `AddRegistryEntry` and `RemoveRegistryEntry`.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct RegistryEntryArgs {
+	pub role: u8,
+	pub member: Pubkey,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct CreateAttestationArgs {
+	pub id: u8,
+	/// Distinguishes attestations by the same attester on the same alloy.
+	pub nonce: u64,
+	pub kind: PropertyKind,
+	pub unit: PropertyUnit,
+	/// Measured value in thousandths of `unit`.
+	pub value_milli: i64,
+	/// SHA-256 of the lab report backing the value.
+	pub document_hash: [u8; 32],
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct CertifyAlloyArgs {
+	pub id: u8,
+	/// SHA-256 of the inspection certificate.
+	pub certificate_hash: [u8; 32],
+	/// Unix timestamp the certificate is valid until.
+	pub expires_at: i64,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct RequestRedemptionArgs {
+	pub id: u8,
+	/// SHA-256 of the shipping details shared with the custodian off-chain.
+	pub shipping_hash: [u8; 32],
+}
+
+/// Args of the redemption steps after the request.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct RedemptionArgs {
+	pub id: u8,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct SplitAlloyArgs {
+	/// Parent lot.
+	pub id: u8,
+	pub child_id: u8,
+	/// Grams moved from the parent into the child.
+	pub quantity_grams: u64,
+	pub listed_price: u64,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct MergeAlloysArgs {
+	/// Lot that receives the grams.
+	pub id: u8,
+	/// Lot that is emptied and retired.
+	pub source_id: u8,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct CreateVaultArgs {
+	pub id: u8,
+	/// Shares minted to the curator, in the share mint's smallest unit.
+	pub share_supply: u64,
+	/// Lamports for which anyone may buy the NFT out of the vault.
+	pub reserve_price: u64,
+}
+
+/// Args of the vault instructions after its creation.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct VaultArgs {
+	pub id: u8,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct CreateRentalArgs {
+	pub id: u8,
+	/// Lamports the renter pays the lessor per day.
+	pub price_per_day: u64,
+	/// Longest rental accepted, in days.
+	pub max_days: u16,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct RentAlloyArgs {
+	pub id: u8,
+	/// Length of the rental, in days from now.
+	pub days: u16,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct RentalArgs {
+	pub id: u8,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct CreateInstallmentPlanArgs {
+	pub id: u8,
+	/// The only account allowed to pay the installments.
+	pub buyer: Pubkey,
+	/// Lamports paid over all installments together.
+	pub total_price: u64,
+	pub payments: u8,
+	/// Unix timestamp by which the first payment is due.
+	pub first_due_at: i64,
+	/// Seconds between consecutive deadlines.
+	pub interval: i64,
+	/// Part of the paid lamports the seller keeps on default, in basis points.
+	pub forfeit_bps: u16,
+}
+
+/// Args of the installment plan instructions after its creation.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct InstallmentPlanArgs {
+	pub id: u8,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct CreateBundleArgs {
+	/// Tells apart the seller's bundles.
+	pub nonce: u64,
+	/// Alloys in the bundle, in the order their accounts follow the fixed ones.
+	pub ids: Vec<u8>,
+	/// Lamports for every alloy together.
+	pub price: u64,
+}
+
+/// Args of the bundle instructions after its creation.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct BundleArgs {
+	pub nonce: u64,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct ProposeSwapArgs {
+	pub id: u8,
+	/// The alloy the proposer wants in return.
+	pub wanted_id: u8,
+	/// Lamports the counterparty pays on top of their alloy, if any.
+	pub top_up: u64,
+}
+
+/// Args of the swap instructions after its proposal.
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct SwapArgs {
+	pub id: u8,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Clone, PartialEq)]
+pub struct RecordCustodyArgs {
+	pub id: u8,
+	/// Where the lot was received, such as a UN/LOCODE.
+	pub location: String,
+	/// SHA-256 of the handoff paperwork.
+	pub document_hash: [u8; 32],
 }
 
-#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+/// Instructions understood by the program.
+///
+/// On the wire an instruction is `[discriminator, args version, Borsh-encoded args]`. Both
+/// leading bytes are fixed per instruction below and must never be renumbered or reused, so
+/// variants can be reordered or added freely. Changing an args struct or the accounts an
+/// instruction takes means bumping its version, so that clients still sending the old layout
+/// get `UnknownInstruction` instead of having their bytes or accounts misread.
+#[derive(Debug, Clone, PartialEq)]
 pub enum NftInstruction {
 	CreateAlloyDataAccount(CreateAlloyDataAccountArgs),
 	UpdateAlloyPrice(UpdateAlloyPriceArgs),
 	PurchaseAlloy(PurchaseAlloyArgs),
+	CreateAlloyComposition(AlloyCompositionArgs),
+	UpdateAlloyComposition(AlloyCompositionArgs),
+	/// Creates the program config. Whoever runs it first picks the authority, so it belongs
+	/// in the same transaction as the deployment.
+	InitializeConfig(InitializeConfigArgs),
+	AddRegistryEntry(RegistryEntryArgs),
+	RemoveRegistryEntry(RegistryEntryArgs),
+	CreateAttestation(CreateAttestationArgs),
+	/// Records a registered inspector's certificate on the alloy, replacing any earlier one.
+	CertifyAlloy(CertifyAlloyArgs),
+	/// Escrows the holder's NFT and opens a claim on the metal behind it.
+	RequestRedemption(RequestRedemptionArgs),
+	AcknowledgeRedemption(RedemptionArgs),
+	MarkShipped(RedemptionArgs),
+	/// Burns the escrowed NFT and marks the alloy redeemed.
+	ConfirmDelivery(RedemptionArgs),
+	/// Returns the NFT to the holder, as long as the metal has not shipped.
+	CancelRedemption(RedemptionArgs),
+	/// Appends a handoff to the alloy's custody log, recorded by the registered custodian
+	/// taking over the lot from the previous one.
+	RecordCustody(RecordCustodyArgs),
+	/// Moves part of a lot into a new child alloy, copying its metadata and composition, and
+	/// mints the child's NFT to the holder from a fresh mint.
+	SplitAlloy(SplitAlloyArgs),
+	/// Moves every gram of the source lot into a lot of the same grade and burns the
+	/// source's NFT.
+	MergeAlloys(MergeAlloysArgs),
+	/// Locks the holder's NFT in a vault and mints every share of it to them.
+	CreateVault(CreateVaultArgs),
+	/// Returns the NFT to whoever holds every outstanding share, burning the shares and
+	/// closing the vault.
+	RedeemVault(VaultArgs),
+	/// Buys the NFT out of the vault at its reserve price, which stays in the vault for the
+	/// share holders.
+	BuyoutVault(VaultArgs),
+	/// Burns the signer's shares of a bought-out vault for their pro rata part of the
+	/// reserve price.
+	ClaimVaultProceeds(VaultArgs),
+	/// Offers the holder's alloy for rent at a daily price, approving the rental as delegate
+	/// for the NFT.
+	CreateRental(CreateRentalArgs),
+	/// Pays the lessor for a number of days and moves the NFT into the rental's escrow, where
+	/// it stays until the rental is ended. Until it expires, this program also refuses to
+	/// list, sell, split or merge the alloy.
+	RentAlloy(RentAlloyArgs),
+	/// Closes the rental offer and returns an escrowed NFT to the lessor. The renter may end a
+	/// running rental early and anyone may end an expired one; an offer never taken up only
+	/// closes for its lessor.
+	EndRental(RentalArgs),
+	/// Escrows the seller's NFT for a buyer who pays for it in tranches.
+	CreateInstallmentPlan(CreateInstallmentPlanArgs),
+	/// Pays the next installment before its deadline. The final payment releases the NFT to
+	/// the buyer and every paid lamport to the seller.
+	PayInstallment(InstallmentPlanArgs),
+	/// Returns the NFT to the seller once the buyer has missed a deadline, refunding the
+	/// buyer whatever the plan does not forfeit.
+	ReclaimInstallmentPlan(InstallmentPlanArgs),
+	/// Escrows several of the seller's NFTs under one listing with a single price.
+	CreateBundle(CreateBundleArgs),
+	/// Pays the seller the bundle's price and releases every NFT in it to the buyer, splitting
+	/// the price across the alloys' `last_price` by their grams.
+	PurchaseBundle(BundleArgs),
+	/// Returns every NFT in an unsold bundle to the seller.
+	CancelBundle(BundleArgs),
+	/// Escrows the proposer's NFT in exchange for another alloy plus an optional top-up.
+	ProposeSwap(ProposeSwapArgs),
+	/// Trades the wanted alloy and the top-up for the escrowed alloy, whoever holds the
+	/// wanted one.
+	AcceptSwap(SwapArgs),
+	/// Returns the escrowed NFT to the proposer.
+	CancelSwap(SwapArgs),
 }
 
 impl NftInstruction {
+	pub const CREATE_ALLOY_DATA_ACCOUNT: u8 = 0;
+	pub const UPDATE_ALLOY_PRICE: u8 = 1;
+	pub const PURCHASE_ALLOY: u8 = 2;
+	pub const CREATE_ALLOY_COMPOSITION: u8 = 3;
+	pub const UPDATE_ALLOY_COMPOSITION: u8 = 4;
+	pub const INITIALIZE_CONFIG: u8 = 5;
+	pub const ADD_REGISTRY_ENTRY: u8 = 6;
+	pub const REMOVE_REGISTRY_ENTRY: u8 = 7;
+	pub const CREATE_ATTESTATION: u8 = 8;
+	pub const CERTIFY_ALLOY: u8 = 9;
+	pub const REQUEST_REDEMPTION: u8 = 10;
+	pub const ACKNOWLEDGE_REDEMPTION: u8 = 11;
+	pub const MARK_SHIPPED: u8 = 12;
+	pub const CONFIRM_DELIVERY: u8 = 13;
+	pub const CANCEL_REDEMPTION: u8 = 14;
+	pub const RECORD_CUSTODY: u8 = 15;
+	pub const SPLIT_ALLOY: u8 = 16;
+	pub const MERGE_ALLOYS: u8 = 17;
+	pub const CREATE_VAULT: u8 = 18;
+	pub const REDEEM_VAULT: u8 = 19;
+	pub const BUYOUT_VAULT: u8 = 20;
+	pub const CLAIM_VAULT_PROCEEDS: u8 = 21;
+	pub const CREATE_RENTAL: u8 = 22;
+	pub const RENT_ALLOY: u8 = 23;
+	pub const END_RENTAL: u8 = 24;
+	pub const CREATE_INSTALLMENT_PLAN: u8 = 25;
+	pub const PAY_INSTALLMENT: u8 = 26;
+	pub const RECLAIM_INSTALLMENT_PLAN: u8 = 27;
+	pub const CREATE_BUNDLE: u8 = 28;
+	pub const PURCHASE_BUNDLE: u8 = 29;
+	pub const CANCEL_BUNDLE: u8 = 30;
+	pub const PROPOSE_SWAP: u8 = 31;
+	pub const ACCEPT_SWAP: u8 = 32;
+	pub const CANCEL_SWAP: u8 = 33;
+
+	/// Args versions accepted for each instruction. Versions 1 to 3 of
+	/// `CreateAlloyDataAccount`, from before `uri_hash`, `quantity_grams` and `provenance`,
+	/// and versions 1 and 2 of `PurchaseAlloy`, from before `new_uri_hash` and
+	/// `require_certification`, are retired: they also lack an account the current layout
+	/// requires, so they get `UnknownInstruction`. So is version 1 of `UpdateAlloyPrice`,
+	/// whose args are unchanged but which predates the `clock` account.
+	pub const CREATE_ALLOY_DATA_ACCOUNT_VERSION: u8 = 4;
+	pub const UPDATE_ALLOY_PRICE_VERSION: u8 = 2;
+	pub const PURCHASE_ALLOY_VERSION: u8 = 3;
+	pub const CREATE_ALLOY_COMPOSITION_VERSION: u8 = 1;
+	pub const UPDATE_ALLOY_COMPOSITION_VERSION: u8 = 1;
+	pub const INITIALIZE_CONFIG_VERSION: u8 = 1;
+	pub const ADD_REGISTRY_ENTRY_VERSION: u8 = 1;
+	pub const REMOVE_REGISTRY_ENTRY_VERSION: u8 = 1;
+	pub const CREATE_ATTESTATION_VERSION: u8 = 1;
+	pub const CERTIFY_ALLOY_VERSION: u8 = 1;
+	pub const REQUEST_REDEMPTION_VERSION: u8 = 1;
+	pub const ACKNOWLEDGE_REDEMPTION_VERSION: u8 = 1;
+	pub const MARK_SHIPPED_VERSION: u8 = 1;
+	pub const CONFIRM_DELIVERY_VERSION: u8 = 1;
+	pub const CANCEL_REDEMPTION_VERSION: u8 = 1;
+	pub const RECORD_CUSTODY_VERSION: u8 = 1;
+	pub const SPLIT_ALLOY_VERSION: u8 = 1;
+	pub const MERGE_ALLOYS_VERSION: u8 = 1;
+	pub const CREATE_VAULT_VERSION: u8 = 1;
+	pub const REDEEM_VAULT_VERSION: u8 = 1;
+	pub const BUYOUT_VAULT_VERSION: u8 = 1;
+	pub const CLAIM_VAULT_PROCEEDS_VERSION: u8 = 1;
+	pub const CREATE_RENTAL_VERSION: u8 = 1;
+	pub const RENT_ALLOY_VERSION: u8 = 1;
+	pub const END_RENTAL_VERSION: u8 = 1;
+	pub const CREATE_INSTALLMENT_PLAN_VERSION: u8 = 1;
+	pub const PAY_INSTALLMENT_VERSION: u8 = 1;
+	pub const RECLAIM_INSTALLMENT_PLAN_VERSION: u8 = 1;
+	pub const CREATE_BUNDLE_VERSION: u8 = 1;
+	pub const PURCHASE_BUNDLE_VERSION: u8 = 1;
+	pub const CANCEL_BUNDLE_VERSION: u8 = 1;
+	pub const PROPOSE_SWAP_VERSION: u8 = 1;
+	pub const ACCEPT_SWAP_VERSION: u8 = 1;
+	pub const CANCEL_SWAP_VERSION: u8 = 1;
+
+	pub fn discriminator(&self) -> u8 {
+		match self {
+			Self::CreateAlloyDataAccount(_) => Self::CREATE_ALLOY_DATA_ACCOUNT,
+			Self::UpdateAlloyPrice(_) => Self::UPDATE_ALLOY_PRICE,
+			Self::PurchaseAlloy(_) => Self::PURCHASE_ALLOY,
+			Self::CreateAlloyComposition(_) => Self::CREATE_ALLOY_COMPOSITION,
+			Self::UpdateAlloyComposition(_) => Self::UPDATE_ALLOY_COMPOSITION,
+			Self::InitializeConfig(_) => Self::INITIALIZE_CONFIG,
+			Self::AddRegistryEntry(_) => Self::ADD_REGISTRY_ENTRY,
+			Self::RemoveRegistryEntry(_) => Self::REMOVE_REGISTRY_ENTRY,
+			Self::CreateAttestation(_) => Self::CREATE_ATTESTATION,
+			Self::CertifyAlloy(_) => Self::CERTIFY_ALLOY,
+			Self::RequestRedemption(_) => Self::REQUEST_REDEMPTION,
+			Self::AcknowledgeRedemption(_) => Self::ACKNOWLEDGE_REDEMPTION,
+			Self::MarkShipped(_) => Self::MARK_SHIPPED,
+			Self::ConfirmDelivery(_) => Self::CONFIRM_DELIVERY,
+			Self::CancelRedemption(_) => Self::CANCEL_REDEMPTION,
+			Self::RecordCustody(_) => Self::RECORD_CUSTODY,
+			Self::SplitAlloy(_) => Self::SPLIT_ALLOY,
+			Self::MergeAlloys(_) => Self::MERGE_ALLOYS,
+			Self::CreateVault(_) => Self::CREATE_VAULT,
+			Self::RedeemVault(_) => Self::REDEEM_VAULT,
+			Self::BuyoutVault(_) => Self::BUYOUT_VAULT,
+			Self::ClaimVaultProceeds(_) => Self::CLAIM_VAULT_PROCEEDS,
+			Self::CreateRental(_) => Self::CREATE_RENTAL,
+			Self::RentAlloy(_) => Self::RENT_ALLOY,
+			Self::EndRental(_) => Self::END_RENTAL,
+			Self::CreateInstallmentPlan(_) => Self::CREATE_INSTALLMENT_PLAN,
+			Self::PayInstallment(_) => Self::PAY_INSTALLMENT,
+			Self::ReclaimInstallmentPlan(_) => Self::RECLAIM_INSTALLMENT_PLAN,
+			Self::CreateBundle(_) => Self::CREATE_BUNDLE,
+			Self::PurchaseBundle(_) => Self::PURCHASE_BUNDLE,
+			Self::CancelBundle(_) => Self::CANCEL_BUNDLE,
+			Self::ProposeSwap(_) => Self::PROPOSE_SWAP,
+			Self::AcceptSwap(_) => Self::ACCEPT_SWAP,
+			Self::CancelSwap(_) => Self::CANCEL_SWAP,
+		}
+	}
+
+	pub fn args_version(&self) -> u8 {
+		match self {
+			Self::CreateAlloyDataAccount(_) => Self::CREATE_ALLOY_DATA_ACCOUNT_VERSION,
+			Self::UpdateAlloyPrice(_) => Self::UPDATE_ALLOY_PRICE_VERSION,
+			Self::PurchaseAlloy(_) => Self::PURCHASE_ALLOY_VERSION,
+			Self::CreateAlloyComposition(_) => Self::CREATE_ALLOY_COMPOSITION_VERSION,
+			Self::UpdateAlloyComposition(_) => Self::UPDATE_ALLOY_COMPOSITION_VERSION,
+			Self::InitializeConfig(_) => Self::INITIALIZE_CONFIG_VERSION,
+			Self::AddRegistryEntry(_) => Self::ADD_REGISTRY_ENTRY_VERSION,
+			Self::RemoveRegistryEntry(_) => Self::REMOVE_REGISTRY_ENTRY_VERSION,
+			Self::CreateAttestation(_) => Self::CREATE_ATTESTATION_VERSION,
+			Self::CertifyAlloy(_) => Self::CERTIFY_ALLOY_VERSION,
+			Self::RequestRedemption(_) => Self::REQUEST_REDEMPTION_VERSION,
+			Self::AcknowledgeRedemption(_) => Self::ACKNOWLEDGE_REDEMPTION_VERSION,
+			Self::MarkShipped(_) => Self::MARK_SHIPPED_VERSION,
+			Self::ConfirmDelivery(_) => Self::CONFIRM_DELIVERY_VERSION,
+			Self::CancelRedemption(_) => Self::CANCEL_REDEMPTION_VERSION,
+			Self::RecordCustody(_) => Self::RECORD_CUSTODY_VERSION,
+			Self::SplitAlloy(_) => Self::SPLIT_ALLOY_VERSION,
+			Self::MergeAlloys(_) => Self::MERGE_ALLOYS_VERSION,
+			Self::CreateVault(_) => Self::CREATE_VAULT_VERSION,
+			Self::RedeemVault(_) => Self::REDEEM_VAULT_VERSION,
+			Self::BuyoutVault(_) => Self::BUYOUT_VAULT_VERSION,
+			Self::ClaimVaultProceeds(_) => Self::CLAIM_VAULT_PROCEEDS_VERSION,
+			Self::CreateRental(_) => Self::CREATE_RENTAL_VERSION,
+			Self::RentAlloy(_) => Self::RENT_ALLOY_VERSION,
+			Self::EndRental(_) => Self::END_RENTAL_VERSION,
+			Self::CreateInstallmentPlan(_) => Self::CREATE_INSTALLMENT_PLAN_VERSION,
+			Self::PayInstallment(_) => Self::PAY_INSTALLMENT_VERSION,
+			Self::ReclaimInstallmentPlan(_) => Self::RECLAIM_INSTALLMENT_PLAN_VERSION,
+			Self::CreateBundle(_) => Self::CREATE_BUNDLE_VERSION,
+			Self::PurchaseBundle(_) => Self::PURCHASE_BUNDLE_VERSION,
+			Self::CancelBundle(_) => Self::CANCEL_BUNDLE_VERSION,
+			Self::ProposeSwap(_) => Self::PROPOSE_SWAP_VERSION,
+			Self::AcceptSwap(_) => Self::ACCEPT_SWAP_VERSION,
+			Self::CancelSwap(_) => Self::CANCEL_SWAP_VERSION,
+		}
+	}
+
+	pub fn pack(&self) -> Vec<u8> {
+		let mut data = vec![self.discriminator(), self.args_version()];
+
+		match self {
+			Self::CreateAlloyDataAccount(args) => args.serialize(&mut data),
+			Self::UpdateAlloyPrice(args) => args.serialize(&mut data),
+			Self::PurchaseAlloy(args) => args.serialize(&mut data),
+			Self::CreateAlloyComposition(args) => args.serialize(&mut data),
+			Self::UpdateAlloyComposition(args) => args.serialize(&mut data),
+			Self::InitializeConfig(args) => args.serialize(&mut data),
+			Self::AddRegistryEntry(args) => args.serialize(&mut data),
+			Self::RemoveRegistryEntry(args) => args.serialize(&mut data),
+			Self::CreateAttestation(args) => args.serialize(&mut data),
+			Self::CertifyAlloy(args) => args.serialize(&mut data),
+			Self::RequestRedemption(args) => args.serialize(&mut data),
+			Self::AcknowledgeRedemption(args) => args.serialize(&mut data),
+			Self::MarkShipped(args) => args.serialize(&mut data),
+			Self::ConfirmDelivery(args) => args.serialize(&mut data),
+			Self::CancelRedemption(args) => args.serialize(&mut data),
+			Self::RecordCustody(args) => args.serialize(&mut data),
+			Self::SplitAlloy(args) => args.serialize(&mut data),
+			Self::MergeAlloys(args) => args.serialize(&mut data),
+			Self::CreateVault(args) => args.serialize(&mut data),
+			Self::RedeemVault(args) => args.serialize(&mut data),
+			Self::BuyoutVault(args) => args.serialize(&mut data),
+			Self::ClaimVaultProceeds(args) => args.serialize(&mut data),
+			Self::CreateRental(args) => args.serialize(&mut data),
+			Self::RentAlloy(args) => args.serialize(&mut data),
+			Self::EndRental(args) => args.serialize(&mut data),
+			Self::CreateInstallmentPlan(args) => args.serialize(&mut data),
+			Self::PayInstallment(args) => args.serialize(&mut data),
+			Self::ReclaimInstallmentPlan(args) => args.serialize(&mut data),
+			Self::CreateBundle(args) => args.serialize(&mut data),
+			Self::PurchaseBundle(args) => args.serialize(&mut data),
+			Self::CancelBundle(args) => args.serialize(&mut data),
+			Self::ProposeSwap(args) => args.serialize(&mut data),
+			Self::AcceptSwap(args) => args.serialize(&mut data),
+			Self::CancelSwap(args) => args.serialize(&mut data),
+		}.unwrap();
+
+		data
+	}
+
+	/// Decodes instruction data, failing with `UnknownInstruction` for a discriminator or
+	/// args version this program does not know and `InvalidInstructionData` for a payload
+	/// that does not match its declared layout.
+	pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+		let (&discriminator, rest) = data.split_first().ok_or(CustomError::UnknownInstruction)?;
+		let (&version, args) = rest.split_first().ok_or(CustomError::UnknownInstruction)?;
+
+		match (discriminator, version) {
+			(Self::CREATE_ALLOY_DATA_ACCOUNT, Self::CREATE_ALLOY_DATA_ACCOUNT_VERSION) => Ok(Self::CreateAlloyDataAccount(unpack_args(args)?)),
+			(Self::UPDATE_ALLOY_PRICE, Self::UPDATE_ALLOY_PRICE_VERSION) => Ok(Self::UpdateAlloyPrice(unpack_args(args)?)),
+			(Self::PURCHASE_ALLOY, Self::PURCHASE_ALLOY_VERSION) => Ok(Self::PurchaseAlloy(unpack_args(args)?)),
+			(Self::CREATE_ALLOY_COMPOSITION, Self::CREATE_ALLOY_COMPOSITION_VERSION) => Ok(Self::CreateAlloyComposition(unpack_args(args)?)),
+			(Self::UPDATE_ALLOY_COMPOSITION, Self::UPDATE_ALLOY_COMPOSITION_VERSION) => Ok(Self::UpdateAlloyComposition(unpack_args(args)?)),
+			(Self::INITIALIZE_CONFIG, Self::INITIALIZE_CONFIG_VERSION) => Ok(Self::InitializeConfig(unpack_args(args)?)),
+			(Self::ADD_REGISTRY_ENTRY, Self::ADD_REGISTRY_ENTRY_VERSION) => Ok(Self::AddRegistryEntry(unpack_args(args)?)),
+			(Self::REMOVE_REGISTRY_ENTRY, Self::REMOVE_REGISTRY_ENTRY_VERSION) => Ok(Self::RemoveRegistryEntry(unpack_args(args)?)),
+			(Self::CREATE_ATTESTATION, Self::CREATE_ATTESTATION_VERSION) => Ok(Self::CreateAttestation(unpack_args(args)?)),
+			(Self::CERTIFY_ALLOY, Self::CERTIFY_ALLOY_VERSION) => Ok(Self::CertifyAlloy(unpack_args(args)?)),
+			(Self::REQUEST_REDEMPTION, Self::REQUEST_REDEMPTION_VERSION) => Ok(Self::RequestRedemption(unpack_args(args)?)),
+			(Self::ACKNOWLEDGE_REDEMPTION, Self::ACKNOWLEDGE_REDEMPTION_VERSION) => Ok(Self::AcknowledgeRedemption(unpack_args(args)?)),
+			(Self::MARK_SHIPPED, Self::MARK_SHIPPED_VERSION) => Ok(Self::MarkShipped(unpack_args(args)?)),
+			(Self::CONFIRM_DELIVERY, Self::CONFIRM_DELIVERY_VERSION) => Ok(Self::ConfirmDelivery(unpack_args(args)?)),
+			(Self::CANCEL_REDEMPTION, Self::CANCEL_REDEMPTION_VERSION) => Ok(Self::CancelRedemption(unpack_args(args)?)),
+			(Self::RECORD_CUSTODY, Self::RECORD_CUSTODY_VERSION) => Ok(Self::RecordCustody(unpack_args(args)?)),
+			(Self::SPLIT_ALLOY, Self::SPLIT_ALLOY_VERSION) => Ok(Self::SplitAlloy(unpack_args(args)?)),
+			(Self::MERGE_ALLOYS, Self::MERGE_ALLOYS_VERSION) => Ok(Self::MergeAlloys(unpack_args(args)?)),
+			(Self::CREATE_VAULT, Self::CREATE_VAULT_VERSION) => Ok(Self::CreateVault(unpack_args(args)?)),
+			(Self::REDEEM_VAULT, Self::REDEEM_VAULT_VERSION) => Ok(Self::RedeemVault(unpack_args(args)?)),
+			(Self::BUYOUT_VAULT, Self::BUYOUT_VAULT_VERSION) => Ok(Self::BuyoutVault(unpack_args(args)?)),
+			(Self::CLAIM_VAULT_PROCEEDS, Self::CLAIM_VAULT_PROCEEDS_VERSION) => Ok(Self::ClaimVaultProceeds(unpack_args(args)?)),
+			(Self::CREATE_RENTAL, Self::CREATE_RENTAL_VERSION) => Ok(Self::CreateRental(unpack_args(args)?)),
+			(Self::RENT_ALLOY, Self::RENT_ALLOY_VERSION) => Ok(Self::RentAlloy(unpack_args(args)?)),
+			(Self::END_RENTAL, Self::END_RENTAL_VERSION) => Ok(Self::EndRental(unpack_args(args)?)),
+			(Self::CREATE_INSTALLMENT_PLAN, Self::CREATE_INSTALLMENT_PLAN_VERSION) => Ok(Self::CreateInstallmentPlan(unpack_args(args)?)),
+			(Self::PAY_INSTALLMENT, Self::PAY_INSTALLMENT_VERSION) => Ok(Self::PayInstallment(unpack_args(args)?)),
+			(Self::RECLAIM_INSTALLMENT_PLAN, Self::RECLAIM_INSTALLMENT_PLAN_VERSION) => Ok(Self::ReclaimInstallmentPlan(unpack_args(args)?)),
+			(Self::CREATE_BUNDLE, Self::CREATE_BUNDLE_VERSION) => Ok(Self::CreateBundle(unpack_args(args)?)),
+			(Self::PURCHASE_BUNDLE, Self::PURCHASE_BUNDLE_VERSION) => Ok(Self::PurchaseBundle(unpack_args(args)?)),
+			(Self::CANCEL_BUNDLE, Self::CANCEL_BUNDLE_VERSION) => Ok(Self::CancelBundle(unpack_args(args)?)),
+			(Self::PROPOSE_SWAP, Self::PROPOSE_SWAP_VERSION) => Ok(Self::ProposeSwap(unpack_args(args)?)),
+			(Self::ACCEPT_SWAP, Self::ACCEPT_SWAP_VERSION) => Ok(Self::AcceptSwap(unpack_args(args)?)),
+			(Self::CANCEL_SWAP, Self::CANCEL_SWAP_VERSION) => Ok(Self::CancelSwap(unpack_args(args)?)),
+			_ => Err(CustomError::UnknownInstruction.into()),
+		}
+	}
 
+	#[allow(clippy::too_many_arguments)]
 	pub fn create_alloy_data_accounts(
 		program_id: &Pubkey,
 		alloy_data_account: &Pubkey,
+		heat_index_account: &Pubkey,
 		payer: &Pubkey,
 		id: u8,
 		name: String,
 		symbol: String,
 		uri: String,
+		uri_hash: [u8; 32],
 		last_price: u64,
 		listed_price: u64,
+		quantity_grams: u64,
+		provenance: ProvenanceArgs,
 		owner_address: &Pubkey,
 	) -> Instruction {
-		let account_metas = vec![
-			AccountMeta::new(*alloy_data_account, false),
-			AccountMeta::new(*payer, true),
-			AccountMeta::new_readonly(system_program::id(), false),
-			AccountMeta::new_readonly(rent::id(), false)
-		];
+		let account_metas = CreateAlloyDataAccountAccounts {
+			alloy_data: *alloy_data_account,
+			heat_index: *heat_index_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			rent: rent::id(),
+		}.to_account_metas(program_id);
 
 		let alloy_data = Self::CreateAlloyDataAccount(CreateAlloyDataAccountArgs {
-			data: AlloyData {
-				id,
-				name,
-				symbol,
-				uri,
-				last_price,
-				listed_price,
-				owner_address: *owner_address
-			},
 			id,
+			name,
+			symbol,
+			uri,
+			uri_hash,
+			last_price,
+			listed_price,
+			owner_address: *owner_address,
+			quantity_grams,
+			provenance,
 		});
 
 		Instruction {
 			program_id: *program_id,
 			accounts: account_metas,
-			data: alloy_data.try_to_vec().unwrap()
+			data: alloy_data.pack()
 		}
 	}
 
@@ -83,11 +647,12 @@ impl NftInstruction {
 		owner: &Pubkey,
 		owner_nft_token_account: &Pubkey,
 	) -> Instruction {
-		let account_metas = vec![
-			AccountMeta::new(*alloy_data_account, false),
-			AccountMeta::new_readonly(*owner, true),
-			AccountMeta::new_readonly(*owner_nft_token_account, false)
-		];
+		let account_metas = UpdateAlloyPriceAccounts {
+			alloy_data: *alloy_data_account,
+			owner: *owner,
+			owner_nft_token_account: *owner_nft_token_account,
+			clock: clock::id(),
+		}.to_account_metas(program_id);
 
 		let update_data = Self::UpdateAlloyPrice(UpdateAlloyPriceArgs {
 			id,
@@ -97,39 +662,1056 @@ impl NftInstruction {
 		Instruction {
 			program_id: *program_id,
 			accounts: account_metas,
-			data: update_data.try_to_vec().unwrap()
+			data: update_data.pack()
 		}
 	}
 
+	#[allow(clippy::too_many_arguments)]
 	pub fn purchase_alloy(
-    program_id: &Pubkey,
-    alloy_data_account: &Pubkey,
-    id: u8,
-    new_name: Option<String>,
-    new_uri: Option<String>,
-    new_price: Option<u64>,
-    nft_owner_address: &Pubkey,
-    nft_token_account: &Pubkey,
-	) -> Instruction {
-		let account_metas = vec![
-            AccountMeta::new(*alloy_data_account, false),
-            AccountMeta::new_readonly(*nft_owner_address, true),
-            AccountMeta::new_readonly(*nft_token_account, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-            AccountMeta::new_readonly(rent::id(), false),
-		];
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		id: u8,
+		new_name: Option<String>,
+		new_uri: Option<String>,
+		new_uri_hash: Option<[u8; 32]>,
+		new_price: Option<u64>,
+		require_certification: bool,
+		payer: &Pubkey,
+		nft_owner: &Pubkey,
+		nft_token_account: &Pubkey,
+	) -> Instruction {
+		let account_metas = PurchaseAlloyAccounts {
+			alloy_data: *alloy_data_account,
+			payer: *payer,
+			nft_owner: *nft_owner,
+			nft_token_account: *nft_token_account,
+			system_program: system_program::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
 
 		let purchase_data = Self::PurchaseAlloy(PurchaseAlloyArgs {
-            id,
-            new_name,
-            new_uri,
-            new_price,
+			id,
+			new_name,
+			new_uri,
+			new_uri_hash,
+			new_price,
+			require_certification,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: purchase_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_alloy_composition(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		composition_account: &Pubkey,
+		id: u8,
+		elements: Vec<ElementComposition>,
+		authority: &Pubkey,
+		authority_nft_token_account: &Pubkey,
+		payer: &Pubkey,
+	) -> Instruction {
+		let account_metas = CreateAlloyCompositionAccounts {
+			alloy_data: *alloy_data_account,
+			composition: *composition_account,
+			authority: *authority,
+			authority_nft_token_account: *authority_nft_token_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			rent: rent::id(),
+		}.to_account_metas(program_id);
+
+		let composition_data = Self::CreateAlloyComposition(AlloyCompositionArgs {
+			id,
+			elements,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: composition_data.pack()
+		}
+	}
+
+	pub fn update_alloy_composition(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		composition_account: &Pubkey,
+		id: u8,
+		elements: Vec<ElementComposition>,
+		authority: &Pubkey,
+		authority_nft_token_account: &Pubkey,
+	) -> Instruction {
+		let account_metas = UpdateAlloyCompositionAccounts {
+			alloy_data: *alloy_data_account,
+			composition: *composition_account,
+			authority: *authority,
+			authority_nft_token_account: *authority_nft_token_account,
+		}.to_account_metas(program_id);
+
+		let composition_data = Self::UpdateAlloyComposition(AlloyCompositionArgs {
+			id,
+			elements,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: composition_data.pack()
+		}
+	}
+
+	pub fn initialize_config(
+		program_id: &Pubkey,
+		config_account: &Pubkey,
+		authority: &Pubkey,
+		payer: &Pubkey,
+	) -> Instruction {
+		let account_metas = InitializeConfigAccounts {
+			config: *config_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			rent: rent::id(),
+		}.to_account_metas(program_id);
+
+		let config_data = Self::InitializeConfig(InitializeConfigArgs {
+			authority: *authority,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: config_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn add_registry_entry(
+		program_id: &Pubkey,
+		config_account: &Pubkey,
+		authority: &Pubkey,
+		entry_account: &Pubkey,
+		role: u8,
+		member: &Pubkey,
+		payer: &Pubkey,
+	) -> Instruction {
+		let account_metas = AddRegistryEntryAccounts {
+			config: *config_account,
+			authority: *authority,
+			entry: *entry_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let entry_data = Self::AddRegistryEntry(RegistryEntryArgs {
+			role,
+			member: *member,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: entry_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn remove_registry_entry(
+		program_id: &Pubkey,
+		config_account: &Pubkey,
+		authority: &Pubkey,
+		entry_account: &Pubkey,
+		role: u8,
+		member: &Pubkey,
+		recipient: &Pubkey,
+	) -> Instruction {
+		let account_metas = RemoveRegistryEntryAccounts {
+			config: *config_account,
+			authority: *authority,
+			entry: *entry_account,
+			recipient: *recipient,
+		}.to_account_metas(program_id);
+
+		let entry_data = Self::RemoveRegistryEntry(RegistryEntryArgs {
+			role,
+			member: *member,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: entry_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_attestation(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		attestation_account: &Pubkey,
+		attester: &Pubkey,
+		attester_entry: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		nonce: u64,
+		kind: PropertyKind,
+		unit: PropertyUnit,
+		value_milli: i64,
+		document_hash: [u8; 32],
+	) -> Instruction {
+		let account_metas = CreateAttestationAccounts {
+			alloy_data: *alloy_data_account,
+			attestation: *attestation_account,
+			attester: *attester,
+			attester_entry: *attester_entry,
+			payer: *payer,
+			system_program: system_program::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let attestation_data = Self::CreateAttestation(CreateAttestationArgs {
+			id,
+			nonce,
+			kind,
+			unit,
+			value_milli,
+			document_hash,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: attestation_data.pack()
+		}
+	}
+
+	pub fn certify_alloy(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		inspector: &Pubkey,
+		inspector_entry: &Pubkey,
+		id: u8,
+		certificate_hash: [u8; 32],
+		expires_at: i64,
+	) -> Instruction {
+		let account_metas = CertifyAlloyAccounts {
+			alloy_data: *alloy_data_account,
+			inspector: *inspector,
+			inspector_entry: *inspector_entry,
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let certify_data = Self::CertifyAlloy(CertifyAlloyArgs {
+			id,
+			certificate_hash,
+			expires_at,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: certify_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn request_redemption(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		redemption_account: &Pubkey,
+		escrow_account: &Pubkey,
+		mint: &Pubkey,
+		holder: &Pubkey,
+		holder_token_account: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		shipping_hash: [u8; 32],
+	) -> Instruction {
+		let account_metas = RequestRedemptionAccounts {
+			alloy_data: *alloy_data_account,
+			redemption: *redemption_account,
+			escrow: *escrow_account,
+			mint: *mint,
+			holder: *holder,
+			holder_token_account: *holder_token_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let redemption_data = Self::RequestRedemption(RequestRedemptionArgs {
+			id,
+			shipping_hash,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: redemption_data.pack()
+		}
+	}
+
+	pub fn acknowledge_redemption(
+		program_id: &Pubkey,
+		redemption_account: &Pubkey,
+		custodian: &Pubkey,
+		custodian_entry: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		Self::redemption_step(program_id, redemption_account, custodian, custodian_entry, Self::AcknowledgeRedemption(RedemptionArgs { id }))
+	}
+
+	pub fn mark_shipped(
+		program_id: &Pubkey,
+		redemption_account: &Pubkey,
+		custodian: &Pubkey,
+		custodian_entry: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		Self::redemption_step(program_id, redemption_account, custodian, custodian_entry, Self::MarkShipped(RedemptionArgs { id }))
+	}
+
+	fn redemption_step(
+		program_id: &Pubkey,
+		redemption_account: &Pubkey,
+		custodian: &Pubkey,
+		custodian_entry: &Pubkey,
+		step: Self,
+	) -> Instruction {
+		let account_metas = RedemptionStepAccounts {
+			redemption: *redemption_account,
+			custodian: *custodian,
+			custodian_entry: *custodian_entry,
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: step.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn confirm_delivery(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		redemption_account: &Pubkey,
+		escrow_account: &Pubkey,
+		mint: &Pubkey,
+		holder: &Pubkey,
+		custodian: &Pubkey,
+		custodian_entry: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = ConfirmDeliveryAccounts {
+			alloy_data: *alloy_data_account,
+			redemption: *redemption_account,
+			escrow: *escrow_account,
+			mint: *mint,
+			holder: *holder,
+			custodian: *custodian,
+			custodian_entry: *custodian_entry,
+			token_program: spl_token::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let delivery_data = Self::ConfirmDelivery(RedemptionArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: delivery_data.pack()
+		}
+	}
+
+	pub fn cancel_redemption(
+		program_id: &Pubkey,
+		redemption_account: &Pubkey,
+		escrow_account: &Pubkey,
+		holder: &Pubkey,
+		holder_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = CancelRedemptionAccounts {
+			redemption: *redemption_account,
+			escrow: *escrow_account,
+			holder: *holder,
+			holder_token_account: *holder_token_account,
+			token_program: spl_token::id(),
+		}.to_account_metas(program_id);
+
+		let cancel_data = Self::CancelRedemption(RedemptionArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: cancel_data.pack()
+		}
+	}
+
+	/// `custody_page` must be the page the next entry falls into: page 0 for a new log,
+	/// otherwise `entry_count / CUSTODY_ENTRIES_PER_PAGE` of the current `CustodyLog`.
+	#[allow(clippy::too_many_arguments)]
+	pub fn record_custody(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		custody_log_account: &Pubkey,
+		custody_page_account: &Pubkey,
+		custodian: &Pubkey,
+		custodian_entry: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		location: &str,
+		document_hash: [u8; 32],
+	) -> Instruction {
+		let account_metas = RecordCustodyAccounts {
+			alloy_data: *alloy_data_account,
+			custody_log: *custody_log_account,
+			custody_page: *custody_page_account,
+			custodian: *custodian,
+			custodian_entry: *custodian_entry,
+			payer: *payer,
+			system_program: system_program::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let custody_data = Self::RecordCustody(RecordCustodyArgs {
+			id,
+			location: location.to_string(),
+			document_hash,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: custody_data.pack()
+		}
+	}
+
+	/// `child_mint` must already hold its whole supply of one in `holder_child_token_account`
+	/// with minting disabled.
+	#[allow(clippy::too_many_arguments)]
+	pub fn split_alloy(
+		program_id: &Pubkey,
+		parent_alloy_data_account: &Pubkey,
+		child_alloy_data_account: &Pubkey,
+		parent_composition_account: &Pubkey,
+		child_composition_account: &Pubkey,
+		child_mint: &Pubkey,
+		holder: &Pubkey,
+		holder_token_account: &Pubkey,
+		holder_child_token_account: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		child_id: u8,
+		quantity_grams: u64,
+		listed_price: u64,
+	) -> Instruction {
+		let account_metas = SplitAlloyAccounts {
+			parent_alloy_data: *parent_alloy_data_account,
+			child_alloy_data: *child_alloy_data_account,
+			parent_composition: *parent_composition_account,
+			child_composition: *child_composition_account,
+			child_mint: *child_mint,
+			holder: *holder,
+			holder_token_account: *holder_token_account,
+			holder_child_token_account: *holder_child_token_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let split_data = Self::SplitAlloy(SplitAlloyArgs {
+			id,
+			child_id,
+			quantity_grams,
+			listed_price,
+		});
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: split_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn merge_alloys(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		source_alloy_data_account: &Pubkey,
+		composition_account: &Pubkey,
+		source_composition_account: &Pubkey,
+		source_mint: &Pubkey,
+		holder: &Pubkey,
+		holder_token_account: &Pubkey,
+		holder_source_token_account: &Pubkey,
+		id: u8,
+		source_id: u8,
+	) -> Instruction {
+		let account_metas = MergeAlloysAccounts {
+			alloy_data: *alloy_data_account,
+			source_alloy_data: *source_alloy_data_account,
+			composition: *composition_account,
+			source_composition: *source_composition_account,
+			source_mint: *source_mint,
+			holder: *holder,
+			holder_token_account: *holder_token_account,
+			holder_source_token_account: *holder_source_token_account,
+			token_program: spl_token::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let merge_data = Self::MergeAlloys(MergeAlloysArgs { id, source_id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: merge_data.pack()
+		}
+	}
+
+	/// `share_mint` must be a fresh mint whose mint authority is `vault_account` and which
+	/// has no freeze authority.
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_vault(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		vault_account: &Pubkey,
+		escrow_account: &Pubkey,
+		mint: &Pubkey,
+		share_mint: &Pubkey,
+		curator: &Pubkey,
+		curator_token_account: &Pubkey,
+		curator_share_token_account: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		share_supply: u64,
+		reserve_price: u64,
+	) -> Instruction {
+		let account_metas = CreateVaultAccounts {
+			alloy_data: *alloy_data_account,
+			vault: *vault_account,
+			escrow: *escrow_account,
+			mint: *mint,
+			share_mint: *share_mint,
+			curator: *curator,
+			curator_token_account: *curator_token_account,
+			curator_share_token_account: *curator_share_token_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let create_vault_data = Self::CreateVault(CreateVaultArgs { id, share_supply, reserve_price });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: create_vault_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn redeem_vault(
+		program_id: &Pubkey,
+		vault_account: &Pubkey,
+		escrow_account: &Pubkey,
+		share_mint: &Pubkey,
+		holder: &Pubkey,
+		holder_share_token_account: &Pubkey,
+		holder_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = RedeemVaultAccounts {
+			vault: *vault_account,
+			escrow: *escrow_account,
+			share_mint: *share_mint,
+			holder: *holder,
+			holder_share_token_account: *holder_share_token_account,
+			holder_token_account: *holder_token_account,
+			token_program: spl_token::id(),
+		}.to_account_metas(program_id);
+
+		let redeem_data = Self::RedeemVault(VaultArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: redeem_data.pack()
+		}
+	}
+
+	pub fn buyout_vault(
+		program_id: &Pubkey,
+		vault_account: &Pubkey,
+		escrow_account: &Pubkey,
+		buyer: &Pubkey,
+		buyer_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = BuyoutVaultAccounts {
+			vault: *vault_account,
+			escrow: *escrow_account,
+			buyer: *buyer,
+			buyer_token_account: *buyer_token_account,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+		}.to_account_metas(program_id);
+
+		let buyout_data = Self::BuyoutVault(VaultArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: buyout_data.pack()
+		}
+	}
+
+	pub fn claim_vault_proceeds(
+		program_id: &Pubkey,
+		vault_account: &Pubkey,
+		share_mint: &Pubkey,
+		holder: &Pubkey,
+		holder_share_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = ClaimVaultProceedsAccounts {
+			vault: *vault_account,
+			share_mint: *share_mint,
+			holder: *holder,
+			holder_share_token_account: *holder_share_token_account,
+			token_program: spl_token::id(),
+		}.to_account_metas(program_id);
+
+		let claim_data = Self::ClaimVaultProceeds(VaultArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: claim_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_rental(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		rental_account: &Pubkey,
+		escrow_account: &Pubkey,
+		mint: &Pubkey,
+		lessor: &Pubkey,
+		lessor_token_account: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		price_per_day: u64,
+		max_days: u16,
+	) -> Instruction {
+		let account_metas = CreateRentalAccounts {
+			alloy_data: *alloy_data_account,
+			rental: *rental_account,
+			escrow: *escrow_account,
+			mint: *mint,
+			lessor: *lessor,
+			lessor_token_account: *lessor_token_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			rent: rent::id(),
+		}.to_account_metas(program_id);
+
+		let create_rental_data = Self::CreateRental(CreateRentalArgs { id, price_per_day, max_days });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: create_rental_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn rent_alloy(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		rental_account: &Pubkey,
+		escrow_account: &Pubkey,
+		lessor: &Pubkey,
+		lessor_token_account: &Pubkey,
+		renter: &Pubkey,
+		id: u8,
+		days: u16,
+	) -> Instruction {
+		let account_metas = RentAlloyAccounts {
+			alloy_data: *alloy_data_account,
+			rental: *rental_account,
+			escrow: *escrow_account,
+			lessor: *lessor,
+			lessor_token_account: *lessor_token_account,
+			renter: *renter,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let rent_data = Self::RentAlloy(RentAlloyArgs { id, days });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: rent_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn end_rental(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		rental_account: &Pubkey,
+		escrow_account: &Pubkey,
+		authority: &Pubkey,
+		lessor: &Pubkey,
+		lessor_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = EndRentalAccounts {
+			alloy_data: *alloy_data_account,
+			rental: *rental_account,
+			escrow: *escrow_account,
+			authority: *authority,
+			lessor: *lessor,
+			lessor_token_account: *lessor_token_account,
+			token_program: spl_token::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let end_data = Self::EndRental(RentalArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: end_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_installment_plan(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		plan_account: &Pubkey,
+		escrow_account: &Pubkey,
+		mint: &Pubkey,
+		seller: &Pubkey,
+		seller_token_account: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		buyer: &Pubkey,
+		total_price: u64,
+		payments: u8,
+		first_due_at: i64,
+		interval: i64,
+		forfeit_bps: u16,
+	) -> Instruction {
+		let account_metas = CreateInstallmentPlanAccounts {
+			alloy_data: *alloy_data_account,
+			plan: *plan_account,
+			escrow: *escrow_account,
+			mint: *mint,
+			seller: *seller,
+			seller_token_account: *seller_token_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let create_plan_data = Self::CreateInstallmentPlan(CreateInstallmentPlanArgs {
+			id,
+			buyer: *buyer,
+			total_price,
+			payments,
+			first_due_at,
+			interval,
+			forfeit_bps,
 		});
 
 		Instruction {
 			program_id: *program_id,
 			accounts: account_metas,
-			data: purchase_data.try_to_vec().unwrap()
+			data: create_plan_data.pack()
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn pay_installment(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		plan_account: &Pubkey,
+		escrow_account: &Pubkey,
+		seller: &Pubkey,
+		buyer: &Pubkey,
+		buyer_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = PayInstallmentAccounts {
+			alloy_data: *alloy_data_account,
+			plan: *plan_account,
+			escrow: *escrow_account,
+			seller: *seller,
+			buyer: *buyer,
+			buyer_token_account: *buyer_token_account,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let pay_data = Self::PayInstallment(InstallmentPlanArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: pay_data.pack()
+		}
+	}
+
+	pub fn reclaim_installment_plan(
+		program_id: &Pubkey,
+		plan_account: &Pubkey,
+		escrow_account: &Pubkey,
+		seller: &Pubkey,
+		seller_token_account: &Pubkey,
+		buyer: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = ReclaimInstallmentPlanAccounts {
+			plan: *plan_account,
+			escrow: *escrow_account,
+			seller: *seller,
+			seller_token_account: *seller_token_account,
+			buyer: *buyer,
+			token_program: spl_token::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let reclaim_data = Self::ReclaimInstallmentPlan(InstallmentPlanArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: reclaim_data.pack()
+		}
+	}
+
+	/// `lots` holds the accounts of each alloy in `ids`, in the same order.
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_bundle(
+		program_id: &Pubkey,
+		bundle_account: &Pubkey,
+		seller: &Pubkey,
+		payer: &Pubkey,
+		lots: &[CreateBundleLotAccounts<Pubkey>],
+		nonce: u64,
+		ids: Vec<u8>,
+		price: u64,
+	) -> Instruction {
+		let mut account_metas = CreateBundleAccounts {
+			bundle: *bundle_account,
+			seller: *seller,
+			payer: *payer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		for lot in lots {
+			account_metas.extend(lot.to_account_metas(program_id));
+		}
+
+		let create_bundle_data = Self::CreateBundle(CreateBundleArgs { nonce, ids, price });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: create_bundle_data.pack()
+		}
+	}
+
+	/// `lots` holds the accounts of each alloy in the bundle, in the bundle's order.
+	pub fn purchase_bundle(
+		program_id: &Pubkey,
+		bundle_account: &Pubkey,
+		seller: &Pubkey,
+		buyer: &Pubkey,
+		lots: &[PurchaseBundleLotAccounts<Pubkey>],
+		nonce: u64,
+	) -> Instruction {
+		let mut account_metas = PurchaseBundleAccounts {
+			bundle: *bundle_account,
+			seller: *seller,
+			buyer: *buyer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+		}.to_account_metas(program_id);
+
+		for lot in lots {
+			account_metas.extend(lot.to_account_metas(program_id));
+		}
+
+		let purchase_bundle_data = Self::PurchaseBundle(BundleArgs { nonce });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: purchase_bundle_data.pack()
+		}
+	}
+
+	/// `lots` holds the accounts of each alloy in the bundle, in the bundle's order.
+	pub fn cancel_bundle(
+		program_id: &Pubkey,
+		bundle_account: &Pubkey,
+		seller: &Pubkey,
+		lots: &[CancelBundleLotAccounts<Pubkey>],
+		nonce: u64,
+	) -> Instruction {
+		let mut account_metas = CancelBundleAccounts {
+			bundle: *bundle_account,
+			seller: *seller,
+			token_program: spl_token::id(),
+		}.to_account_metas(program_id);
+
+		for lot in lots {
+			account_metas.extend(lot.to_account_metas(program_id));
+		}
+
+		let cancel_bundle_data = Self::CancelBundle(BundleArgs { nonce });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: cancel_bundle_data.pack()
 		}
 	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn propose_swap(
+		program_id: &Pubkey,
+		alloy_data_account: &Pubkey,
+		wanted_alloy_data_account: &Pubkey,
+		swap_account: &Pubkey,
+		escrow_account: &Pubkey,
+		mint: &Pubkey,
+		proposer: &Pubkey,
+		proposer_token_account: &Pubkey,
+		payer: &Pubkey,
+		id: u8,
+		wanted_id: u8,
+		top_up: u64,
+	) -> Instruction {
+		let account_metas = ProposeSwapAccounts {
+			alloy_data: *alloy_data_account,
+			wanted_alloy_data: *wanted_alloy_data_account,
+			swap: *swap_account,
+			escrow: *escrow_account,
+			mint: *mint,
+			proposer: *proposer,
+			proposer_token_account: *proposer_token_account,
+			payer: *payer,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			rent: rent::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let propose_swap_data = Self::ProposeSwap(ProposeSwapArgs { id, wanted_id, top_up });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: propose_swap_data.pack()
+		}
+	}
+
+	/// `proposer_token_account` must belong to the proposer and receives the wanted NFT.
+	#[allow(clippy::too_many_arguments)]
+	pub fn accept_swap(
+		program_id: &Pubkey,
+		wanted_alloy_data_account: &Pubkey,
+		swap_account: &Pubkey,
+		escrow_account: &Pubkey,
+		proposer: &Pubkey,
+		proposer_token_account: &Pubkey,
+		counterparty: &Pubkey,
+		counterparty_token_account: &Pubkey,
+		counterparty_offered_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = AcceptSwapAccounts {
+			wanted_alloy_data: *wanted_alloy_data_account,
+			swap: *swap_account,
+			escrow: *escrow_account,
+			proposer: *proposer,
+			proposer_token_account: *proposer_token_account,
+			counterparty: *counterparty,
+			counterparty_token_account: *counterparty_token_account,
+			counterparty_offered_token_account: *counterparty_offered_token_account,
+			system_program: system_program::id(),
+			token_program: spl_token::id(),
+			clock: clock::id(),
+		}.to_account_metas(program_id);
+
+		let accept_swap_data = Self::AcceptSwap(SwapArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: accept_swap_data.pack()
+		}
+	}
+
+	pub fn cancel_swap(
+		program_id: &Pubkey,
+		swap_account: &Pubkey,
+		escrow_account: &Pubkey,
+		proposer: &Pubkey,
+		proposer_token_account: &Pubkey,
+		id: u8,
+	) -> Instruction {
+		let account_metas = CancelSwapAccounts {
+			swap: *swap_account,
+			escrow: *escrow_account,
+			proposer: *proposer,
+			proposer_token_account: *proposer_token_account,
+			token_program: spl_token::id(),
+		}.to_account_metas(program_id);
+
+		let cancel_swap_data = Self::CancelSwap(SwapArgs { id });
+
+		Instruction {
+			program_id: *program_id,
+			accounts: account_metas,
+			data: cancel_swap_data.pack()
+		}
+	}
+}
+
+fn unpack_args<T: BorshDeserialize>(args: &[u8]) -> Result<T, ProgramError> {
+	borsh::from_slice(args).map_err(|_| ProgramError::InvalidInstructionData)
 }
\ No newline at end of file
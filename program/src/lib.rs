@@ -3,5 +3,19 @@ pub mod entrypoint;
 pub mod instruction;
 pub mod processor;
 pub mod error;
+pub mod event;
+pub mod validation;
+pub mod metadata;
+pub mod composition;
+pub mod attestation;
+pub mod redemption;
+pub mod custody;
+pub mod vault;
+pub mod rental;
+pub mod installment;
+pub mod bundle;
+pub mod accounts;
+#[cfg(not(target_os = "solana"))]
+pub mod idl;
 
 solana_program::declare_id!("D7RRT9SPTuFxXtEJQSP1kSN5me9Q56UsDYAhFsbmXpsa");
@@ -0,0 +1,95 @@
+//! Validation of the alloy metadata strings, shared by every instruction that writes them.
+//!
+//! Instruction args are Borsh `String`s, so UTF-8 validity is already enforced when the
+//! instruction is decoded; the checks here cover length, content and format.
+
+use solana_program::entrypoint::ProgramResult;
+use crate::{
+	error::CustomError,
+	instruction::ProvenanceArgs,
+	state::{ MAX_NAME_LENGTH, MAX_PROVENANCE_CODE_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH },
+};
+
+/// URI schemes an alloy may point its off-chain metadata at.
+pub const ALLOWED_URI_SCHEMES: &[&str] = &["https://", "ipfs://", "ar://"];
+
+/// A name is 1 to `MAX_NAME_LENGTH` bytes of UTF-8 without control characters.
+pub fn validate_name(name: &str) -> ProgramResult {
+	if name.len() > MAX_NAME_LENGTH {
+		return Err(CustomError::NameTooLong.into());
+	}
+
+	if name.is_empty() || name.chars().any(char::is_control) {
+		return Err(CustomError::InvalidName.into());
+	}
+
+	Ok(())
+}
+
+/// A symbol is 1 to `MAX_SYMBOL_LENGTH` uppercase ASCII letters or digits.
+pub fn validate_symbol(symbol: &str) -> ProgramResult {
+	if symbol.len() > MAX_SYMBOL_LENGTH {
+		return Err(CustomError::SymbolTooLong.into());
+	}
+
+	if symbol.is_empty() || !symbol.bytes().all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit()) {
+		return Err(CustomError::InvalidSymbol.into());
+	}
+
+	Ok(())
+}
+
+/// A URI is at most `MAX_URI_LENGTH` bytes, uses one of `ALLOWED_URI_SCHEMES` followed by a
+/// non-empty location, and contains no whitespace or control characters.
+pub fn validate_uri(uri: &str) -> ProgramResult {
+	if uri.len() > MAX_URI_LENGTH {
+		return Err(CustomError::UriTooLong.into());
+	}
+
+	if uri.chars().any(|c| c.is_whitespace() || c.is_control()) {
+		return Err(CustomError::InvalidUri.into());
+	}
+
+	let location = ALLOWED_URI_SCHEMES.iter()
+		.find_map(|scheme| uri.strip_prefix(scheme))
+		.ok_or(CustomError::UnsupportedUriScheme)?;
+
+	if location.is_empty() {
+		return Err(CustomError::InvalidUri.into());
+	}
+
+	Ok(())
+}
+
+/// A URI hash is the SHA-256 of the metadata document. All zeroes is what an unset hash
+/// looks like, so it is rejected rather than stored.
+pub fn validate_uri_hash(uri_hash: &[u8; 32]) -> ProgramResult {
+	if uri_hash.iter().all(|&byte| byte == 0) {
+		return Err(CustomError::MissingUriHash.into());
+	}
+
+	Ok(())
+}
+
+/// Mill ids and heat numbers are 1 to `MAX_PROVENANCE_CODE_LENGTH` uppercase ASCII letters,
+/// digits or dashes, so that one heat cannot be indexed under two spellings. The standard is
+/// 1 to `MAX_PROVENANCE_CODE_LENGTH` printable ASCII characters without surrounding spaces,
+/// and the production date must be set.
+pub fn validate_provenance(provenance: &ProvenanceArgs) -> ProgramResult {
+	let is_code = |code: &str| {
+		!code.is_empty()
+			&& code.len() <= MAX_PROVENANCE_CODE_LENGTH
+			&& code.bytes().all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit() || byte == b'-')
+	};
+	let standard = &provenance.standard;
+	let is_standard = !standard.is_empty()
+		&& standard.len() <= MAX_PROVENANCE_CODE_LENGTH
+		&& standard.trim() == standard
+		&& standard.bytes().all(|byte| byte.is_ascii_graphic() || byte == b' ');
+
+	if !is_code(&provenance.mill) || !is_code(&provenance.heat_number) || !is_standard || provenance.production_date <= 0 {
+		return Err(CustomError::InvalidProvenance.into());
+	}
+
+	Ok(())
+}
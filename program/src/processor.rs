@@ -1,22 +1,140 @@
 use crate::{
-	state::{ AlloyData, PREFIX, MAX_DATA_SIZE, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH },
-	instruction::NftInstruction,
+	state::{
+		AlloyComposition,
+		AlloyData,
+		Attestation,
+		Bundle,
+		CustodyEntry,
+		CustodyLog,
+		CustodyLogPage,
+		HeatIndex,
+		InstallmentPlan,
+		ProgramConfig,
+		Redemption,
+		RegistryEntry,
+		Rental,
+		Swap,
+		Vault,
+		ATTESTATION_DATA_SIZE,
+		ATTESTATION_SEED,
+		BUNDLE_DATA_SIZE,
+		BUNDLE_SEED,
+		COMPOSITION_SEED,
+		COMPOSITION_DATA_SIZE,
+		CONFIG_DATA_SIZE,
+		CONFIG_SEED,
+		CUSTODY_ENTRIES_PER_PAGE,
+		CUSTODY_LOG_DATA_SIZE,
+		CUSTODY_PAGE_DATA_SIZE,
+		CUSTODY_SEED,
+		ESCROW_SEED,
+		HEAT_INDEX_DATA_SIZE,
+		HEAT_SEED,
+		INSTALLMENT_PLAN_DATA_SIZE,
+		INSTALLMENT_SEED,
+		PREFIX,
+		MAX_DATA_SIZE,
+		REDEMPTION_DATA_SIZE,
+		REDEMPTION_SEED,
+		REGISTRY_ENTRY_DATA_SIZE,
+		REGISTRY_SEED,
+		RENTAL_DATA_SIZE,
+		RENTAL_SEED,
+		ROLE_ATTESTER,
+		ROLE_CUSTODIAN,
+		ROLE_INSPECTOR,
+		SWAP_DATA_SIZE,
+		SWAP_SEED,
+		VAULT_DATA_SIZE,
+		VAULT_SEED,
+		pad_str,
+	},
+	instruction::{
+		NftInstruction,
+		CreateAlloyDataAccountArgs,
+		AlloyCompositionArgs,
+		InitializeConfigArgs,
+		RegistryEntryArgs,
+		CreateAttestationArgs,
+		CertifyAlloyArgs,
+		RequestRedemptionArgs,
+		RedemptionArgs,
+		RecordCustodyArgs,
+		SplitAlloyArgs,
+		MergeAlloysArgs,
+		CreateVaultArgs,
+		VaultArgs,
+		CreateRentalArgs,
+		RentAlloyArgs,
+		RentalArgs,
+		CreateInstallmentPlanArgs,
+		InstallmentPlanArgs,
+		CreateBundleArgs,
+		BundleArgs,
+		ProposeSwapArgs,
+		SwapArgs,
+	},
+	accounts::{
+		CreateAlloyDataAccountAccounts,
+		UpdateAlloyPriceAccounts,
+		PurchaseAlloyAccounts,
+		CreateAlloyCompositionAccounts,
+		UpdateAlloyCompositionAccounts,
+		InitializeConfigAccounts,
+		AddRegistryEntryAccounts,
+		RemoveRegistryEntryAccounts,
+		CreateAttestationAccounts,
+		CertifyAlloyAccounts,
+		RequestRedemptionAccounts,
+		RedemptionStepAccounts,
+		ConfirmDeliveryAccounts,
+		CancelRedemptionAccounts,
+		RecordCustodyAccounts,
+		SplitAlloyAccounts,
+		MergeAlloysAccounts,
+		CreateVaultAccounts,
+		RedeemVaultAccounts,
+		BuyoutVaultAccounts,
+		ClaimVaultProceedsAccounts,
+		CreateRentalAccounts,
+		RentAlloyAccounts,
+		EndRentalAccounts,
+		CreateInstallmentPlanAccounts,
+		PayInstallmentAccounts,
+		ReclaimInstallmentPlanAccounts,
+		CreateBundleAccounts,
+		PurchaseBundleAccounts,
+		CancelBundleAccounts,
+		ProposeSwapAccounts,
+		AcceptSwapAccounts,
+		CancelSwapAccounts,
+	},
+	attestation::validate_property_unit,
+	redemption::RedemptionStatus,
+	vault::VaultStatus,
+	installment::validate_installment_terms,
+	bundle::{ split_price, validate_bundle },
+	custody::{ pad_location_code, validate_location_code },
+	validation::{ assert_active, assert_alloy_token_account, assert_holds_alloy_nft, assert_holds_shares, assert_not_rented, assert_share_mint, assert_uninitialized },
+	metadata::{ validate_name, validate_provenance, validate_symbol, validate_uri, validate_uri_hash },
+	composition::validate_composition,
+	event::AlloyEvent,
 	error::CustomError,
 };
-use borsh::{ BorshSerialize, BorshDeserialize };
 use solana_program::{
-        account_info::{next_account_info, AccountInfo},
-        entrypoint::ProgramResult,
-        msg,
-        program_error::ProgramError,
-        pubkey::Pubkey,
-        program::{ invoke, invoke_signed },
-        sysvar::rent::Rent,
-        sysvar::Sysvar,
-        system_instruction,
-        program_pack::{IsInitialized, Pack},
+	account_info::AccountInfo,
+	entrypoint::ProgramResult,
+	msg,
+	pubkey::Pubkey,
+	program::{ invoke, invoke_signed },
+	program_option::COption,
+	program_pack::Pack,
+	sysvar::{ clock::Clock, rent::Rent },
+	sysvar::Sysvar,
+	system_instruction,
+	system_program,
+	program_error::ProgramError,
 };
-use spl_token::state::Account;
 
 pub struct Processor;
 
@@ -26,17 +144,16 @@ impl Processor {
 		accounts: &[AccountInfo],
 		instruction_data: &[u8]
 	) -> ProgramResult {
-		let instruction = NftInstruction::try_from_slice(instruction_data)?;
+		let instruction = NftInstruction::unpack(instruction_data)?;
 
 		match instruction {
 			NftInstruction::CreateAlloyDataAccount(args) => {
 				msg!("Instruction: Create Alloy Data Accounts");
 				process_create_alloy_data_accounts(
 					program_id,
-			                accounts,
-			                args.data,
-							args.id
-			        )
+					accounts,
+					args,
+				)
 			},
 			NftInstruction::UpdateAlloyPrice(args) => {
 				msg!("Instruction: Update Alloy Price from Id");
@@ -55,9 +172,135 @@ impl Processor {
 					args.id,
 					args.new_name,
 					args.new_uri,
+					args.new_uri_hash,
 					args.new_price,
+					args.require_certification,
 				)
-			}
+			},
+			NftInstruction::CreateAlloyComposition(args) => {
+				msg!("Instruction: Create Alloy Composition");
+				process_create_alloy_composition(program_id, accounts, args)
+			},
+			NftInstruction::UpdateAlloyComposition(args) => {
+				msg!("Instruction: Update Alloy Composition");
+				process_update_alloy_composition(program_id, accounts, args)
+			},
+			NftInstruction::InitializeConfig(args) => {
+				msg!("Instruction: Initialize Config");
+				process_initialize_config(program_id, accounts, args)
+			},
+			NftInstruction::AddRegistryEntry(args) => {
+				msg!("Instruction: Add Registry Entry");
+				process_add_registry_entry(program_id, accounts, args)
+			},
+			NftInstruction::RemoveRegistryEntry(args) => {
+				msg!("Instruction: Remove Registry Entry");
+				process_remove_registry_entry(program_id, accounts, args)
+			},
+			NftInstruction::CreateAttestation(args) => {
+				msg!("Instruction: Create Attestation");
+				process_create_attestation(program_id, accounts, args)
+			},
+			NftInstruction::CertifyAlloy(args) => {
+				msg!("Instruction: Certify Alloy");
+				process_certify_alloy(program_id, accounts, args)
+			},
+			NftInstruction::RequestRedemption(args) => {
+				msg!("Instruction: Request Redemption");
+				process_request_redemption(program_id, accounts, args)
+			},
+			NftInstruction::AcknowledgeRedemption(args) => {
+				msg!("Instruction: Acknowledge Redemption");
+				process_redemption_step(program_id, accounts, args, RedemptionStatus::Requested, RedemptionStatus::Acknowledged)
+			},
+			NftInstruction::MarkShipped(args) => {
+				msg!("Instruction: Mark Shipped");
+				process_redemption_step(program_id, accounts, args, RedemptionStatus::Acknowledged, RedemptionStatus::Shipped)
+			},
+			NftInstruction::ConfirmDelivery(args) => {
+				msg!("Instruction: Confirm Delivery");
+				process_confirm_delivery(program_id, accounts, args)
+			},
+			NftInstruction::CancelRedemption(args) => {
+				msg!("Instruction: Cancel Redemption");
+				process_cancel_redemption(program_id, accounts, args)
+			},
+			NftInstruction::RecordCustody(args) => {
+				msg!("Instruction: Record Custody");
+				process_record_custody(program_id, accounts, args)
+			},
+			NftInstruction::SplitAlloy(args) => {
+				msg!("Instruction: Split Alloy");
+				process_split_alloy(program_id, accounts, args)
+			},
+			NftInstruction::MergeAlloys(args) => {
+				msg!("Instruction: Merge Alloys");
+				process_merge_alloys(program_id, accounts, args)
+			},
+			NftInstruction::CreateVault(args) => {
+				msg!("Instruction: Create Vault");
+				process_create_vault(program_id, accounts, args)
+			},
+			NftInstruction::RedeemVault(args) => {
+				msg!("Instruction: Redeem Vault");
+				process_redeem_vault(program_id, accounts, args)
+			},
+			NftInstruction::BuyoutVault(args) => {
+				msg!("Instruction: Buyout Vault");
+				process_buyout_vault(program_id, accounts, args)
+			},
+			NftInstruction::ClaimVaultProceeds(args) => {
+				msg!("Instruction: Claim Vault Proceeds");
+				process_claim_vault_proceeds(program_id, accounts, args)
+			},
+			NftInstruction::CreateRental(args) => {
+				msg!("Instruction: Create Rental");
+				process_create_rental(program_id, accounts, args)
+			},
+			NftInstruction::RentAlloy(args) => {
+				msg!("Instruction: Rent Alloy");
+				process_rent_alloy(program_id, accounts, args)
+			},
+			NftInstruction::EndRental(args) => {
+				msg!("Instruction: End Rental");
+				process_end_rental(program_id, accounts, args)
+			},
+			NftInstruction::CreateInstallmentPlan(args) => {
+				msg!("Instruction: Create Installment Plan");
+				process_create_installment_plan(program_id, accounts, args)
+			},
+			NftInstruction::PayInstallment(args) => {
+				msg!("Instruction: Pay Installment");
+				process_pay_installment(program_id, accounts, args)
+			},
+			NftInstruction::ReclaimInstallmentPlan(args) => {
+				msg!("Instruction: Reclaim Installment Plan");
+				process_reclaim_installment_plan(program_id, accounts, args)
+			},
+			NftInstruction::CreateBundle(args) => {
+				msg!("Instruction: Create Bundle");
+				process_create_bundle(program_id, accounts, args)
+			},
+			NftInstruction::PurchaseBundle(args) => {
+				msg!("Instruction: Purchase Bundle");
+				process_purchase_bundle(program_id, accounts, args)
+			},
+			NftInstruction::CancelBundle(args) => {
+				msg!("Instruction: Cancel Bundle");
+				process_cancel_bundle(program_id, accounts, args)
+			},
+			NftInstruction::ProposeSwap(args) => {
+				msg!("Instruction: Propose Swap");
+				process_propose_swap(program_id, accounts, args)
+			},
+			NftInstruction::AcceptSwap(args) => {
+				msg!("Instruction: Accept Swap");
+				process_accept_swap(program_id, accounts, args)
+			},
+			NftInstruction::CancelSwap(args) => {
+				msg!("Instruction: Cancel Swap");
+				process_cancel_swap(program_id, accounts, args)
+			},
 
 		}
 	}
@@ -66,267 +309,2008 @@ impl Processor {
 pub fn process_create_alloy_data_accounts(
 	program_id: &Pubkey,
 	accounts: &[AccountInfo],
-	data: AlloyData,
-	id: u8,
+	args: CreateAlloyDataAccountArgs,
 ) -> ProgramResult {
-	let account_iter = &mut accounts.iter();
-
-	let alloy_data_account_info = next_account_info(account_iter)?;
-	let payer_info = next_account_info(account_iter)?;
-	let system_account_info = next_account_info(account_iter)?;
-	let rent_account_info = next_account_info(account_iter)?;
+	let mill = pad_str(&args.provenance.mill);
+	let heat_number = pad_str(&args.provenance.heat_number);
+	let (accounts, alloy_data_bump, heat_index_bump) = CreateAlloyDataAccountAccounts::parse(program_id, accounts, args.id, &mill, &heat_number)?;
+
+	validate_name(&args.name)?;
+	validate_symbol(&args.symbol)?;
+	validate_uri(&args.uri)?;
+	validate_uri_hash(&args.uri_hash)?;
+	validate_provenance(&args.provenance)?;
+
+	if args.quantity_grams == 0 {
+		return Err(CustomError::InvalidQuantity.into());
+	}
 
-	let alloy_data_seeds = &[
+	let alloy_data_authority_signer_seeds = &[
 		PREFIX.as_bytes(),
 		program_id.as_ref(),
-		&[id]
+		&[args.id],
+		&[alloy_data_bump],
 	];
 
-	let (alloy_data_key, alloy_data_bump_seed) = Pubkey::find_program_address(alloy_data_seeds, program_id);
-	msg!("Alloy Data Key: {:?}", &alloy_data_key);
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.alloy_data,
+		accounts.system_program,
+		&Rent::from_account_info(accounts.rent)?,
+		MAX_DATA_SIZE,
+		alloy_data_authority_signer_seeds,
+	)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.heat_index,
+		accounts.system_program,
+		&Rent::from_account_info(accounts.rent)?,
+		HEAT_INDEX_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), HEAT_SEED.as_bytes(), &mill, &heat_number, &[heat_index_bump]],
+	)?;
+
+	let mut heat_index_data = accounts.heat_index.data.borrow_mut();
+	let heat_index = HeatIndex::load_mut(&mut heat_index_data)?;
+
+	heat_index.alloy = *accounts.alloy_data.key;
+	heat_index.mill = mill;
+	heat_index.heat_number = heat_number;
+	heat_index.id = args.id;
+	heat_index.bump = heat_index_bump;
+
+	let mut alloy_data_account_data = accounts.alloy_data.data.borrow_mut();
+	let alloy_data = AlloyData::load_mut(&mut alloy_data_account_data)?;
+
+	alloy_data.id = args.id;
+	alloy_data.bump = alloy_data_bump;
+	alloy_data.set_name(&args.name)?;
+	alloy_data.set_symbol(&args.symbol)?;
+	alloy_data.set_uri(&args.uri)?;
+	alloy_data.uri_hash = args.uri_hash;
+	alloy_data.last_price = args.last_price;
+	alloy_data.listed_price = args.listed_price;
+	alloy_data.owner_address = args.owner_address;
+	alloy_data.quantity_grams = args.quantity_grams;
+	alloy_data.production_date = args.provenance.production_date;
+	alloy_data.mill = mill;
+	alloy_data.heat_number = heat_number;
+	alloy_data.standard = pad_str(&args.provenance.standard);
+
+	AlloyEvent::Created {
+		id: alloy_data.id,
+		alloy: *accounts.alloy_data.key,
+		owner_address: alloy_data.owner_address,
+		listed_price: alloy_data.listed_price,
+	}.emit();
 
-	let alloy_data_authority_signer_seeds = &[
-		PREFIX.as_bytes(),
-        	program_id.as_ref(),
-        	&[id],
-        	&[alloy_data_bump_seed],
-    	];
-
-    	if *alloy_data_account_info.key != alloy_data_key {
-    		return Err(CustomError::InvalidAlloyDataKey.into());
-    	}
-
-    	let rent = &Rent::from_account_info(rent_account_info)?;
-    	let req_lamports = rent.minimum_balance(MAX_DATA_SIZE).max(1).saturating_sub(alloy_data_account_info.lamports());
-
-    	if req_lamports > 0 {
-    		msg!("--> {} lamports are transferred to the new acccount", req_lamports);
-    		invoke(
-    			&system_instruction::transfer(&payer_info.key, alloy_data_account_info.key, req_lamports),
-    			&[
-    			payer_info.clone(),
-    			alloy_data_account_info.clone(),
-    			system_account_info.clone()
-    			],
-    		)?;
-    	}
-
-    	let accounts = &[alloy_data_account_info.clone(), system_account_info.clone()];
-
-    	msg!("--> Allocate space for the account.");
-    	invoke_signed(
-    		&system_instruction::allocate(alloy_data_account_info.key, MAX_DATA_SIZE.try_into().unwrap()),
-    		accounts,
-    		&[alloy_data_authority_signer_seeds],
-    	)?;
-
-		msg!("--> Assign the account to the owning program");
-		invoke_signed(
-			&system_instruction::assign(alloy_data_account_info.key, &program_id),
-			accounts,
-			&[alloy_data_authority_signer_seeds],
-		)?;
+	Ok(())
+}
+
+pub fn process_update_alloy_price(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	alloy_id: u8,
+	new_price: u64
+) -> ProgramResult {
+	let accounts = UpdateAlloyPriceAccounts::parse(program_id, accounts, alloy_id)?;
+
+	let mut alloy_data_account_data = accounts.alloy_data.data.borrow_mut();
+	let alloy_data = AlloyData::load_mut(&mut alloy_data_account_data)?;
+
+	assert_active(alloy_data)?;
+	assert_not_rented(alloy_data, Clock::from_account_info(accounts.clock)?.unix_timestamp)?;
+	assert_holds_alloy_nft(accounts.owner_nft_token_account, alloy_data, accounts.owner.key)?;
+
+	let old_price = alloy_data.listed_price;
+	alloy_data.listed_price = new_price;
+
+	AlloyEvent::PriceUpdated {
+		id: alloy_data.id,
+		old_price,
+		new_price,
+	}.emit();
+
+	Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_purchase_alloy(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	id: u8,
+	new_name: Option<String>,
+	new_uri: Option<String>,
+	new_uri_hash: Option<[u8; 32]>,
+	new_price: Option<u64>,
+	require_certification: bool,
+) -> ProgramResult {
+	let accounts = PurchaseAlloyAccounts::parse(program_id, accounts, id)?;
 
-    	let mut alloy_data = AlloyData::from_acc_info(alloy_data_account_info)?;
+	if let Some(new_name) = &new_name {
+		validate_name(new_name)?;
+	}
 
-    	if data.name.len() > MAX_NAME_LENGTH {
-    		return Err(CustomError::NameTooLong.into());
-    	}
+	if let Some(new_uri) = &new_uri {
+		validate_uri(new_uri)?;
 
-		if data.symbol.len() > MAX_SYMBOL_LENGTH {
-    		return Err(CustomError::SymbolTooLong.into());
-    	}
+		if new_uri_hash.is_none() {
+			return Err(CustomError::MissingUriHash.into());
+		}
+	}
 
-    	if data.uri.len() > MAX_URI_LENGTH {
-    		return Err(CustomError::UriTooLong.into());
-    	}
+	if let Some(new_uri_hash) = &new_uri_hash {
+		validate_uri_hash(new_uri_hash)?;
+	}
 
-    	alloy_data.id = data.id;
-    	alloy_data.name = data.name;
-		alloy_data.symbol = data.symbol;
-    	alloy_data.uri = data.uri;
-    	alloy_data.last_price = data.last_price;
-    	alloy_data.listed_price = data.listed_price;
-    	alloy_data.owner_address = data.owner_address;
+	let mut alloy_data_account_data = accounts.alloy_data.data.borrow_mut();
+	let alloy_data = AlloyData::load_mut(&mut alloy_data_account_data)?;
 
-    	let mut array_of_zeroes = vec![];
+	assert_active(alloy_data)?;
+	assert_holds_alloy_nft(accounts.nft_token_account, alloy_data, accounts.nft_owner.key)?;
 
-    	while array_of_zeroes.len() > MAX_NAME_LENGTH - alloy_data.name.len() {
-    		array_of_zeroes.push(0u8);
-    	}
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+	assert_not_rented(alloy_data, now)?;
 
-    	alloy_data.name = alloy_data.name.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
+	let certified = alloy_data.is_certified(now);
 
-		let mut array_of_zeroes = vec![];
+	if require_certification && !certified {
+		return Err(CustomError::CertificationExpired.into());
+	}
 
-    	while array_of_zeroes.len() > MAX_SYMBOL_LENGTH - alloy_data.symbol.len() {
-    		array_of_zeroes.push(0u8);
-    	}
+	invoke(
+		&system_instruction::transfer(accounts.payer.key, accounts.nft_owner.key, alloy_data.listed_price),
+		&[
+			accounts.payer.clone(),
+			accounts.nft_owner.clone(),
+			accounts.system_program.clone(),
+		],
+	)?;
+
+	if let Some(new_name) = new_name {
+		alloy_data.set_name(&new_name)?;
+	}
 
-    	alloy_data.symbol = alloy_data.symbol.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
+	if let Some(new_uri) = new_uri {
+		alloy_data.set_uri(&new_uri)?;
+	}
 
-    	let mut array_of_zeroes = vec![];
+	if let Some(new_uri_hash) = new_uri_hash {
+		alloy_data.uri_hash = new_uri_hash;
+	}
 
-    	while array_of_zeroes.len() > MAX_URI_LENGTH - alloy_data.uri.len() {
-    		array_of_zeroes.push(0u8);
-    	}
+	alloy_data.last_price = alloy_data.listed_price;
 
-    	alloy_data.uri = alloy_data.uri.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
+	if let Some(new_price) = new_price {
+		alloy_data.listed_price = new_price;
+	}
 
-    	alloy_data.serialize(&mut *alloy_data_account_info.data.borrow_mut())?;
-    	msg!("Alloy Data Saved! {:#?}", alloy_data);
+	AlloyEvent::Purchased {
+		id: alloy_data.id,
+		seller: *accounts.nft_owner.key,
+		buyer: *accounts.payer.key,
+		price: alloy_data.last_price,
+		certified,
+	}.emit();
 
 	Ok(())
 }
 
-pub fn process_update_alloy_price(
+pub fn process_create_alloy_composition(
 	program_id: &Pubkey,
 	accounts: &[AccountInfo],
-	alloy_id: u8,
-	new_price: u64
+	args: AlloyCompositionArgs,
 ) -> ProgramResult {
-	let account_iter = &mut accounts.iter();
+	let (accounts, composition_bump) = CreateAlloyCompositionAccounts::parse(program_id, accounts, args.id)?;
+
+	validate_composition(&args.elements)?;
 
-	let alloy_data_account_info = next_account_info(account_iter)?;
-	let owner_info = next_account_info(account_iter)?;
-	let owner_nft_account_info = next_account_info(account_iter)?;
+	{
+		let alloy_data_account_data = accounts.alloy_data.data.borrow();
+		let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+		assert_holds_alloy_nft(accounts.authority_nft_token_account, alloy_data, accounts.authority.key)?;
+	}
 
-	let alloy_data_seeds = &[
+	let composition_signer_seeds = &[
 		PREFIX.as_bytes(),
 		program_id.as_ref(),
-		&[alloy_id]
+		&[args.id],
+		COMPOSITION_SEED.as_bytes(),
+		&[composition_bump],
 	];
 
-	let (alloy_data_key, _alloy_data_bump_seed) = Pubkey::find_program_address(alloy_data_seeds, program_id);
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.composition,
+		accounts.system_program,
+		&Rent::from_account_info(accounts.rent)?,
+		COMPOSITION_DATA_SIZE,
+		composition_signer_seeds,
+	)?;
 
-	if *alloy_data_account_info.key != alloy_data_key {
-		return Err(CustomError::InvalidAlloyDataKey.into());
-	}
+	let mut composition_account_data = accounts.composition.data.borrow_mut();
+	let composition = AlloyComposition::load_mut(&mut composition_account_data)?;
 
-	if alloy_data_account_info.owner != program_id {
-		return Err(CustomError::IncorrectOwner.into());
-	}
+	composition.id = args.id;
+	composition.bump = composition_bump;
+	composition.set_elements(&args.elements)?;
 
-	let mut alloy_data = AlloyData::from_acc_info(alloy_data_account_info)?;
+	AlloyEvent::CompositionUpdated {
+		id: composition.id,
+		element_count: composition.element_count,
+	}.emit();
 
-	let token_acc: Account = assert_initialized(&owner_nft_account_info)?;
+	Ok(())
+}
 
-	if owner_nft_account_info.owner != &spl_token::id() {
-		return Err(CustomError::IncorrectOwner.into());
-	};
+pub fn process_update_alloy_composition(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: AlloyCompositionArgs,
+) -> ProgramResult {
+	let accounts = UpdateAlloyCompositionAccounts::parse(program_id, accounts, args.id)?;
 
-	if alloy_data.owner_address != token_acc.mint {
-		return Err(CustomError::OwnerMismatch.into());
+	validate_composition(&args.elements)?;
+
+	let alloy_data_account_data = accounts.alloy_data.data.borrow();
+	let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+	assert_holds_alloy_nft(accounts.authority_nft_token_account, alloy_data, accounts.authority.key)?;
+
+	let mut composition_account_data = accounts.composition.data.borrow_mut();
+	let composition = AlloyComposition::load_mut(&mut composition_account_data)?;
+
+	composition.set_elements(&args.elements)?;
+
+	AlloyEvent::CompositionUpdated {
+		id: composition.id,
+		element_count: composition.element_count,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_initialize_config(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: InitializeConfigArgs,
+) -> ProgramResult {
+	let (accounts, config_bump) = InitializeConfigAccounts::parse(program_id, accounts)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.config,
+		accounts.system_program,
+		&Rent::from_account_info(accounts.rent)?,
+		CONFIG_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), CONFIG_SEED.as_bytes(), &[config_bump]],
+	)?;
+
+	let mut config_account_data = accounts.config.data.borrow_mut();
+	let config = ProgramConfig::load_mut(&mut config_account_data)?;
+
+	config.authority = args.authority;
+	config.bump = config_bump;
+
+	AlloyEvent::ConfigInitialized {
+		authority: config.authority,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_add_registry_entry(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RegistryEntryArgs,
+) -> ProgramResult {
+	if !matches!(args.role, ROLE_ATTESTER | ROLE_INSPECTOR | ROLE_CUSTODIAN) {
+		return Err(CustomError::UnknownRegistryRole.into());
 	}
 
-	if token_acc.owner != *owner_info.key {
-        	return Err(CustomError::InvalidOwner.into());
-    	}
+	let (accounts, entry_bump) = AddRegistryEntryAccounts::parse(program_id, accounts, args.role, &args.member)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.entry,
+		accounts.system_program,
+		&Rent::from_account_info(accounts.rent)?,
+		REGISTRY_ENTRY_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), REGISTRY_SEED.as_bytes(), &[args.role], args.member.as_ref(), &[entry_bump]],
+	)?;
+
+	let mut entry_account_data = accounts.entry.data.borrow_mut();
+	let entry = RegistryEntry::load_mut(&mut entry_account_data)?;
 
-    	alloy_data.listed_price = new_price;
+	entry.member = args.member;
+	entry.registered_at = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+	entry.role = args.role;
+	entry.bump = entry_bump;
 
-    	alloy_data.serialize(&mut *alloy_data_account_info.data.borrow_mut())?;
+	AlloyEvent::RegistryEntryAdded {
+		role: entry.role,
+		member: entry.member,
+	}.emit();
 
 	Ok(())
 }
 
-pub fn process_purchase_alloy(
+pub fn process_remove_registry_entry(
 	program_id: &Pubkey,
 	accounts: &[AccountInfo],
-	id: u8,
-	new_name: Option<String>,
-	new_uri: Option<String>,
-	new_price: Option<u64>
+	args: RegistryEntryArgs,
 ) -> ProgramResult {
-	let account_iter = &mut accounts.iter();
+	let accounts = RemoveRegistryEntryAccounts::parse(program_id, accounts, args.role, &args.member)?;
 
-	let alloy_data_account_info = next_account_info(account_iter)?;
-	let payer_info = next_account_info(account_iter)?;
-	let nft_owner_address_info = next_account_info(account_iter)?;
-	let nft_token_account_info = next_account_info(account_iter)?;
-	let system_account_info = next_account_info(account_iter)?;
+	close_program_account(accounts.entry, accounts.recipient)?;
 
-	let alloy_data_seeds = &[
-		PREFIX.as_bytes(),
-		program_id.as_ref(),
-		&[id]
-	];
+	AlloyEvent::RegistryEntryRemoved {
+		role: args.role,
+		member: args.member,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_create_attestation(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: CreateAttestationArgs,
+) -> ProgramResult {
+	let (accounts, attestation_bump) = CreateAttestationAccounts::parse(program_id, accounts, args.id, args.nonce)?;
 
-	let (alloy_data_key, _alloy_data_bump_seed) = Pubkey::find_program_address(alloy_data_seeds, program_id);
+	validate_property_unit(args.kind, args.unit)?;
 
-	if *alloy_data_account_info.key != alloy_data_key {
-		return Err(CustomError::InvalidAlloyDataKey.into());
+	if args.document_hash.iter().all(|&byte| byte == 0) {
+		return Err(CustomError::MissingDocumentHash.into());
 	}
 
-	let mut alloy_data = AlloyData::from_acc_info(alloy_data_account_info)?;
-	let token_acc: Account = assert_initialized(&nft_token_account_info)?;
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.attestation,
+		accounts.system_program,
+		&Rent::from_account_info(accounts.rent)?,
+		ATTESTATION_DATA_SIZE,
+		&[
+			PREFIX.as_bytes(),
+			program_id.as_ref(),
+			&[args.id],
+			ATTESTATION_SEED.as_bytes(),
+			accounts.attester.key.as_ref(),
+			&args.nonce.to_le_bytes(),
+			&[attestation_bump],
+		],
+	)?;
+
+	let mut attestation_account_data = accounts.attestation.data.borrow_mut();
+	let attestation = Attestation::load_mut(&mut attestation_account_data)?;
+
+	attestation.alloy = *accounts.alloy_data.key;
+	attestation.attester = *accounts.attester.key;
+	attestation.document_hash = args.document_hash;
+	attestation.timestamp = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+	attestation.value_milli = args.value_milli;
+	attestation.nonce = args.nonce;
+	attestation.id = args.id;
+	attestation.bump = attestation_bump;
+	attestation.kind = args.kind as u8;
+	attestation.unit = args.unit as u8;
+
+	AlloyEvent::Attested {
+		id: args.id,
+		attestation: *accounts.attestation.key,
+		attester: attestation.attester,
+		kind: args.kind,
+		value_milli: attestation.value_milli,
+	}.emit();
 
-	if *nft_owner_address_info.key != token_acc.owner {
-		return Err(CustomError::OwnerMismatch.into());
+	Ok(())
+}
+
+pub fn process_certify_alloy(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: CertifyAlloyArgs,
+) -> ProgramResult {
+	let accounts = CertifyAlloyAccounts::parse(program_id, accounts, args.id)?;
+
+	if args.certificate_hash.iter().all(|&byte| byte == 0) {
+		return Err(CustomError::MissingCertificateHash.into());
 	}
 
-	if alloy_data.owner_address != token_acc.owner {
-		return Err(CustomError::InvalidOwner.into());
+	if args.expires_at <= Clock::from_account_info(accounts.clock)?.unix_timestamp {
+		return Err(CustomError::InvalidCertificateExpiry.into());
+	}
+
+	let mut alloy_data_account_data = accounts.alloy_data.data.borrow_mut();
+	let alloy_data = AlloyData::load_mut(&mut alloy_data_account_data)?;
+
+	alloy_data.inspector = *accounts.inspector.key;
+	alloy_data.certificate_hash = args.certificate_hash;
+	alloy_data.certificate_expires_at = args.expires_at;
+
+	AlloyEvent::Certified {
+		id: alloy_data.id,
+		inspector: alloy_data.inspector,
+		certificate_hash: alloy_data.certificate_hash,
+		expires_at: alloy_data.certificate_expires_at,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_request_redemption(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RequestRedemptionArgs,
+) -> ProgramResult {
+	let (accounts, redemption_bump, escrow_bump) = RequestRedemptionAccounts::parse(program_id, accounts, args.id)?;
+
+	if args.shipping_hash.iter().all(|&byte| byte == 0) {
+		return Err(CustomError::MissingShippingHash.into());
+	}
+
+	{
+		let alloy_data_account_data = accounts.alloy_data.data.borrow();
+		let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+
+		assert_active(alloy_data)?;
+		assert_not_rented(alloy_data, Clock::from_account_info(accounts.clock)?.unix_timestamp)?;
+		assert_holds_alloy_nft(accounts.holder_token_account, alloy_data, accounts.holder.key)?;
+
+		if *accounts.mint.key != alloy_data.owner_address {
+			return Err(CustomError::OwnerMismatch.into());
+		}
 	}
 
+	let rent = Rent::from_account_info(accounts.rent)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.redemption,
+		accounts.system_program,
+		&rent,
+		REDEMPTION_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], REDEMPTION_SEED.as_bytes(), &[redemption_bump]],
+	)?;
+
+	create_program_account(
+		&spl_token::id(),
+		accounts.payer,
+		accounts.escrow,
+		accounts.system_program,
+		&rent,
+		spl_token::state::Account::LEN,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], REDEMPTION_SEED.as_bytes(), ESCROW_SEED.as_bytes(), &[escrow_bump]],
+	)?;
+
 	invoke(
-        &system_instruction::transfer(&payer_info.key, &nft_owner_address_info.key, alloy_data.listed_price as u64),
-        &[
-            payer_info.clone(),
-            nft_owner_address_info.clone(),
-            system_account_info.clone(),
-        ],
-    )?;
-
-	alloy_data.name = match new_name {
-		Some(new_name) => new_name,
-		None => alloy_data.name
-	};
+		&spl_token::instruction::initialize_account3(&spl_token::id(), accounts.escrow.key, accounts.mint.key, accounts.redemption.key)?,
+		&[accounts.escrow.clone(), accounts.mint.clone(), accounts.token_program.clone()],
+	)?;
+
+	let amount = spl_token::state::Account::unpack(&accounts.holder_token_account.data.borrow())?.amount;
+
+	invoke(
+		&spl_token::instruction::transfer(&spl_token::id(), accounts.holder_token_account.key, accounts.escrow.key, accounts.holder.key, &[], amount)?,
+		&[
+			accounts.holder_token_account.clone(),
+			accounts.escrow.clone(),
+			accounts.holder.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+	let mut redemption_account_data = accounts.redemption.data.borrow_mut();
+	let redemption = Redemption::load_mut(&mut redemption_account_data)?;
+
+	redemption.holder = *accounts.holder.key;
+	redemption.shipping_hash = args.shipping_hash;
+	redemption.requested_at = now;
+	redemption.updated_at = now;
+	redemption.id = args.id;
+	redemption.bump = redemption_bump;
+	redemption.escrow_bump = escrow_bump;
+	redemption.status = RedemptionStatus::Requested as u8;
+
+	AlloyEvent::RedemptionRequested {
+		id: redemption.id,
+		holder: redemption.holder,
+		shipping_hash: redemption.shipping_hash,
+	}.emit();
+
+	Ok(())
+}
+
+/// Advances a redemption from `from` to `to` on behalf of a registered custodian.
+pub fn process_redemption_step(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RedemptionArgs,
+	from: RedemptionStatus,
+	to: RedemptionStatus,
+) -> ProgramResult {
+	let accounts = RedemptionStepAccounts::parse(program_id, accounts, args.id)?;
+
+	let mut redemption_account_data = accounts.redemption.data.borrow_mut();
+	let redemption = Redemption::load_mut(&mut redemption_account_data)?;
+
+	redemption.transition(from, to, Clock::from_account_info(accounts.clock)?.unix_timestamp)?;
+
+	AlloyEvent::RedemptionUpdated {
+		id: redemption.id,
+		status: to,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_confirm_delivery(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RedemptionArgs,
+) -> ProgramResult {
+	let accounts = ConfirmDeliveryAccounts::parse(program_id, accounts, args.id)?;
 
-	alloy_data.uri = match new_uri {
-		Some(new_uri) => new_uri,
-		None => alloy_data.uri
+	let redemption_bump = {
+		let mut redemption_account_data = accounts.redemption.data.borrow_mut();
+		let redemption = Redemption::load_mut(&mut redemption_account_data)?;
+
+		redemption.transition(RedemptionStatus::Shipped, RedemptionStatus::Delivered, Clock::from_account_info(accounts.clock)?.unix_timestamp)?;
+		redemption.bump
 	};
+	let redemption_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], REDEMPTION_SEED.as_bytes(), &[redemption_bump]];
 
-	alloy_data.last_price = alloy_data.listed_price;
-    alloy_data.listed_price = match new_price {
-        Some(price) => {
-            price
-        }
-        None => {
-            alloy_data.listed_price
-        }
-    };
+	let amount = spl_token::state::Account::unpack(&accounts.escrow.data.borrow())?.amount;
+
+	invoke_signed(
+		&spl_token::instruction::burn(&spl_token::id(), accounts.escrow.key, accounts.mint.key, accounts.redemption.key, &[], amount)?,
+		&[
+			accounts.escrow.clone(),
+			accounts.mint.clone(),
+			accounts.redemption.clone(),
+			accounts.token_program.clone(),
+		],
+		&[redemption_signer_seeds],
+	)?;
+
+	close_escrow(accounts.escrow, accounts.holder, accounts.redemption, accounts.token_program, redemption_signer_seeds)?;
+
+	let mut alloy_data_account_data = accounts.alloy_data.data.borrow_mut();
+	let alloy_data = AlloyData::load_mut(&mut alloy_data_account_data)?;
+
+	alloy_data.redeemed = 1;
+
+	AlloyEvent::RedemptionUpdated {
+		id: args.id,
+		status: RedemptionStatus::Delivered,
+	}.emit();
+
+	AlloyEvent::Burned {
+		id: args.id,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_cancel_redemption(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RedemptionArgs,
+) -> ProgramResult {
+	let accounts = CancelRedemptionAccounts::parse(program_id, accounts, args.id)?;
+
+	let redemption = *Redemption::load(&accounts.redemption.data.borrow())?;
+
+	if !redemption.status().is_some_and(RedemptionStatus::is_cancellable) {
+		return Err(CustomError::InvalidRedemptionState.into());
+	}
+
+	let redemption_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], REDEMPTION_SEED.as_bytes(), &[redemption.bump]];
+
+	release_escrow(accounts.escrow, accounts.holder_token_account, accounts.redemption, accounts.token_program, redemption_signer_seeds)?;
+	close_escrow(accounts.escrow, accounts.holder, accounts.redemption, accounts.token_program, redemption_signer_seeds)?;
+	close_program_account(accounts.redemption, accounts.holder)?;
+
+	AlloyEvent::RedemptionCancelled {
+		id: args.id,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_record_custody(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RecordCustodyArgs,
+) -> ProgramResult {
+	let (accounts, log_bump, page, page_bump) = RecordCustodyAccounts::parse(program_id, accounts, args.id)?;
+
+	validate_location_code(&args.location)?;
+
+	if args.document_hash.iter().all(|&byte| byte == 0) {
+		return Err(CustomError::MissingDocumentHash.into());
+	}
+
+	let rent = Rent::from_account_info(accounts.rent)?;
+
+	if accounts.custody_log.owner != program_id {
+		create_program_account(
+			program_id,
+			accounts.payer,
+			accounts.custody_log,
+			accounts.system_program,
+			&rent,
+			CUSTODY_LOG_DATA_SIZE,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], CUSTODY_SEED.as_bytes(), &[log_bump]],
+		)?;
+
+		let mut custody_log_account_data = accounts.custody_log.data.borrow_mut();
+		let custody_log = CustodyLog::load_mut(&mut custody_log_account_data)?;
+
+		custody_log.alloy = *accounts.alloy_data.key;
+		custody_log.id = args.id;
+		custody_log.bump = log_bump;
+	}
+
+	let mut custody_log_account_data = accounts.custody_log.data.borrow_mut();
+	let custody_log = CustodyLog::load_mut(&mut custody_log_account_data)?;
+
+	// A full page is never reopened: the next entry starts the following one.
+	if custody_log.entry_count as usize == page as usize * CUSTODY_ENTRIES_PER_PAGE {
+		assert_uninitialized(accounts.custody_page)?;
+		create_program_account(
+			program_id,
+			accounts.payer,
+			accounts.custody_page,
+			accounts.system_program,
+			&rent,
+			CUSTODY_PAGE_DATA_SIZE,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], CUSTODY_SEED.as_bytes(), &page.to_le_bytes(), &[page_bump]],
+		)?;
 
-	let mut array_of_zeroes = vec![];
+		let mut custody_page_account_data = accounts.custody_page.data.borrow_mut();
+		let custody_page = CustodyLogPage::load_mut(&mut custody_page_account_data)?;
 
-	while array_of_zeroes.len() > MAX_NAME_LENGTH - alloy_data.name.len() {
-		array_of_zeroes.push(0u8);
+		custody_page.page = page;
+		custody_page.id = args.id;
+		custody_page.bump = page_bump;
+	} else if accounts.custody_page.owner != program_id {
+		return Err(CustomError::InvalidCustodyLogKey.into());
 	}
 
-	alloy_data.name = alloy_data.name.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
+	let entry = CustodyEntry {
+		from: custody_log.current_custodian,
+		to: *accounts.custodian.key,
+		timestamp: Clock::from_account_info(accounts.clock)?.unix_timestamp,
+		document_hash: args.document_hash,
+		location: pad_location_code(&args.location),
+	};
+
+	CustodyLogPage::load_mut(&mut accounts.custody_page.data.borrow_mut())?.push(entry)?;
+
+	let index = custody_log.entry_count;
+	custody_log.entry_count += 1;
+	custody_log.current_custodian = entry.to;
+
+	AlloyEvent::CustodyRecorded {
+		id: args.id,
+		index,
+		from: entry.from,
+		to: entry.to,
+		location: args.location,
+		document_hash: entry.document_hash,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_split_alloy(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: SplitAlloyArgs,
+) -> ProgramResult {
+	let (accounts, child_alloy_data_bump, child_composition_bump) = SplitAlloyAccounts::parse(program_id, accounts, args.id, args.child_id)?;
+
+	let parent = {
+		let mut parent_account_data = accounts.parent_alloy_data.data.borrow_mut();
+		let parent = AlloyData::load_mut(&mut parent_account_data)?;
+
+		assert_active(parent)?;
+		assert_not_rented(parent, Clock::from_account_info(accounts.clock)?.unix_timestamp)?;
+		assert_holds_alloy_nft(accounts.holder_token_account, parent, accounts.holder.key)?;
+
+		if args.quantity_grams == 0 || args.quantity_grams >= parent.quantity_grams {
+			return Err(CustomError::InvalidQuantity.into());
+		}
+
+		parent.quantity_grams -= args.quantity_grams;
+		*parent
+	};
+
+	let rent = Rent::from_account_info(accounts.rent)?;
+
+	mint_child_nft(&accounts, &rent)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.child_alloy_data,
+		accounts.system_program,
+		&rent,
+		MAX_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.child_id], &[child_alloy_data_bump]],
+	)?;
+
+	let mut child_account_data = accounts.child_alloy_data.data.borrow_mut();
+	let child = AlloyData::load_mut(&mut child_account_data)?;
+
+	// The child keeps the parent's metadata but not its certificate, which vouched for the
+	// lot as a whole.
+	*child = AlloyData {
+		id: args.child_id,
+		bump: child_alloy_data_bump,
+		owner_address: *accounts.child_mint.key,
+		last_price: 0,
+		listed_price: args.listed_price,
+		quantity_grams: args.quantity_grams,
+		parent: *accounts.parent_alloy_data.key,
+		inspector: Pubkey::default(),
+		certificate_hash: [0; 32],
+		certificate_expires_at: 0,
+		..parent
+	};
+
+	if accounts.parent_composition.owner == program_id {
+		create_program_account(
+			program_id,
+			accounts.payer,
+			accounts.child_composition,
+			accounts.system_program,
+			&rent,
+			COMPOSITION_DATA_SIZE,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[args.child_id], COMPOSITION_SEED.as_bytes(), &[child_composition_bump]],
+		)?;
 
-	let mut array_of_zeroes = vec![];
+		let parent_composition = *AlloyComposition::load(&accounts.parent_composition.data.borrow())?;
+		let mut child_composition_account_data = accounts.child_composition.data.borrow_mut();
 
-	while array_of_zeroes.len() > MAX_URI_LENGTH - alloy_data.uri.len() {
-		array_of_zeroes.push(0u8);
+		*AlloyComposition::load_mut(&mut child_composition_account_data)? = AlloyComposition {
+			id: args.child_id,
+			bump: child_composition_bump,
+			..parent_composition
+		};
 	}
 
-	alloy_data.uri = alloy_data.uri.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
+	AlloyEvent::Created {
+		id: child.id,
+		alloy: *accounts.child_alloy_data.key,
+		owner_address: child.owner_address,
+		listed_price: child.listed_price,
+	}.emit();
 
-	alloy_data.serialize(&mut *alloy_data_account_info.data.borrow_mut())?;
-	msg!("Alloy Data Replaced!");
+	AlloyEvent::Split {
+		id: args.id,
+		child_id: args.child_id,
+		quantity_grams: args.quantity_grams,
+	}.emit();
 
 	Ok(())
 }
 
-pub fn assert_initialized<T: Pack + IsInitialized>(
-	account_info: &AccountInfo,
-) -> Result<T, ProgramError> {
-	let account: T = T::unpack_unchecked(&account_info.data.borrow())?;
-    	if !account.is_initialized() {
-        	Err(CustomError::Uninitialized.into())
-    	} else {
-        	Ok(account)
-    	}
-}
\ No newline at end of file
+/// Creates the child lot's mint and the holder's token account for it, mints the single
+/// token and drops the mint authority so the supply stays fixed at one.
+fn mint_child_nft(accounts: &SplitAlloyAccounts<&AccountInfo>, rent: &Rent) -> ProgramResult {
+	invoke(
+		&system_instruction::create_account(
+			accounts.payer.key,
+			accounts.child_mint.key,
+			rent.minimum_balance(spl_token::state::Mint::LEN),
+			spl_token::state::Mint::LEN as u64,
+			&spl_token::id(),
+		),
+		&[accounts.payer.clone(), accounts.child_mint.clone(), accounts.system_program.clone()],
+	)?;
+
+	invoke(
+		&spl_token::instruction::initialize_mint2(&spl_token::id(), accounts.child_mint.key, accounts.holder.key, None, 0)?,
+		&[accounts.child_mint.clone(), accounts.token_program.clone()],
+	)?;
+
+	invoke(
+		&system_instruction::create_account(
+			accounts.payer.key,
+			accounts.holder_child_token_account.key,
+			rent.minimum_balance(spl_token::state::Account::LEN),
+			spl_token::state::Account::LEN as u64,
+			&spl_token::id(),
+		),
+		&[accounts.payer.clone(), accounts.holder_child_token_account.clone(), accounts.system_program.clone()],
+	)?;
+
+	invoke(
+		&spl_token::instruction::initialize_account3(
+			&spl_token::id(),
+			accounts.holder_child_token_account.key,
+			accounts.child_mint.key,
+			accounts.holder.key,
+		)?,
+		&[accounts.holder_child_token_account.clone(), accounts.child_mint.clone(), accounts.token_program.clone()],
+	)?;
+
+	invoke(
+		&spl_token::instruction::mint_to(
+			&spl_token::id(),
+			accounts.child_mint.key,
+			accounts.holder_child_token_account.key,
+			accounts.holder.key,
+			&[],
+			1,
+		)?,
+		&[
+			accounts.child_mint.clone(),
+			accounts.holder_child_token_account.clone(),
+			accounts.holder.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	invoke(
+		&spl_token::instruction::set_authority(
+			&spl_token::id(),
+			accounts.child_mint.key,
+			None,
+			spl_token::instruction::AuthorityType::MintTokens,
+			accounts.holder.key,
+			&[],
+		)?,
+		&[accounts.child_mint.clone(), accounts.holder.clone(), accounts.token_program.clone()],
+	)
+}
+
+/// The composition recorded in `composition_info`, or `None` if the alloy has none yet.
+fn recorded_composition(program_id: &Pubkey, composition_info: &AccountInfo) -> Result<Option<AlloyComposition>, ProgramError> {
+	if composition_info.owner != program_id {
+		return Ok(None);
+	}
+
+	Ok(Some(*AlloyComposition::load(&composition_info.data.borrow())?))
+}
+
+pub fn process_merge_alloys(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: MergeAlloysArgs,
+) -> ProgramResult {
+	if args.id == args.source_id {
+		return Err(CustomError::MergeIntoSelf.into());
+	}
+
+	let accounts = MergeAlloysAccounts::parse(program_id, accounts, args.id, args.source_id)?;
+
+	let mut alloy_data_account_data = accounts.alloy_data.data.borrow_mut();
+	let alloy_data = AlloyData::load_mut(&mut alloy_data_account_data)?;
+	let mut source_account_data = accounts.source_alloy_data.data.borrow_mut();
+	let source = AlloyData::load_mut(&mut source_account_data)?;
+
+	assert_active(alloy_data)?;
+	assert_active(source)?;
+
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+	assert_not_rented(alloy_data, now)?;
+	assert_not_rented(source, now)?;
+
+	assert_holds_alloy_nft(accounts.holder_token_account, alloy_data, accounts.holder.key)?;
+	assert_holds_alloy_nft(accounts.holder_source_token_account, source, accounts.holder.key)?;
+
+	if *accounts.source_mint.key != source.owner_address {
+		return Err(CustomError::OwnerMismatch.into());
+	}
+
+	let same_composition = match (recorded_composition(program_id, accounts.composition)?, recorded_composition(program_id, accounts.source_composition)?) {
+		(Some(composition), Some(source_composition)) => composition.is_same_spec(&source_composition),
+		(None, None) => true,
+		_ => false,
+	};
+
+	if !alloy_data.is_same_grade(source) || !same_composition {
+		return Err(CustomError::GradeMismatch.into());
+	}
+
+	let quantity_grams = source.quantity_grams;
+
+	alloy_data.quantity_grams = alloy_data.quantity_grams
+		.checked_add(quantity_grams)
+		.ok_or(ProgramError::ArithmeticOverflow)?;
+
+	let amount = spl_token::state::Account::unpack(&accounts.holder_source_token_account.data.borrow())?.amount;
+
+	invoke(
+		&spl_token::instruction::burn(&spl_token::id(), accounts.holder_source_token_account.key, accounts.source_mint.key, accounts.holder.key, &[], amount)?,
+		&[
+			accounts.holder_source_token_account.clone(),
+			accounts.source_mint.clone(),
+			accounts.holder.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	source.quantity_grams = 0;
+	source.merged_into = *accounts.alloy_data.key;
+
+	AlloyEvent::Burned {
+		id: args.source_id,
+	}.emit();
+
+	AlloyEvent::Merged {
+		id: args.id,
+		source_id: args.source_id,
+		quantity_grams,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_create_vault(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: CreateVaultArgs,
+) -> ProgramResult {
+	let (accounts, vault_bump, escrow_bump) = CreateVaultAccounts::parse(program_id, accounts, args.id)?;
+
+	if args.share_supply == 0 || args.reserve_price == 0 {
+		return Err(CustomError::InvalidVaultTerms.into());
+	}
+
+	{
+		let alloy_data_account_data = accounts.alloy_data.data.borrow();
+		let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+
+		assert_active(alloy_data)?;
+		assert_not_rented(alloy_data, Clock::from_account_info(accounts.clock)?.unix_timestamp)?;
+		assert_holds_alloy_nft(accounts.curator_token_account, alloy_data, accounts.curator.key)?;
+
+		if *accounts.mint.key != alloy_data.owner_address {
+			return Err(CustomError::OwnerMismatch.into());
+		}
+	}
+
+	assert_share_mint(accounts.share_mint, accounts.vault.key)?;
+
+	let rent = Rent::from_account_info(accounts.rent)?;
+	let vault_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], VAULT_SEED.as_bytes(), &[vault_bump]];
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.vault,
+		accounts.system_program,
+		&rent,
+		VAULT_DATA_SIZE,
+		vault_signer_seeds,
+	)?;
+
+	create_program_account(
+		&spl_token::id(),
+		accounts.payer,
+		accounts.escrow,
+		accounts.system_program,
+		&rent,
+		spl_token::state::Account::LEN,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], VAULT_SEED.as_bytes(), ESCROW_SEED.as_bytes(), &[escrow_bump]],
+	)?;
+
+	invoke(
+		&spl_token::instruction::initialize_account3(&spl_token::id(), accounts.escrow.key, accounts.mint.key, accounts.vault.key)?,
+		&[accounts.escrow.clone(), accounts.mint.clone(), accounts.token_program.clone()],
+	)?;
+
+	let amount = spl_token::state::Account::unpack(&accounts.curator_token_account.data.borrow())?.amount;
+
+	invoke(
+		&spl_token::instruction::transfer(&spl_token::id(), accounts.curator_token_account.key, accounts.escrow.key, accounts.curator.key, &[], amount)?,
+		&[
+			accounts.curator_token_account.clone(),
+			accounts.escrow.clone(),
+			accounts.curator.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	invoke_signed(
+		&spl_token::instruction::mint_to(
+			&spl_token::id(),
+			accounts.share_mint.key,
+			accounts.curator_share_token_account.key,
+			accounts.vault.key,
+			&[],
+			args.share_supply,
+		)?,
+		&[
+			accounts.share_mint.clone(),
+			accounts.curator_share_token_account.clone(),
+			accounts.vault.clone(),
+			accounts.token_program.clone(),
+		],
+		&[vault_signer_seeds],
+	)?;
+
+	// The supply is final: dropping the mint authority keeps the vault from ever diluting it.
+	invoke_signed(
+		&spl_token::instruction::set_authority(
+			&spl_token::id(),
+			accounts.share_mint.key,
+			None,
+			spl_token::instruction::AuthorityType::MintTokens,
+			accounts.vault.key,
+			&[],
+		)?,
+		&[accounts.share_mint.clone(), accounts.vault.clone(), accounts.token_program.clone()],
+		&[vault_signer_seeds],
+	)?;
+
+	let mut vault_account_data = accounts.vault.data.borrow_mut();
+	let vault = Vault::load_mut(&mut vault_account_data)?;
+
+	vault.alloy = *accounts.alloy_data.key;
+	vault.curator = *accounts.curator.key;
+	vault.share_mint = *accounts.share_mint.key;
+	vault.share_supply = args.share_supply;
+	vault.reserve_price = args.reserve_price;
+	vault.id = args.id;
+	vault.bump = vault_bump;
+	vault.escrow_bump = escrow_bump;
+	vault.status = VaultStatus::Active as u8;
+
+	AlloyEvent::VaultCreated {
+		id: args.id,
+		vault: *accounts.vault.key,
+		share_mint: vault.share_mint,
+		share_supply: vault.share_supply,
+		reserve_price: vault.reserve_price,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_redeem_vault(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: VaultArgs,
+) -> ProgramResult {
+	let accounts = RedeemVaultAccounts::parse(program_id, accounts, args.id)?;
+
+	let vault = *Vault::load(&accounts.vault.data.borrow())?;
+	vault.assert_status(VaultStatus::Active)?;
+
+	let shares = assert_holds_shares(accounts.holder_share_token_account, &vault.share_mint, accounts.holder.key)?;
+	let outstanding = spl_token::state::Mint::unpack(&accounts.share_mint.data.borrow())?.supply;
+
+	if shares < outstanding {
+		return Err(CustomError::IncompleteShares.into());
+	}
+
+	burn_shares(accounts.holder_share_token_account, accounts.share_mint, accounts.holder, accounts.token_program, shares)?;
+
+	let vault_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], VAULT_SEED.as_bytes(), &[vault.bump]];
+
+	release_escrow(accounts.escrow, accounts.holder_token_account, accounts.vault, accounts.token_program, vault_signer_seeds)?;
+	close_escrow(accounts.escrow, accounts.holder, accounts.vault, accounts.token_program, vault_signer_seeds)?;
+	close_program_account(accounts.vault, accounts.holder)?;
+
+	AlloyEvent::VaultRedeemed {
+		id: args.id,
+		holder: *accounts.holder.key,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_buyout_vault(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: VaultArgs,
+) -> ProgramResult {
+	let accounts = BuyoutVaultAccounts::parse(program_id, accounts, args.id)?;
+
+	let vault = *Vault::load(&accounts.vault.data.borrow())?;
+	vault.assert_status(VaultStatus::Active)?;
+
+	invoke(
+		&system_instruction::transfer(accounts.buyer.key, accounts.vault.key, vault.reserve_price),
+		&[
+			accounts.buyer.clone(),
+			accounts.vault.clone(),
+			accounts.system_program.clone(),
+		],
+	)?;
+
+	let vault_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], VAULT_SEED.as_bytes(), &[vault.bump]];
+
+	release_escrow(accounts.escrow, accounts.buyer_token_account, accounts.vault, accounts.token_program, vault_signer_seeds)?;
+	close_escrow(accounts.escrow, accounts.buyer, accounts.vault, accounts.token_program, vault_signer_seeds)?;
+
+	let mut vault_account_data = accounts.vault.data.borrow_mut();
+	let vault = Vault::load_mut(&mut vault_account_data)?;
+
+	vault.status = VaultStatus::BoughtOut as u8;
+	vault.buyer = *accounts.buyer.key;
+	vault.proceeds = vault.reserve_price;
+
+	AlloyEvent::VaultBoughtOut {
+		id: args.id,
+		buyer: vault.buyer,
+		price: vault.reserve_price,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_claim_vault_proceeds(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: VaultArgs,
+) -> ProgramResult {
+	let accounts = ClaimVaultProceedsAccounts::parse(program_id, accounts, args.id)?;
+
+	let vault = *Vault::load(&accounts.vault.data.borrow())?;
+	vault.assert_status(VaultStatus::BoughtOut)?;
+
+	let shares = assert_holds_shares(accounts.holder_share_token_account, &vault.share_mint, accounts.holder.key)?;
+
+	if shares == 0 {
+		return Err(CustomError::NoShares.into());
+	}
+
+	let outstanding = spl_token::state::Mint::unpack(&accounts.share_mint.data.borrow())?.supply;
+	let lamports = vault.claimable(shares, outstanding);
+
+	burn_shares(accounts.holder_share_token_account, accounts.share_mint, accounts.holder, accounts.token_program, shares)?;
+
+	if shares == outstanding {
+		// The last shares take the rest of the proceeds along with the vault's rent.
+		close_program_account(accounts.vault, accounts.holder)?;
+	} else {
+		**accounts.vault.try_borrow_mut_lamports()? -= lamports;
+		**accounts.holder.try_borrow_mut_lamports()? = accounts.holder.lamports()
+			.checked_add(lamports)
+			.ok_or(ProgramError::ArithmeticOverflow)?;
+
+		Vault::load_mut(&mut accounts.vault.data.borrow_mut())?.proceeds -= lamports;
+	}
+
+	AlloyEvent::VaultProceedsClaimed {
+		id: args.id,
+		holder: *accounts.holder.key,
+		shares,
+		lamports,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_create_rental(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: CreateRentalArgs,
+) -> ProgramResult {
+	let (accounts, rental_bump, escrow_bump) = CreateRentalAccounts::parse(program_id, accounts, args.id)?;
+
+	if args.price_per_day == 0 || args.max_days == 0 {
+		return Err(CustomError::InvalidRentalTerms.into());
+	}
+
+	{
+		let alloy_data_account_data = accounts.alloy_data.data.borrow();
+		let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+
+		assert_active(alloy_data)?;
+		assert_holds_alloy_nft(accounts.lessor_token_account, alloy_data, accounts.lessor.key)?;
+
+		if *accounts.mint.key != alloy_data.owner_address {
+			return Err(CustomError::OwnerMismatch.into());
+		}
+	}
+
+	let rent = Rent::from_account_info(accounts.rent)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.rental,
+		accounts.system_program,
+		&rent,
+		RENTAL_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], RENTAL_SEED.as_bytes(), &[rental_bump]],
+	)?;
+
+	create_program_account(
+		&spl_token::id(),
+		accounts.payer,
+		accounts.escrow,
+		accounts.system_program,
+		&rent,
+		spl_token::state::Account::LEN,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], RENTAL_SEED.as_bytes(), ESCROW_SEED.as_bytes(), &[escrow_bump]],
+	)?;
+
+	invoke(
+		&spl_token::instruction::initialize_account3(&spl_token::id(), accounts.escrow.key, accounts.mint.key, accounts.rental.key)?,
+		&[accounts.escrow.clone(), accounts.mint.clone(), accounts.token_program.clone()],
+	)?;
+
+	let amount = spl_token::state::Account::unpack(&accounts.lessor_token_account.data.borrow())?.amount;
+
+	invoke(
+		&spl_token::instruction::approve(&spl_token::id(), accounts.lessor_token_account.key, accounts.rental.key, accounts.lessor.key, &[], amount)?,
+		&[
+			accounts.lessor_token_account.clone(),
+			accounts.rental.clone(),
+			accounts.lessor.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	let mut rental_account_data = accounts.rental.data.borrow_mut();
+	let rental = Rental::load_mut(&mut rental_account_data)?;
+
+	rental.lessor = *accounts.lessor.key;
+	rental.price_per_day = args.price_per_day;
+	rental.max_days = args.max_days;
+	rental.id = args.id;
+	rental.bump = rental_bump;
+	rental.escrow_bump = escrow_bump;
+
+	AlloyEvent::RentalOffered {
+		id: args.id,
+		lessor: rental.lessor,
+		price_per_day: rental.price_per_day,
+		max_days: rental.max_days,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_rent_alloy(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RentAlloyArgs,
+) -> ProgramResult {
+	let accounts = RentAlloyAccounts::parse(program_id, accounts, args.id)?;
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+
+	let mut rental = *Rental::load(&accounts.rental.data.borrow())?;
+	let price = rental.price_for(args.days)?;
+
+	{
+		let alloy_data_account_data = accounts.alloy_data.data.borrow();
+		let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+
+		assert_active(alloy_data)?;
+		assert_not_rented(alloy_data, now)?;
+	}
+
+	// A rental that expired without being ended leaves the NFT in escrow for the next one.
+	if spl_token::state::Account::unpack(&accounts.escrow.data.borrow())?.amount == 0 {
+		// An offer outlives a sale of the NFT, but only its current holder can rent the lot out.
+		assert_holds_alloy_nft(accounts.lessor_token_account, AlloyData::load(&accounts.alloy_data.data.borrow())?, accounts.lessor.key)?;
+
+		let amount = spl_token::state::Account::unpack(&accounts.lessor_token_account.data.borrow())?.amount;
+
+		invoke_signed(
+			&spl_token::instruction::transfer(&spl_token::id(), accounts.lessor_token_account.key, accounts.escrow.key, accounts.rental.key, &[], amount)?,
+			&[
+				accounts.lessor_token_account.clone(),
+				accounts.escrow.clone(),
+				accounts.rental.clone(),
+				accounts.token_program.clone(),
+			],
+			&[&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], RENTAL_SEED.as_bytes(), &[rental.bump]]],
+		)?;
+	}
+
+	invoke(
+		&system_instruction::transfer(accounts.renter.key, accounts.lessor.key, price),
+		&[
+			accounts.renter.clone(),
+			accounts.lessor.clone(),
+			accounts.system_program.clone(),
+		],
+	)?;
+
+	let expires_at = rental.start(*accounts.renter.key, args.days, now);
+	*Rental::load_mut(&mut accounts.rental.data.borrow_mut())? = rental;
+	AlloyData::load_mut(&mut accounts.alloy_data.data.borrow_mut())?.rented_until = expires_at;
+
+	AlloyEvent::Rented {
+		id: args.id,
+		renter: rental.renter,
+		price,
+		expires_at,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_end_rental(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: RentalArgs,
+) -> ProgramResult {
+	let accounts = EndRentalAccounts::parse(program_id, accounts, args.id)?;
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+
+	let rental = *Rental::load(&accounts.rental.data.borrow())?;
+	let escrowed = spl_token::state::Account::unpack(&accounts.escrow.data.borrow())?.amount > 0;
+
+	if rental.is_rented(now) {
+		if *accounts.authority.key != rental.renter {
+			return Err(CustomError::AlloyRented.into());
+		}
+
+		AlloyData::load_mut(&mut accounts.alloy_data.data.borrow_mut())?.rented_until = now;
+	} else if !escrowed && *accounts.authority.key != rental.lessor {
+		// Once a rental has run, anyone may end it to hand the NFT back; an offer nobody took
+		// up is the lessor's alone to withdraw.
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	let rental_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], RENTAL_SEED.as_bytes(), &[rental.bump]];
+
+	if escrowed {
+		assert_alloy_token_account(accounts.lessor_token_account, AlloyData::load(&accounts.alloy_data.data.borrow())?, &rental.lessor)?;
+		release_escrow(accounts.escrow, accounts.lessor_token_account, accounts.rental, accounts.token_program, rental_seeds)?;
+	} else {
+		let lessor_token_account = spl_token::state::Account::unpack(&accounts.lessor_token_account.data.borrow())?;
+
+		// The lessor signed above, so the approval left from `CreateRental` can be withdrawn.
+		if lessor_token_account.owner == rental.lessor && lessor_token_account.delegate == COption::Some(*accounts.rental.key) {
+			invoke(
+				&spl_token::instruction::revoke(&spl_token::id(), accounts.lessor_token_account.key, accounts.authority.key, &[])?,
+				&[
+					accounts.lessor_token_account.clone(),
+					accounts.authority.clone(),
+					accounts.token_program.clone(),
+				],
+			)?;
+		}
+	}
+
+	close_escrow(accounts.escrow, accounts.lessor, accounts.rental, accounts.token_program, rental_seeds)?;
+	close_program_account(accounts.rental, accounts.lessor)?;
+
+	AlloyEvent::RentalEnded {
+		id: args.id,
+		renter: rental.renter,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_create_installment_plan(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: CreateInstallmentPlanArgs,
+) -> ProgramResult {
+	let (accounts, plan_bump, escrow_bump) = CreateInstallmentPlanAccounts::parse(program_id, accounts, args.id)?;
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+
+	validate_installment_terms(args.total_price, args.payments, args.first_due_at, args.interval, args.forfeit_bps, now)?;
+
+	{
+		let alloy_data_account_data = accounts.alloy_data.data.borrow();
+		let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+
+		assert_active(alloy_data)?;
+		assert_not_rented(alloy_data, now)?;
+		assert_holds_alloy_nft(accounts.seller_token_account, alloy_data, accounts.seller.key)?;
+
+		if *accounts.mint.key != alloy_data.owner_address {
+			return Err(CustomError::OwnerMismatch.into());
+		}
+	}
+
+	let rent = Rent::from_account_info(accounts.rent)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.plan,
+		accounts.system_program,
+		&rent,
+		INSTALLMENT_PLAN_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], INSTALLMENT_SEED.as_bytes(), &[plan_bump]],
+	)?;
+
+	create_program_account(
+		&spl_token::id(),
+		accounts.payer,
+		accounts.escrow,
+		accounts.system_program,
+		&rent,
+		spl_token::state::Account::LEN,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], INSTALLMENT_SEED.as_bytes(), ESCROW_SEED.as_bytes(), &[escrow_bump]],
+	)?;
+
+	invoke(
+		&spl_token::instruction::initialize_account3(&spl_token::id(), accounts.escrow.key, accounts.mint.key, accounts.plan.key)?,
+		&[accounts.escrow.clone(), accounts.mint.clone(), accounts.token_program.clone()],
+	)?;
+
+	let amount = spl_token::state::Account::unpack(&accounts.seller_token_account.data.borrow())?.amount;
+
+	invoke(
+		&spl_token::instruction::transfer(&spl_token::id(), accounts.seller_token_account.key, accounts.escrow.key, accounts.seller.key, &[], amount)?,
+		&[
+			accounts.seller_token_account.clone(),
+			accounts.escrow.clone(),
+			accounts.seller.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	let mut plan_account_data = accounts.plan.data.borrow_mut();
+	let plan = InstallmentPlan::load_mut(&mut plan_account_data)?;
+
+	plan.seller = *accounts.seller.key;
+	plan.buyer = args.buyer;
+	plan.total_price = args.total_price;
+	plan.first_due_at = args.first_due_at;
+	plan.interval = args.interval;
+	plan.forfeit_bps = args.forfeit_bps;
+	plan.payments = args.payments;
+	plan.id = args.id;
+	plan.bump = plan_bump;
+	plan.escrow_bump = escrow_bump;
+
+	AlloyEvent::InstallmentPlanCreated {
+		id: args.id,
+		seller: plan.seller,
+		buyer: plan.buyer,
+		total_price: plan.total_price,
+		payments: plan.payments,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_pay_installment(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: InstallmentPlanArgs,
+) -> ProgramResult {
+	let accounts = PayInstallmentAccounts::parse(program_id, accounts, args.id)?;
+
+	let mut plan = *InstallmentPlan::load(&accounts.plan.data.borrow())?;
+
+	if Clock::from_account_info(accounts.clock)?.unix_timestamp > plan.next_due_at() {
+		return Err(CustomError::InstallmentOverdue.into());
+	}
+
+	let amount = plan.next_payment();
+
+	invoke(
+		&system_instruction::transfer(accounts.buyer.key, accounts.plan.key, amount),
+		&[
+			accounts.buyer.clone(),
+			accounts.plan.clone(),
+			accounts.system_program.clone(),
+		],
+	)?;
+
+	plan.paid += amount;
+	plan.payments_made += 1;
+
+	AlloyEvent::InstallmentPaid {
+		id: args.id,
+		buyer: plan.buyer,
+		amount,
+		payments_made: plan.payments_made,
+	}.emit();
+
+	if !plan.is_paid_off() {
+		*InstallmentPlan::load_mut(&mut accounts.plan.data.borrow_mut())? = plan;
+		return Ok(());
+	}
+
+	let plan_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], INSTALLMENT_SEED.as_bytes(), &[plan.bump]];
+
+	release_escrow(accounts.escrow, accounts.buyer_token_account, accounts.plan, accounts.token_program, plan_signer_seeds)?;
+	close_escrow(accounts.escrow, accounts.seller, accounts.plan, accounts.token_program, plan_signer_seeds)?;
+	// Every payment plus the plan's rent goes to the seller.
+	close_program_account(accounts.plan, accounts.seller)?;
+
+	AlloyData::load_mut(&mut accounts.alloy_data.data.borrow_mut())?.last_price = plan.total_price;
+
+	AlloyEvent::InstallmentPlanCompleted {
+		id: args.id,
+		buyer: plan.buyer,
+		total_price: plan.total_price,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_reclaim_installment_plan(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: InstallmentPlanArgs,
+) -> ProgramResult {
+	let accounts = ReclaimInstallmentPlanAccounts::parse(program_id, accounts, args.id)?;
+
+	let plan = *InstallmentPlan::load(&accounts.plan.data.borrow())?;
+
+	if Clock::from_account_info(accounts.clock)?.unix_timestamp <= plan.next_due_at() {
+		return Err(CustomError::InstallmentNotOverdue.into());
+	}
+
+	let plan_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], INSTALLMENT_SEED.as_bytes(), &[plan.bump]];
+
+	release_escrow(accounts.escrow, accounts.seller_token_account, accounts.plan, accounts.token_program, plan_signer_seeds)?;
+	close_escrow(accounts.escrow, accounts.seller, accounts.plan, accounts.token_program, plan_signer_seeds)?;
+
+	let refunded = plan.refund();
+
+	**accounts.plan.try_borrow_mut_lamports()? -= refunded;
+	**accounts.buyer.try_borrow_mut_lamports()? = accounts.buyer.lamports()
+		.checked_add(refunded)
+		.ok_or(ProgramError::ArithmeticOverflow)?;
+
+	// The forfeited part stays on the plan and goes to the seller with its rent.
+	close_program_account(accounts.plan, accounts.seller)?;
+
+	AlloyEvent::InstallmentPlanDefaulted {
+		id: args.id,
+		buyer: plan.buyer,
+		refunded,
+		forfeited: plan.paid - refunded,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_create_bundle(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: CreateBundleArgs,
+) -> ProgramResult {
+	validate_bundle(&args.ids, args.price)?;
+
+	let (accounts, lots, bundle_bump, escrow_bumps) = CreateBundleAccounts::parse(program_id, accounts, args.nonce, &args.ids)?;
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+	let rent = Rent::from_account_info(accounts.rent)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.bundle,
+		accounts.system_program,
+		&rent,
+		BUNDLE_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), BUNDLE_SEED.as_bytes(), accounts.seller.key.as_ref(), &args.nonce.to_le_bytes(), &[bundle_bump]],
+	)?;
+
+	for ((lot, &id), &escrow_bump) in lots.iter().zip(&args.ids).zip(&escrow_bumps) {
+		{
+			let alloy_data_account_data = lot.alloy_data.data.borrow();
+			let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+
+			assert_active(alloy_data)?;
+			assert_not_rented(alloy_data, now)?;
+			assert_holds_alloy_nft(lot.seller_token_account, alloy_data, accounts.seller.key)?;
+
+			if *lot.mint.key != alloy_data.owner_address {
+				return Err(CustomError::OwnerMismatch.into());
+			}
+		}
+
+		create_program_account(
+			&spl_token::id(),
+			accounts.payer,
+			lot.escrow,
+			accounts.system_program,
+			&rent,
+			spl_token::state::Account::LEN,
+			&[PREFIX.as_bytes(), program_id.as_ref(), &[id], BUNDLE_SEED.as_bytes(), ESCROW_SEED.as_bytes(), &[escrow_bump]],
+		)?;
+
+		invoke(
+			&spl_token::instruction::initialize_account3(&spl_token::id(), lot.escrow.key, lot.mint.key, accounts.bundle.key)?,
+			&[lot.escrow.clone(), lot.mint.clone(), accounts.token_program.clone()],
+		)?;
+
+		let amount = spl_token::state::Account::unpack(&lot.seller_token_account.data.borrow())?.amount;
+
+		invoke(
+			&spl_token::instruction::transfer(&spl_token::id(), lot.seller_token_account.key, lot.escrow.key, accounts.seller.key, &[], amount)?,
+			&[
+				lot.seller_token_account.clone(),
+				lot.escrow.clone(),
+				accounts.seller.clone(),
+				accounts.token_program.clone(),
+			],
+		)?;
+	}
+
+	let mut bundle_account_data = accounts.bundle.data.borrow_mut();
+	let bundle = Bundle::load_mut(&mut bundle_account_data)?;
+
+	bundle.seller = *accounts.seller.key;
+	bundle.price = args.price;
+	bundle.nonce = args.nonce;
+	bundle.created_at = now;
+	bundle.ids[..args.ids.len()].copy_from_slice(&args.ids);
+	bundle.escrow_bumps[..escrow_bumps.len()].copy_from_slice(&escrow_bumps);
+	bundle.len = args.ids.len() as u8;
+	bundle.bump = bundle_bump;
+
+	AlloyEvent::BundleCreated {
+		bundle: *accounts.bundle.key,
+		seller: bundle.seller,
+		ids: args.ids,
+		price: bundle.price,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_purchase_bundle(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: BundleArgs,
+) -> ProgramResult {
+	let (accounts, lots) = PurchaseBundleAccounts::parse(program_id, accounts, args.nonce)?;
+
+	let bundle = *Bundle::load(&accounts.bundle.data.borrow())?;
+
+	invoke(
+		&system_instruction::transfer(accounts.buyer.key, accounts.seller.key, bundle.price),
+		&[
+			accounts.buyer.clone(),
+			accounts.seller.clone(),
+			accounts.system_program.clone(),
+		],
+	)?;
+
+	let weights = lots.iter()
+		.map(|lot| Ok(AlloyData::load(&lot.alloy_data.data.borrow())?.quantity_grams))
+		.collect::<Result<Vec<u64>, ProgramError>>()?;
+	let bundle_signer_seeds: &[&[u8]] = &[
+		PREFIX.as_bytes(),
+		program_id.as_ref(),
+		BUNDLE_SEED.as_bytes(),
+		bundle.seller.as_ref(),
+		&args.nonce.to_le_bytes(),
+		&[bundle.bump],
+	];
+
+	for (lot, part) in lots.iter().zip(split_price(bundle.price, &weights)) {
+		release_escrow(lot.escrow, lot.buyer_token_account, accounts.bundle, accounts.token_program, bundle_signer_seeds)?;
+		close_escrow(lot.escrow, accounts.seller, accounts.bundle, accounts.token_program, bundle_signer_seeds)?;
+
+		AlloyData::load_mut(&mut lot.alloy_data.data.borrow_mut())?.last_price = part;
+	}
+
+	close_program_account(accounts.bundle, accounts.seller)?;
+
+	AlloyEvent::BundlePurchased {
+		bundle: *accounts.bundle.key,
+		seller: bundle.seller,
+		buyer: *accounts.buyer.key,
+		price: bundle.price,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_cancel_bundle(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: BundleArgs,
+) -> ProgramResult {
+	let (accounts, lots) = CancelBundleAccounts::parse(program_id, accounts, args.nonce)?;
+
+	let bundle = *Bundle::load(&accounts.bundle.data.borrow())?;
+	let bundle_signer_seeds: &[&[u8]] = &[
+		PREFIX.as_bytes(),
+		program_id.as_ref(),
+		BUNDLE_SEED.as_bytes(),
+		bundle.seller.as_ref(),
+		&args.nonce.to_le_bytes(),
+		&[bundle.bump],
+	];
+
+	for lot in &lots {
+		release_escrow(lot.escrow, lot.seller_token_account, accounts.bundle, accounts.token_program, bundle_signer_seeds)?;
+		close_escrow(lot.escrow, accounts.seller, accounts.bundle, accounts.token_program, bundle_signer_seeds)?;
+	}
+
+	close_program_account(accounts.bundle, accounts.seller)?;
+
+	AlloyEvent::BundleCancelled {
+		bundle: *accounts.bundle.key,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_propose_swap(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: ProposeSwapArgs,
+) -> ProgramResult {
+	if args.wanted_id == args.id {
+		return Err(CustomError::InvalidSwap.into());
+	}
+
+	let (accounts, swap_bump, escrow_bump) = ProposeSwapAccounts::parse(program_id, accounts, args.id, args.wanted_id)?;
+	let now = Clock::from_account_info(accounts.clock)?.unix_timestamp;
+
+	{
+		let alloy_data_account_data = accounts.alloy_data.data.borrow();
+		let alloy_data = AlloyData::load(&alloy_data_account_data)?;
+
+		assert_active(alloy_data)?;
+		assert_not_rented(alloy_data, now)?;
+		assert_holds_alloy_nft(accounts.proposer_token_account, alloy_data, accounts.proposer.key)?;
+
+		if *accounts.mint.key != alloy_data.owner_address {
+			return Err(CustomError::OwnerMismatch.into());
+		}
+
+		assert_active(AlloyData::load(&accounts.wanted_alloy_data.data.borrow())?)?;
+	}
+
+	let rent = Rent::from_account_info(accounts.rent)?;
+
+	create_program_account(
+		program_id,
+		accounts.payer,
+		accounts.swap,
+		accounts.system_program,
+		&rent,
+		SWAP_DATA_SIZE,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], SWAP_SEED.as_bytes(), &[swap_bump]],
+	)?;
+
+	create_program_account(
+		&spl_token::id(),
+		accounts.payer,
+		accounts.escrow,
+		accounts.system_program,
+		&rent,
+		spl_token::state::Account::LEN,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], SWAP_SEED.as_bytes(), ESCROW_SEED.as_bytes(), &[escrow_bump]],
+	)?;
+
+	invoke(
+		&spl_token::instruction::initialize_account3(&spl_token::id(), accounts.escrow.key, accounts.mint.key, accounts.swap.key)?,
+		&[accounts.escrow.clone(), accounts.mint.clone(), accounts.token_program.clone()],
+	)?;
+
+	let amount = spl_token::state::Account::unpack(&accounts.proposer_token_account.data.borrow())?.amount;
+
+	invoke(
+		&spl_token::instruction::transfer(&spl_token::id(), accounts.proposer_token_account.key, accounts.escrow.key, accounts.proposer.key, &[], amount)?,
+		&[
+			accounts.proposer_token_account.clone(),
+			accounts.escrow.clone(),
+			accounts.proposer.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	let mut swap_account_data = accounts.swap.data.borrow_mut();
+	let swap = Swap::load_mut(&mut swap_account_data)?;
+
+	swap.proposer = *accounts.proposer.key;
+	swap.top_up = args.top_up;
+	swap.created_at = now;
+	swap.id = args.id;
+	swap.wanted_id = args.wanted_id;
+	swap.bump = swap_bump;
+	swap.escrow_bump = escrow_bump;
+
+	AlloyEvent::SwapProposed {
+		id: args.id,
+		proposer: swap.proposer,
+		wanted_id: swap.wanted_id,
+		top_up: swap.top_up,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_accept_swap(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: SwapArgs,
+) -> ProgramResult {
+	let accounts = AcceptSwapAccounts::parse(program_id, accounts, args.id)?;
+
+	let swap = *Swap::load(&accounts.swap.data.borrow())?;
+
+	{
+		let wanted_alloy_data_account_data = accounts.wanted_alloy_data.data.borrow();
+		let wanted_alloy_data = AlloyData::load(&wanted_alloy_data_account_data)?;
+
+		assert_active(wanted_alloy_data)?;
+		assert_not_rented(wanted_alloy_data, Clock::from_account_info(accounts.clock)?.unix_timestamp)?;
+		assert_holds_alloy_nft(accounts.counterparty_token_account, wanted_alloy_data, accounts.counterparty.key)?;
+		assert_alloy_token_account(accounts.proposer_token_account, wanted_alloy_data, &swap.proposer)?;
+	}
+
+	if swap.top_up > 0 {
+		invoke(
+			&system_instruction::transfer(accounts.counterparty.key, accounts.proposer.key, swap.top_up),
+			&[
+				accounts.counterparty.clone(),
+				accounts.proposer.clone(),
+				accounts.system_program.clone(),
+			],
+		)?;
+	}
+
+	let amount = spl_token::state::Account::unpack(&accounts.counterparty_token_account.data.borrow())?.amount;
+
+	invoke(
+		&spl_token::instruction::transfer(
+			&spl_token::id(),
+			accounts.counterparty_token_account.key,
+			accounts.proposer_token_account.key,
+			accounts.counterparty.key,
+			&[],
+			amount,
+		)?,
+		&[
+			accounts.counterparty_token_account.clone(),
+			accounts.proposer_token_account.clone(),
+			accounts.counterparty.clone(),
+			accounts.token_program.clone(),
+		],
+	)?;
+
+	let swap_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], SWAP_SEED.as_bytes(), &[swap.bump]];
+
+	release_escrow(accounts.escrow, accounts.counterparty_offered_token_account, accounts.swap, accounts.token_program, swap_signer_seeds)?;
+	close_escrow(accounts.escrow, accounts.proposer, accounts.swap, accounts.token_program, swap_signer_seeds)?;
+	close_program_account(accounts.swap, accounts.proposer)?;
+
+	AlloyEvent::SwapAccepted {
+		id: args.id,
+		wanted_id: swap.wanted_id,
+		proposer: swap.proposer,
+		counterparty: *accounts.counterparty.key,
+		top_up: swap.top_up,
+	}.emit();
+
+	Ok(())
+}
+
+pub fn process_cancel_swap(
+	program_id: &Pubkey,
+	accounts: &[AccountInfo],
+	args: SwapArgs,
+) -> ProgramResult {
+	let accounts = CancelSwapAccounts::parse(program_id, accounts, args.id)?;
+
+	let swap = *Swap::load(&accounts.swap.data.borrow())?;
+	let swap_signer_seeds: &[&[u8]] = &[PREFIX.as_bytes(), program_id.as_ref(), &[args.id], SWAP_SEED.as_bytes(), &[swap.bump]];
+
+	release_escrow(accounts.escrow, accounts.proposer_token_account, accounts.swap, accounts.token_program, swap_signer_seeds)?;
+	close_escrow(accounts.escrow, accounts.proposer, accounts.swap, accounts.token_program, swap_signer_seeds)?;
+	close_program_account(accounts.swap, accounts.proposer)?;
+
+	AlloyEvent::SwapCancelled {
+		id: args.id,
+	}.emit();
+
+	Ok(())
+}
+
+/// Burns `amount` of the shares in `token_account`, authorised by its owner `holder`.
+fn burn_shares<'info>(
+	token_account: &AccountInfo<'info>,
+	share_mint: &AccountInfo<'info>,
+	holder: &AccountInfo<'info>,
+	token_program: &AccountInfo<'info>,
+	amount: u64,
+) -> ProgramResult {
+	invoke(
+		&spl_token::instruction::burn(&spl_token::id(), token_account.key, share_mint.key, holder.key, &[], amount)?,
+		&[token_account.clone(), share_mint.clone(), holder.clone(), token_program.clone()],
+	)
+}
+
+/// Closes an emptied escrow token account, sending its rent to `recipient`.
+fn close_escrow<'info>(
+	escrow: &AccountInfo<'info>,
+	recipient: &AccountInfo<'info>,
+	authority: &AccountInfo<'info>,
+	token_program: &AccountInfo<'info>,
+	authority_signer_seeds: &[&[u8]],
+) -> ProgramResult {
+	invoke_signed(
+		&spl_token::instruction::close_account(&spl_token::id(), escrow.key, recipient.key, authority.key, &[])?,
+		&[escrow.clone(), recipient.clone(), authority.clone(), token_program.clone()],
+		&[authority_signer_seeds],
+	)
+}
+
+/// Moves the whole balance of `escrow`, owned by the PDA `authority`, to `destination`.
+fn release_escrow<'info>(
+	escrow: &AccountInfo<'info>,
+	destination: &AccountInfo<'info>,
+	authority: &AccountInfo<'info>,
+	token_program: &AccountInfo<'info>,
+	authority_signer_seeds: &[&[u8]],
+) -> ProgramResult {
+	let amount = spl_token::state::Account::unpack(&escrow.data.borrow())?.amount;
+
+	invoke_signed(
+		&spl_token::instruction::transfer(&spl_token::id(), escrow.key, destination.key, authority.key, &[], amount)?,
+		&[escrow.clone(), destination.clone(), authority.clone(), token_program.clone()],
+		&[authority_signer_seeds],
+	)
+}
+
+/// Funds `account` for rent exemption at `size` bytes, then allocates and assigns it to the
+/// program by signing as the PDA behind `signer_seeds`. Lamports already sitting on the
+/// address are counted towards the rent, so pre-funding it cannot block creation.
+fn create_program_account<'info>(
+	program_id: &Pubkey,
+	payer: &AccountInfo<'info>,
+	account: &AccountInfo<'info>,
+	system_program: &AccountInfo<'info>,
+	rent: &Rent,
+	size: usize,
+	signer_seeds: &[&[u8]],
+) -> ProgramResult {
+	let req_lamports = rent.minimum_balance(size).max(1).saturating_sub(account.lamports());
+
+	if req_lamports > 0 {
+		invoke(
+			&system_instruction::transfer(payer.key, account.key, req_lamports),
+			&[payer.clone(), account.clone(), system_program.clone()],
+		)?;
+	}
+
+	let allocate_accounts = &[account.clone(), system_program.clone()];
+
+	invoke_signed(
+		&system_instruction::allocate(account.key, size as u64),
+		allocate_accounts,
+		&[signer_seeds],
+	)?;
+
+	invoke_signed(
+		&system_instruction::assign(account.key, program_id),
+		allocate_accounts,
+		&[signer_seeds],
+	)?;
+
+	Ok(())
+}
+
+/// Moves every lamport of a program-owned `account` to `recipient` and hands the emptied
+/// account back to the system program.
+fn close_program_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+	let lamports = account.lamports();
+
+	**recipient.try_borrow_mut_lamports()? = recipient.lamports()
+		.checked_add(lamports)
+		.ok_or(ProgramError::ArithmeticOverflow)?;
+	**account.try_borrow_mut_lamports()? = 0;
+
+	account.realloc(0, false)?;
+	account.assign(&system_program::id());
+
+	Ok(())
+}
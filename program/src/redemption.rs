@@ -0,0 +1,49 @@
+//! States a physical redemption moves through between the holder's request and delivery.
+
+use borsh::{ BorshSerialize, BorshDeserialize, BorshSchema };
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use solana_program::program_error::ProgramError;
+use crate::{
+	error::CustomError,
+	state::Redemption,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, FromPrimitive, BorshSerialize, BorshDeserialize, BorshSchema)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum RedemptionStatus {
+	/// The holder asked for the metal and escrowed the NFT.
+	Requested = 0,
+	/// The custodian accepted the request.
+	Acknowledged = 1,
+	Shipped = 2,
+	/// The metal arrived and the NFT was burned.
+	Delivered = 3,
+}
+
+impl RedemptionStatus {
+	/// Whether the holder may still withdraw the request and get the NFT back.
+	pub fn is_cancellable(self) -> bool {
+		matches!(self, RedemptionStatus::Requested | RedemptionStatus::Acknowledged)
+	}
+}
+
+impl Redemption {
+	pub fn status(&self) -> Option<RedemptionStatus> {
+		RedemptionStatus::from_u8(self.status)
+	}
+
+	/// Moves the redemption from `from` to `to`, failing with `InvalidRedemptionState` if it
+	/// is not currently in `from`.
+	pub fn transition(&mut self, from: RedemptionStatus, to: RedemptionStatus, now: i64) -> Result<(), ProgramError> {
+		if self.status() != Some(from) {
+			return Err(CustomError::InvalidRedemptionState.into());
+		}
+
+		self.status = to as u8;
+		self.updated_at = now;
+
+		Ok(())
+	}
+}
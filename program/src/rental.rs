@@ -0,0 +1,36 @@
+//! Pricing and timing of alloy rentals.
+
+use solana_program::{ program_error::ProgramError, pubkey::Pubkey };
+use crate::{
+	error::CustomError,
+	state::Rental,
+};
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+impl Rental {
+	pub fn is_rented(&self, now: i64) -> bool {
+		now < self.expires_at
+	}
+
+	/// Lamports owed for renting the alloy for `days`, failing with `InvalidRentalTerms`
+	/// unless `days` is between one and the offer's `max_days`.
+	pub fn price_for(&self, days: u16) -> Result<u64, ProgramError> {
+		if days == 0 || days > self.max_days {
+			return Err(CustomError::InvalidRentalTerms.into());
+		}
+
+		self.price_per_day
+			.checked_mul(days as u64)
+			.ok_or(ProgramError::ArithmeticOverflow)
+	}
+
+	/// Starts a rental of `days` for `renter` at `now`, returning when it expires.
+	pub fn start(&mut self, renter: Pubkey, days: u16, now: i64) -> i64 {
+		self.renter = renter;
+		self.rented_at = now;
+		self.expires_at = now + days as i64 * SECONDS_PER_DAY;
+
+		self.expires_at
+	}
+}
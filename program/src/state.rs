@@ -1,41 +1,601 @@
 use solana_program::{
 	pubkey::Pubkey,
 	program_error::ProgramError,
-	account_info::AccountInfo,
-	borsh as sol_borsh
+	entrypoint::ProgramResult,
 };
 use crate::error::CustomError;
 
-use borsh::{ BorshSerialize, BorshDeserialize };
+use borsh::{ BorshSerialize, BorshDeserialize, BorshSchema };
+use bytemuck::{ Pod, Zeroable };
 
 pub const PREFIX: &str = "alloy";
 pub const MAX_NAME_LENGTH: usize = 32;
 pub const MAX_SYMBOL_LENGTH: usize = 10;
 pub const MAX_URI_LENGTH: usize = 200;
-pub const MAX_DATA_SIZE: usize = 1 + 4 + MAX_NAME_LENGTH + 4 + MAX_URI_LENGTH + 8 + 8 +32;
+pub const MAX_DATA_SIZE: usize = std::mem::size_of::<AlloyData>();
+pub const COMPOSITION_SEED: &str = "composition";
+pub const MAX_COMPOSITION_ELEMENTS: usize = 16;
+pub const COMPOSITION_DATA_SIZE: usize = std::mem::size_of::<AlloyComposition>();
+/// 100% expressed in basis points.
+pub const TOTAL_BPS: u16 = 10_000;
+pub const CONFIG_SEED: &str = "config";
+pub const CONFIG_DATA_SIZE: usize = std::mem::size_of::<ProgramConfig>();
+pub const REGISTRY_SEED: &str = "registry";
+pub const REGISTRY_ENTRY_DATA_SIZE: usize = std::mem::size_of::<RegistryEntry>();
+pub const ATTESTATION_SEED: &str = "attestation";
+pub const ATTESTATION_DATA_SIZE: usize = std::mem::size_of::<Attestation>();
+pub const REDEMPTION_SEED: &str = "redemption";
+pub const REDEMPTION_DATA_SIZE: usize = std::mem::size_of::<Redemption>();
+pub const ESCROW_SEED: &str = "escrow";
+pub const CUSTODY_SEED: &str = "custody";
+pub const CUSTODY_LOG_DATA_SIZE: usize = std::mem::size_of::<CustodyLog>();
+pub const CUSTODY_PAGE_DATA_SIZE: usize = std::mem::size_of::<CustodyLogPage>();
+pub const CUSTODY_ENTRIES_PER_PAGE: usize = 16;
+pub const MAX_LOCATION_CODE_LENGTH: usize = 8;
+pub const HEAT_SEED: &str = "heat";
+pub const HEAT_INDEX_DATA_SIZE: usize = std::mem::size_of::<HeatIndex>();
+/// Width of the mill id, heat number and standard, each stored zero-padded.
+pub const MAX_PROVENANCE_CODE_LENGTH: usize = 16;
+pub const VAULT_SEED: &str = "vault";
+pub const VAULT_DATA_SIZE: usize = std::mem::size_of::<Vault>();
+pub const RENTAL_SEED: &str = "rental";
+pub const RENTAL_DATA_SIZE: usize = std::mem::size_of::<Rental>();
+pub const INSTALLMENT_SEED: &str = "installment";
+pub const INSTALLMENT_PLAN_DATA_SIZE: usize = std::mem::size_of::<InstallmentPlan>();
+pub const BUNDLE_SEED: &str = "bundle";
+pub const BUNDLE_DATA_SIZE: usize = std::mem::size_of::<Bundle>();
+pub const MAX_BUNDLE_SIZE: usize = 8;
+pub const SWAP_SEED: &str = "swap";
+pub const SWAP_DATA_SIZE: usize = std::mem::size_of::<Swap>();
 
-#[derive(Debug, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+/// Registry role of labs allowed to attest alloy properties.
+pub const ROLE_ATTESTER: u8 = 1;
+/// Registry role of accredited inspectors allowed to certify physical lots.
+pub const ROLE_INSPECTOR: u8 = 2;
+/// Registry role of warehouses that hold the metal and ship it out on redemption.
+pub const ROLE_CUSTODIAN: u8 = 3;
+
+/// On-chain layout of an alloy data account.
+///
+/// The struct is read and written in place through `bytemuck`, so fields are ordered to
+/// avoid implicit padding. Strings are stored as fixed-width byte arrays together with
+/// their length; use the accessors to read them back as trimmed `&str`. Without padding the
+/// layout is also its Borsh encoding, which is what the derived schema describes.
+///
+/// This layout has not been released. Fields are appended as features land and
+/// `MAX_DATA_SIZE` grows with them, so accounts written under an earlier layout fail to load
+/// with `DataTypeMismatch`; nothing migrates them. Once the layout ships, changing it needs a
+/// versioned migration instruction that reallocates existing accounts.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
 pub struct AlloyData {
-	pub id: u8,
-	pub name: String,
-	pub symbol: String,
-	pub uri: String,
 	pub last_price: u64,
 	pub listed_price: u64,
 	pub owner_address: Pubkey,
+	pub id: u8,
+	pub bump: u8,
+	pub name_len: u8,
+	pub symbol_len: u8,
+	pub uri_len: u8,
+	pub name: [u8; MAX_NAME_LENGTH],
+	pub symbol: [u8; MAX_SYMBOL_LENGTH],
+	pub uri: [u8; MAX_URI_LENGTH],
+	/// SHA-256 of the off-chain metadata document `uri` points at.
+	pub uri_hash: [u8; 32],
+	/// Non-zero once the metal has been delivered and the NFT burned.
+	pub redeemed: u8,
+	/// Inspector behind the current certificate, or the default key if never certified.
+	pub inspector: Pubkey,
+	/// SHA-256 of the inspection certificate.
+	pub certificate_hash: [u8; 32],
+	/// Unix timestamp after which the certificate no longer vouches for the lot.
+	pub certificate_expires_at: i64,
+	/// Mass of metal in the lot. Splits and merges move grams between lots but never
+	/// create or destroy them.
+	pub quantity_grams: u64,
+	/// Alloy data account of the lot this one was split from, or the default key for a lot
+	/// created directly.
+	pub parent: Pubkey,
+	/// Alloy data account of the lot this one was merged into, or the default key while the
+	/// lot is live.
+	pub merged_into: Pubkey,
+	/// Unix timestamp of the day the heat was cast.
+	pub production_date: i64,
+	/// Producing mill, zero-padded.
+	pub mill: [u8; MAX_PROVENANCE_CODE_LENGTH],
+	/// Heat (melt batch) number assigned by the mill, zero-padded.
+	pub heat_number: [u8; MAX_PROVENANCE_CODE_LENGTH],
+	/// Standard the grade is specified under, such as `EN 10084` or `SAE J404`, zero-padded.
+	pub standard: [u8; MAX_PROVENANCE_CODE_LENGTH],
+	/// Unix timestamp until which a renter holds the lot's use rights; it can be neither
+	/// listed nor sold before then.
+	pub rented_until: i64,
 }
 
 impl AlloyData {
-	pub fn from_acc_info(acc_info: &AccountInfo) -> Result<Self, ProgramError> {
-		
-		let acc_info_data = &acc_info.data.borrow_mut();
+	/// Borrows an alloy data account in place.
+	pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+		bytemuck::try_from_bytes(data).map_err(|_| CustomError::DataTypeMismatch.into())
+	}
+
+	/// Mutably borrows an alloy data account in place.
+	pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+		bytemuck::try_from_bytes_mut(data).map_err(|_| CustomError::DataTypeMismatch.into())
+	}
+
+	/// Copies an alloy data account out of a buffer with no alignment guarantees, such as
+	/// account data fetched over RPC.
+	pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+		bytemuck::try_pod_read_unaligned(data).map_err(|_| CustomError::DataTypeMismatch.into())
+	}
+
+	pub fn name(&self) -> &str {
+		trimmed_str(&self.name, self.name_len)
+	}
+
+	pub fn symbol(&self) -> &str {
+		trimmed_str(&self.symbol, self.symbol_len)
+	}
+
+	pub fn uri(&self) -> &str {
+		trimmed_str(&self.uri, self.uri_len)
+	}
+
+	pub fn set_name(&mut self, name: &str) -> ProgramResult {
+		self.name_len = write_padded(&mut self.name, name, CustomError::NameTooLong)?;
+		Ok(())
+	}
+
+	pub fn set_symbol(&mut self, symbol: &str) -> ProgramResult {
+		self.symbol_len = write_padded(&mut self.symbol, symbol, CustomError::SymbolTooLong)?;
+		Ok(())
+	}
+
+	pub fn set_uri(&mut self, uri: &str) -> ProgramResult {
+		self.uri_len = write_padded(&mut self.uri, uri, CustomError::UriTooLong)?;
+		Ok(())
+	}
+
+	pub fn is_redeemed(&self) -> bool {
+		self.redeemed != 0
+	}
+
+	pub fn mill(&self) -> &str {
+		unpadded_str(&self.mill)
+	}
+
+	pub fn heat_number(&self) -> &str {
+		unpadded_str(&self.heat_number)
+	}
+
+	pub fn standard(&self) -> &str {
+		unpadded_str(&self.standard)
+	}
+
+	pub fn is_merged(&self) -> bool {
+		self.merged_into != Pubkey::default()
+	}
+
+	/// Whether `other` is labelled as the same grade under the same standard. The labels
+	/// alone do not make two lots mergeable: their compositions must match too.
+	pub fn is_same_grade(&self, other: &AlloyData) -> bool {
+		self.name() == other.name() && self.symbol() == other.symbol() && self.standard() == other.standard()
+	}
 
-		if acc_info_data.len() != MAX_DATA_SIZE {
-			return Err(CustomError::DataTypeMismatch.into());
+	pub fn is_rented(&self, now: i64) -> bool {
+		now < self.rented_until
+	}
+
+	/// Whether the alloy holds a certificate that is still valid at `now`.
+	pub fn is_certified(&self, now: i64) -> bool {
+		self.inspector != Pubkey::default() && now < self.certificate_expires_at
+	}
+}
+
+/// Share of one element in an alloy, in basis points of mass.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Pod, Zeroable, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct ElementComposition {
+	/// Element symbol such as `Cr` or `C`, zero-padded.
+	pub symbol: [u8; 2],
+	pub min_bps: u16,
+	pub max_bps: u16,
+	pub actual_bps: u16,
+}
+
+impl ElementComposition {
+	/// Builds an entry from a symbol of at most two bytes; longer symbols are truncated and
+	/// then rejected by validation.
+	pub fn new(symbol: &str, min_bps: u16, max_bps: u16, actual_bps: u16) -> Self {
+		let mut padded = [0; 2];
+		let len = symbol.len().min(padded.len());
+		padded[..len].copy_from_slice(&symbol.as_bytes()[..len]);
+
+		ElementComposition { symbol: padded, min_bps, max_bps, actual_bps }
+	}
+
+	pub fn symbol(&self) -> &str {
+		unpadded_str(&self.symbol)
+	}
+
+	/// Whether `other` is the same element with the same allowed range, whatever share of it
+	/// either lot actually contains.
+	pub fn is_same_spec(&self, other: &ElementComposition) -> bool {
+		self.symbol == other.symbol && self.min_bps == other.min_bps && self.max_bps == other.max_bps
+	}
+}
+
+/// On-chain chemical composition of an alloy, stored in a PDA derived from the alloy's
+/// `PREFIX` seeds followed by `COMPOSITION_SEED`. Like `AlloyData` it is read in place, so
+/// the layout has no implicit padding.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct AlloyComposition {
+	pub id: u8,
+	pub bump: u8,
+	pub element_count: u8,
+	pub _padding: [u8; 1],
+	pub elements: [ElementComposition; MAX_COMPOSITION_ELEMENTS],
+}
+
+impl AlloyComposition {
+	/// The recorded elements, without the unused tail of the array.
+	pub fn elements(&self) -> &[ElementComposition] {
+		&self.elements[..(self.element_count as usize).min(MAX_COMPOSITION_ELEMENTS)]
+	}
+
+	pub fn element(&self, symbol: &str) -> Option<&ElementComposition> {
+		self.elements().iter().find(|element| element.symbol() == symbol)
+	}
+
+	/// Whether `other` allows the same range for exactly the same elements, in any order.
+	pub fn is_same_spec(&self, other: &AlloyComposition) -> bool {
+		self.elements().len() == other.elements().len()
+			&& self.elements().iter().all(|element| {
+				other.element(element.symbol()).is_some_and(|other_element| element.is_same_spec(other_element))
+			})
+	}
+
+	pub fn set_elements(&mut self, elements: &[ElementComposition]) -> ProgramResult {
+		if elements.len() > MAX_COMPOSITION_ELEMENTS {
+			return Err(CustomError::TooManyElements.into());
 		}
 
-		let result = sol_borsh::try_from_slice_unchecked(acc_info_data)?;
+		self.elements[..elements.len()].copy_from_slice(elements);
+		self.elements[elements.len()..].fill(ElementComposition::default());
+		self.element_count = elements.len() as u8;
 
-		Ok(result)
+		Ok(())
 	}
-}
\ No newline at end of file
+}
+
+/// Implements `load`, `load_mut` and `unpack` for accounts read in place, with the same
+/// semantics as on `AlloyData`.
+macro_rules! impl_pod_account {
+	($($account:ty),* $(,)?) => {
+		$(
+			impl $account {
+				pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+					bytemuck::try_from_bytes(data).map_err(|_| CustomError::DataTypeMismatch.into())
+				}
+
+				pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+					bytemuck::try_from_bytes_mut(data).map_err(|_| CustomError::DataTypeMismatch.into())
+				}
+
+				pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+					bytemuck::try_pod_read_unaligned(data).map_err(|_| CustomError::DataTypeMismatch.into())
+				}
+			}
+		)*
+	};
+}
+
+impl_pod_account!(AlloyComposition, ProgramConfig, RegistryEntry, Attestation, Redemption, CustodyLog, CustodyLogPage, HeatIndex, Vault, Rental, InstallmentPlan, Bundle, Swap);
+
+/// Program-wide settings, stored once in the PDA derived from `PREFIX` and `CONFIG_SEED`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct ProgramConfig {
+	/// Manages the registries of trusted parties.
+	pub authority: Pubkey,
+	pub bump: u8,
+	pub _padding: [u8; 7],
+}
+
+/// Membership of `member` in the registry for `role`, stored in the PDA derived from
+/// `PREFIX`, `REGISTRY_SEED`, the role and the member. Removing a member closes the account.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct RegistryEntry {
+	pub member: Pubkey,
+	pub registered_at: i64,
+	pub role: u8,
+	pub bump: u8,
+	pub _padding: [u8; 6],
+}
+
+/// A measured property of an alloy, signed off by a registered attester. Stored in the PDA
+/// derived from the alloy's `PREFIX` seeds, `ATTESTATION_SEED`, the attester and a nonce the
+/// attester picks, so one lab can record any number of attestations per alloy.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct Attestation {
+	/// Alloy data account the attestation is about. Kept first so clients can filter on it.
+	pub alloy: Pubkey,
+	pub attester: Pubkey,
+	/// SHA-256 of the lab report backing the value.
+	pub document_hash: [u8; 32],
+	pub timestamp: i64,
+	/// Measured value in thousandths of `unit`.
+	pub value_milli: i64,
+	pub nonce: u64,
+	pub id: u8,
+	pub bump: u8,
+	/// A `PropertyKind`.
+	pub kind: u8,
+	/// A `PropertyUnit`.
+	pub unit: u8,
+	pub _padding: [u8; 4],
+}
+
+/// A holder's claim on the physical metal behind an alloy, stored in the PDA derived from
+/// the alloy's `PREFIX` seeds followed by `REDEMPTION_SEED`. While it is open the NFT sits in
+/// an escrow token account derived the same way with `ESCROW_SEED` appended and owned by
+/// this PDA.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct Redemption {
+	/// Holder the NFT came from, who gets it back on cancellation.
+	pub holder: Pubkey,
+	/// SHA-256 of the off-chain shipping details.
+	pub shipping_hash: [u8; 32],
+	pub requested_at: i64,
+	/// When the status last changed.
+	pub updated_at: i64,
+	pub id: u8,
+	pub bump: u8,
+	pub escrow_bump: u8,
+	/// A `RedemptionStatus`.
+	pub status: u8,
+	pub _padding: [u8; 4],
+}
+
+/// Fractional ownership of an alloy, stored in the PDA derived from the alloy's `PREFIX` seeds
+/// followed by `VAULT_SEED`. The NFT sits in an escrow token account derived the same way
+/// with `ESCROW_SEED` appended and owned by this PDA, split into `share_supply` fungible
+/// shares of `share_mint`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct Vault {
+	pub alloy: Pubkey,
+	/// Holder who locked the NFT and received every share.
+	pub curator: Pubkey,
+	pub share_mint: Pubkey,
+	/// Who bought the NFT out, or the default key before a buyout.
+	pub buyer: Pubkey,
+	pub share_supply: u64,
+	/// Lamports for which anyone may buy the NFT out of the vault.
+	pub reserve_price: u64,
+	/// Buyout lamports share holders have not claimed yet.
+	pub proceeds: u64,
+	pub id: u8,
+	pub bump: u8,
+	pub escrow_bump: u8,
+	/// A `VaultStatus`.
+	pub status: u8,
+	pub _padding: [u8; 4],
+}
+
+/// An offer to rent out an alloy's use rights, stored in the PDA derived from the alloy's
+/// `PREFIX` seeds followed by `RENTAL_SEED`. The lessor approves this PDA as delegate for the
+/// NFT, which it moves into an escrow token account derived the same way with `ESCROW_SEED`
+/// appended when the alloy is rented. The NFT stays there until the rental is ended, and the
+/// offer stays open between rentals, so the same lessor can rent the lot out again once a
+/// rental expires.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct Rental {
+	/// Holder who offered the alloy and is paid for each rental.
+	pub lessor: Pubkey,
+	/// Current or most recent renter, or the default key before the first rental.
+	pub renter: Pubkey,
+	/// Lamports paid to the lessor per day of rental.
+	pub price_per_day: u64,
+	pub rented_at: i64,
+	/// Unix timestamp at which the current rental ends.
+	pub expires_at: i64,
+	/// Longest rental accepted, in days.
+	pub max_days: u16,
+	pub id: u8,
+	pub bump: u8,
+	pub escrow_bump: u8,
+	pub _padding: [u8; 3],
+}
+
+/// A sale of an alloy paid for in tranches, stored in the PDA derived from the alloy's
+/// `PREFIX` seeds followed by `INSTALLMENT_SEED`. The NFT sits in an escrow token account
+/// derived the same way with `ESCROW_SEED` appended and owned by this PDA, and the buyer's
+/// payments accumulate on the PDA itself until the last one releases both.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct InstallmentPlan {
+	pub seller: Pubkey,
+	pub buyer: Pubkey,
+	pub total_price: u64,
+	/// Lamports paid so far.
+	pub paid: u64,
+	/// Unix timestamp by which the first payment is due.
+	pub first_due_at: i64,
+	/// Seconds between consecutive deadlines.
+	pub interval: i64,
+	/// Part of the paid lamports the seller keeps if the buyer misses a deadline, in basis
+	/// points; the rest is refunded.
+	pub forfeit_bps: u16,
+	pub payments: u8,
+	pub payments_made: u8,
+	pub id: u8,
+	pub bump: u8,
+	pub escrow_bump: u8,
+	pub _padding: [u8; 1],
+}
+
+/// Several alloys listed together at a single price, stored in the PDA derived from `PREFIX`,
+/// `BUNDLE_SEED`, the seller and the little-endian `u64` nonce. Each alloy's NFT sits in an
+/// escrow token account derived from the alloy's `PREFIX` seeds followed by `BUNDLE_SEED` and
+/// `ESCROW_SEED` and owned by this PDA, so an alloy is in at most one bundle at a time.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct Bundle {
+	pub seller: Pubkey,
+	/// Lamports for every alloy in the bundle together.
+	pub price: u64,
+	pub nonce: u64,
+	pub created_at: i64,
+	/// Alloy ids in the order their accounts are passed; only the first `len` are used.
+	pub ids: [u8; MAX_BUNDLE_SIZE],
+	pub escrow_bumps: [u8; MAX_BUNDLE_SIZE],
+	pub len: u8,
+	pub bump: u8,
+	pub _padding: [u8; 6],
+}
+
+/// An offer to trade one alloy for another, stored in the PDA derived from the offered
+/// alloy's `PREFIX` seeds followed by `SWAP_SEED`. The offered NFT sits in the escrow token
+/// account derived from the same seeds followed by `ESCROW_SEED` and owned by this PDA.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct Swap {
+	pub proposer: Pubkey,
+	/// Lamports the counterparty pays on top of their alloy.
+	pub top_up: u64,
+	pub created_at: i64,
+	/// Id of the escrowed alloy.
+	pub id: u8,
+	/// Id of the alloy the proposer wants in return.
+	pub wanted_id: u8,
+	pub bump: u8,
+	pub escrow_bump: u8,
+	pub _padding: [u8; 4],
+}
+
+/// Head of an alloy's chain-of-custody log, stored in the PDA derived from the alloy's
+/// `PREFIX` seeds followed by `CUSTODY_SEED`. The entries themselves live in pages of
+/// `CUSTODY_ENTRIES_PER_PAGE`, each in the PDA with the little-endian `u32` page index
+/// appended to those seeds. Entries are only ever appended.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct CustodyLog {
+	pub alloy: Pubkey,
+	/// Custodian of the latest entry, or the default key before the first one.
+	pub current_custodian: Pubkey,
+	pub entry_count: u32,
+	pub id: u8,
+	pub bump: u8,
+	pub _padding: [u8; 2],
+}
+
+impl CustodyLog {
+	/// Page the next entry goes into.
+	pub fn next_page(&self) -> u32 {
+		self.entry_count / CUSTODY_ENTRIES_PER_PAGE as u32
+	}
+}
+
+/// One handoff of a physical lot, recorded by the custodian receiving it.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct CustodyEntry {
+	/// Custodian handing the lot over, or the default key for the first entry.
+	pub from: Pubkey,
+	pub to: Pubkey,
+	pub timestamp: i64,
+	/// SHA-256 of the handoff paperwork.
+	pub document_hash: [u8; 32],
+	/// Site code such as a UN/LOCODE, zero-padded.
+	pub location: [u8; MAX_LOCATION_CODE_LENGTH],
+}
+
+impl CustodyEntry {
+	pub fn location(&self) -> &str {
+		unpadded_str(&self.location)
+	}
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct CustodyLogPage {
+	pub page: u32,
+	pub id: u8,
+	pub bump: u8,
+	pub entry_count: u8,
+	pub _padding: [u8; 1],
+	pub entries: [CustodyEntry; CUSTODY_ENTRIES_PER_PAGE],
+}
+
+impl CustodyLogPage {
+	/// The recorded entries, without the unused tail of the page.
+	pub fn entries(&self) -> &[CustodyEntry] {
+		&self.entries[..(self.entry_count as usize).min(CUSTODY_ENTRIES_PER_PAGE)]
+	}
+
+	pub fn push(&mut self, entry: CustodyEntry) -> ProgramResult {
+		let index = self.entry_count as usize;
+
+		if index >= CUSTODY_ENTRIES_PER_PAGE {
+			return Err(CustomError::CustodyPageFull.into());
+		}
+
+		self.entries[index] = entry;
+		self.entry_count += 1;
+
+		Ok(())
+	}
+}
+
+/// Uniqueness index of a heat, stored in the PDA derived from `PREFIX`, the program id,
+/// `HEAT_SEED` and the zero-padded mill id and heat number. Creating an alloy creates its
+/// index, so a heat can only be tokenized once; lots split off later share the heat without
+/// an index of their own.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable, BorshSchema)]
+pub struct HeatIndex {
+	pub alloy: Pubkey,
+	pub mill: [u8; MAX_PROVENANCE_CODE_LENGTH],
+	pub heat_number: [u8; MAX_PROVENANCE_CODE_LENGTH],
+	pub id: u8,
+	pub bump: u8,
+}
+
+/// Zero-pads `value` to `N` bytes, truncating anything longer.
+pub fn pad_str<const N: usize>(value: &str) -> [u8; N] {
+	let mut padded = [0; N];
+	let len = value.len().min(N);
+	padded[..len].copy_from_slice(&value.as_bytes()[..len]);
+
+	padded
+}
+
+/// Reads back a zero-padded string.
+fn unpadded_str(bytes: &[u8]) -> &str {
+	let len = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+	std::str::from_utf8(&bytes[..len]).unwrap_or_default()
+}
+
+fn trimmed_str(bytes: &[u8], len: u8) -> &str {
+	let len = (len as usize).min(bytes.len());
+	std::str::from_utf8(&bytes[..len]).unwrap_or_default()
+}
+
+fn write_padded(dst: &mut [u8], value: &str, too_long: CustomError) -> Result<u8, ProgramError> {
+	let bytes = value.as_bytes();
+
+	if bytes.len() > dst.len() {
+		return Err(too_long.into());
+	}
+
+	dst[..bytes.len()].copy_from_slice(bytes);
+	dst[bytes.len()..].fill(0);
+
+	Ok(bytes.len() as u8)
+}
@@ -0,0 +1,371 @@
+use solana_program::{
+	account_info::{ next_account_info, AccountInfo },
+	entrypoint::ProgramResult,
+	program_error::ProgramError,
+	program_option::COption,
+	program_pack::{ IsInitialized, Pack },
+	pubkey::Pubkey,
+	system_program,
+};
+use spl_token::state::{ Account, Mint };
+use crate::{
+	error::CustomError,
+	state::{ AlloyData, ProgramConfig, RegistryEntry, COMPOSITION_SEED, CONFIG_SEED, PREFIX, REGISTRY_SEED },
+};
+
+/// A requirement an account must meet before a processor is allowed to use it.
+pub enum Constraint<'a> {
+	/// The account signed the transaction.
+	Signer,
+	/// The account was passed as writable.
+	Writable,
+	/// The account is owned by the given program.
+	Owner(&'a Pubkey),
+	/// The account is the given program.
+	Program(&'a Pubkey),
+	/// The account is the given sysvar.
+	Sysvar(&'a Pubkey),
+	/// The account is still owned by the system program and holds no data.
+	Uninitialized,
+}
+
+impl Constraint<'_> {
+	pub fn check(&self, account_info: &AccountInfo) -> ProgramResult {
+		match self {
+			Constraint::Signer => assert_signer(account_info),
+			Constraint::Writable => assert_writable(account_info),
+			Constraint::Owner(owner) => assert_owned_by(account_info, owner),
+			Constraint::Program(program_id) => assert_program(account_info, program_id),
+			Constraint::Sysvar(sysvar_id) => assert_sysvar(account_info, sysvar_id),
+			Constraint::Uninitialized => assert_uninitialized(account_info),
+		}
+	}
+}
+
+/// Takes the next account off `account_iter` and checks it against every constraint.
+pub fn next_validated_account<'a, 'info, I>(
+	account_iter: &mut I,
+	constraints: &[Constraint],
+) -> Result<&'a AccountInfo<'info>, ProgramError>
+where
+	I: Iterator<Item = &'a AccountInfo<'info>>,
+{
+	let account_info = next_account_info(account_iter)?;
+
+	for constraint in constraints {
+		constraint.check(account_info)?;
+	}
+
+	Ok(account_info)
+}
+
+pub fn assert_signer(account_info: &AccountInfo) -> ProgramResult {
+	if !account_info.is_signer {
+		return Err(ProgramError::MissingRequiredSignature);
+	}
+
+	Ok(())
+}
+
+pub fn assert_writable(account_info: &AccountInfo) -> ProgramResult {
+	if !account_info.is_writable {
+		return Err(CustomError::AccountNotWritable.into());
+	}
+
+	Ok(())
+}
+
+pub fn assert_owned_by(account_info: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+	if account_info.owner != owner {
+		return Err(CustomError::IncorrectOwner.into());
+	}
+
+	Ok(())
+}
+
+pub fn assert_program(account_info: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+	if account_info.key != program_id {
+		return Err(ProgramError::IncorrectProgramId);
+	}
+
+	Ok(())
+}
+
+pub fn assert_sysvar(account_info: &AccountInfo, sysvar_id: &Pubkey) -> ProgramResult {
+	if account_info.key != sysvar_id {
+		return Err(ProgramError::InvalidArgument);
+	}
+
+	Ok(())
+}
+
+pub fn assert_uninitialized(account_info: &AccountInfo) -> ProgramResult {
+	if !system_program::check_id(account_info.owner) || !account_info.data_is_empty() {
+		return Err(CustomError::AlreadyInitialized.into());
+	}
+
+	Ok(())
+}
+
+/// Finds the canonical alloy data PDA for `id` and checks that `account_info` is it,
+/// returning the bump.
+pub fn assert_canonical_alloy_data_key(
+	program_id: &Pubkey,
+	account_info: &AccountInfo,
+	id: u8,
+) -> Result<u8, ProgramError> {
+	let (alloy_data_key, bump) = Pubkey::find_program_address(
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id]],
+		program_id,
+	);
+
+	if *account_info.key != alloy_data_key {
+		return Err(CustomError::InvalidAlloyDataKey.into());
+	}
+
+	Ok(bump)
+}
+
+/// Checks that `account_info` is the alloy data PDA for `id`, re-deriving it from the bump
+/// stored at creation instead of searching for it again.
+pub fn assert_alloy_data_key(
+	program_id: &Pubkey,
+	account_info: &AccountInfo,
+	id: u8,
+	bump: u8,
+) -> ProgramResult {
+	let alloy_data_key = Pubkey::create_program_address(
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], &[bump]],
+		program_id,
+	).map_err(|_| CustomError::InvalidAlloyDataKey)?;
+
+	if *account_info.key != alloy_data_key {
+		return Err(CustomError::InvalidAlloyDataKey.into());
+	}
+
+	Ok(())
+}
+
+/// Finds the canonical PDA for `seeds` and checks that `account_info` is it, returning the
+/// bump. Fails with `error` otherwise.
+pub fn assert_canonical_program_address(
+	program_id: &Pubkey,
+	account_info: &AccountInfo,
+	seeds: &[&[u8]],
+	error: CustomError,
+) -> Result<u8, ProgramError> {
+	let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+
+	if *account_info.key != address {
+		return Err(error.into());
+	}
+
+	Ok(bump)
+}
+
+/// Checks that `account_info` is the PDA for `seeds` with the given bump, failing with
+/// `error` otherwise.
+pub fn assert_program_address(
+	program_id: &Pubkey,
+	account_info: &AccountInfo,
+	seeds: &[&[u8]],
+	bump: u8,
+	error: CustomError,
+) -> ProgramResult {
+	let address = Pubkey::create_program_address(&[seeds, &[&[bump]]].concat(), program_id)
+		.map_err(|_| error)?;
+
+	if *account_info.key != address {
+		return Err(error.into());
+	}
+
+	Ok(())
+}
+
+/// Finds the canonical composition PDA of alloy `id` and checks that `account_info` is it,
+/// returning the bump.
+pub fn assert_canonical_composition_key(
+	program_id: &Pubkey,
+	account_info: &AccountInfo,
+	id: u8,
+) -> Result<u8, ProgramError> {
+	assert_canonical_program_address(
+		program_id,
+		account_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], COMPOSITION_SEED.as_bytes()],
+		CustomError::InvalidCompositionKey,
+	)
+}
+
+/// Checks that `account_info` is the composition PDA of alloy `id` using its stored bump.
+pub fn assert_composition_key(
+	program_id: &Pubkey,
+	account_info: &AccountInfo,
+	id: u8,
+	bump: u8,
+) -> ProgramResult {
+	assert_program_address(
+		program_id,
+		account_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], COMPOSITION_SEED.as_bytes()],
+		bump,
+		CustomError::InvalidCompositionKey,
+	)
+}
+
+/// Loads the program config, checking it is the config PDA.
+pub fn load_config(program_id: &Pubkey, config_info: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+	let config = *ProgramConfig::load(&config_info.data.borrow())?;
+
+	assert_program_address(
+		program_id,
+		config_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), CONFIG_SEED.as_bytes()],
+		config.bump,
+		CustomError::InvalidConfigKey,
+	)?;
+
+	Ok(config)
+}
+
+/// Checks that `authority_info` is the authority recorded in the program config.
+pub fn assert_authority(program_id: &Pubkey, config_info: &AccountInfo, authority_info: &AccountInfo) -> ProgramResult {
+	if load_config(program_id, config_info)?.authority != *authority_info.key {
+		return Err(CustomError::InvalidAuthority.into());
+	}
+
+	Ok(())
+}
+
+/// Checks that `entry_info` is a live registry entry enrolling `member` under `role`.
+pub fn assert_registered(
+	program_id: &Pubkey,
+	entry_info: &AccountInfo,
+	role: u8,
+	member: &Pubkey,
+) -> ProgramResult {
+	if entry_info.owner != program_id || entry_info.data_is_empty() {
+		return Err(CustomError::NotRegistered.into());
+	}
+
+	let entry = *RegistryEntry::load(&entry_info.data.borrow())?;
+
+	if entry.role != role || entry.member != *member {
+		return Err(CustomError::NotRegistered.into());
+	}
+
+	assert_program_address(
+		program_id,
+		entry_info,
+		&[PREFIX.as_bytes(), program_id.as_ref(), REGISTRY_SEED.as_bytes(), &[role], member.as_ref()],
+		entry.bump,
+		CustomError::InvalidRegistryEntryKey,
+	)
+}
+
+pub fn assert_initialized<T: Pack + IsInitialized>(
+	account_info: &AccountInfo,
+) -> Result<T, ProgramError> {
+	let account: T = T::unpack_unchecked(&account_info.data.borrow())?;
+	if !account.is_initialized() {
+		Err(CustomError::Uninitialized.into())
+	} else {
+		Ok(account)
+	}
+}
+
+/// Checks the metal behind `alloy_data` is still in custody and has not been merged into
+/// another lot, so the alloy can be traded.
+pub fn assert_active(alloy_data: &AlloyData) -> ProgramResult {
+	if alloy_data.is_redeemed() {
+		return Err(CustomError::AlloyRedeemed.into());
+	}
+
+	if alloy_data.is_merged() {
+		return Err(CustomError::AlloyMerged.into());
+	}
+
+	Ok(())
+}
+
+/// Checks no rental of `alloy_data` is running at `now`, so this program may list, sell,
+/// split or merge it.
+pub fn assert_not_rented(alloy_data: &AlloyData, now: i64) -> ProgramResult {
+	if alloy_data.is_rented(now) {
+		return Err(CustomError::AlloyRented.into());
+	}
+
+	Ok(())
+}
+
+/// Checks that `token_account_info` holds the NFT minted for `alloy_data` and belongs to
+/// `holder`.
+pub fn assert_holds_alloy_nft(
+	token_account_info: &AccountInfo,
+	alloy_data: &AlloyData,
+	holder: &Pubkey,
+) -> ProgramResult {
+	let token_acc: Account = assert_initialized(token_account_info)?;
+
+	if token_acc.mint != alloy_data.owner_address {
+		return Err(CustomError::OwnerMismatch.into());
+	}
+
+	if token_acc.owner != *holder {
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	if token_acc.amount == 0 {
+		return Err(CustomError::NotEnoughTokens.into());
+	}
+
+	Ok(())
+}
+
+/// Checks that `token_account_info` is a token account of the NFT minted for `alloy_data`
+/// that belongs to `owner`, whatever it currently holds.
+pub fn assert_alloy_token_account(
+	token_account_info: &AccountInfo,
+	alloy_data: &AlloyData,
+	owner: &Pubkey,
+) -> ProgramResult {
+	let token_acc: Account = assert_initialized(token_account_info)?;
+
+	if token_acc.mint != alloy_data.owner_address {
+		return Err(CustomError::OwnerMismatch.into());
+	}
+
+	if token_acc.owner != *owner {
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	Ok(())
+}
+
+/// Checks that `mint_info` is a fresh mint the vault at `vault` alone can mint shares of and
+/// nobody can freeze.
+pub fn assert_share_mint(mint_info: &AccountInfo, vault: &Pubkey) -> ProgramResult {
+	let mint: Mint = assert_initialized(mint_info)?;
+
+	if mint.supply != 0 || mint.mint_authority != COption::Some(*vault) || mint.freeze_authority.is_some() {
+		return Err(CustomError::InvalidShareMint.into());
+	}
+
+	Ok(())
+}
+
+/// Checks that `token_account_info` holds shares of `share_mint` and belongs to `holder`,
+/// returning how many.
+pub fn assert_holds_shares(token_account_info: &AccountInfo, share_mint: &Pubkey, holder: &Pubkey) -> Result<u64, ProgramError> {
+	let token_acc: Account = assert_initialized(token_account_info)?;
+
+	if token_acc.mint != *share_mint {
+		return Err(CustomError::InvalidShareMint.into());
+	}
+
+	if token_acc.owner != *holder {
+		return Err(CustomError::InvalidOwner.into());
+	}
+
+	Ok(token_acc.amount)
+}
@@ -0,0 +1,46 @@
+//! States of a fractional ownership vault and the split of its buyout proceeds.
+
+use borsh::{ BorshSerialize, BorshDeserialize, BorshSchema };
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use solana_program::program_error::ProgramError;
+use crate::{
+	error::CustomError,
+	state::Vault,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, FromPrimitive, BorshSerialize, BorshDeserialize, BorshSchema)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum VaultStatus {
+	/// The NFT is locked and the shares trade freely.
+	Active = 0,
+	/// Someone paid the reserve price for the NFT; share holders claim the proceeds.
+	BoughtOut = 1,
+}
+
+impl Vault {
+	pub fn status(&self) -> Option<VaultStatus> {
+		VaultStatus::from_u8(self.status)
+	}
+
+	/// Fails with `InvalidVaultState` unless the vault is in `status`.
+	pub fn assert_status(&self, status: VaultStatus) -> Result<(), ProgramError> {
+		if self.status() != Some(status) {
+			return Err(CustomError::InvalidVaultState.into());
+		}
+
+		Ok(())
+	}
+
+	/// Lamports owed for `shares` out of the `outstanding` shares not yet claimed. Shares are
+	/// paid out of what is left rather than the original supply, so rounding never strands
+	/// lamports: the last claimant receives the remainder.
+	pub fn claimable(&self, shares: u64, outstanding: u64) -> u64 {
+		if outstanding == 0 {
+			return 0;
+		}
+
+		(self.proceeds as u128 * shares.min(outstanding) as u128 / outstanding as u128) as u64
+	}
+}
@@ -0,0 +1,160 @@
+//! Integration tests for the program config, the attester registry and attestations.
+
+mod common;
+
+use alloy_token_program::{
+	attestation::{ PropertyKind, PropertyUnit },
+	error::CustomError,
+	instruction::NftInstruction,
+	state::{ Attestation, ProgramConfig, RegistryEntry, ROLE_ATTESTER },
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	clock::Clock,
+	instruction::Instruction,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+};
+
+const REPORT_HASH: [u8; 32] = [9; 32];
+
+fn attestation_instruction(alloy: &AlloyFixture, attester: &Pubkey, payer: &Pubkey, nonce: u64, kind: PropertyKind, unit: PropertyUnit, value_milli: i64) -> Instruction {
+	NftInstruction::create_attestation(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&attestation_key(alloy.id, attester, nonce).0,
+		attester,
+		&registry_entry_key(ROLE_ATTESTER, attester).0,
+		payer,
+		alloy.id,
+		nonce,
+		kind,
+		unit,
+		value_milli,
+		REPORT_HASH,
+	)
+}
+
+/// An alloy, a config authority and a registered lab.
+async fn setup() -> (BanksClient, Keypair, AlloyFixture, Keypair, Keypair) {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let authority = initialize_config(&mut banks_client, &payer).await;
+	let lab = Keypair::new();
+	register(&mut banks_client, &payer, &authority, ROLE_ATTESTER, &lab.pubkey()).await;
+
+	(banks_client, payer, alloy, authority, lab)
+}
+
+#[tokio::test]
+async fn initialize_config_only_once() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let authority = initialize_config(&mut banks_client, &payer).await;
+
+	let config = ProgramConfig::unpack(&get_account(&mut banks_client, &config_key().0).await.data).unwrap();
+	assert_eq!(config.authority, authority.pubkey());
+	assert_eq!(config.bump, config_key().1);
+
+	let instruction = NftInstruction::initialize_config(&alloy_token_program::id(), &config_key().0, &payer.pubkey(), &payer.pubkey());
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::AlreadyInitialized);
+}
+
+#[tokio::test]
+async fn registry_is_managed_by_the_authority() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let authority = initialize_config(&mut banks_client, &payer).await;
+	let impostor = Keypair::new();
+	let lab = Pubkey::new_unique();
+
+	let instruction = add_registry_entry_instruction(&impostor.pubkey(), &payer.pubkey(), ROLE_ATTESTER, &lab);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&impostor]).await;
+	assert_custom_error(result, CustomError::InvalidAuthority);
+
+	let instruction = add_registry_entry_instruction(&authority.pubkey(), &payer.pubkey(), 0xff, &lab);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&authority]).await;
+	assert_custom_error(result, CustomError::UnknownRegistryRole);
+
+	register(&mut banks_client, &payer, &authority, ROLE_ATTESTER, &lab).await;
+
+	let entry = RegistryEntry::unpack(&get_account(&mut banks_client, &registry_entry_key(ROLE_ATTESTER, &lab).0).await.data).unwrap();
+	assert_eq!(entry.member, lab);
+	assert_eq!(entry.role, ROLE_ATTESTER);
+}
+
+#[tokio::test]
+async fn registered_attester_attests() {
+	let (mut banks_client, payer, alloy, _, lab) = setup().await;
+	let clock: Clock = banks_client.get_sysvar().await.unwrap();
+
+	let instructions = [
+		attestation_instruction(&alloy, &lab.pubkey(), &payer.pubkey(), 0, PropertyKind::TensileStrength, PropertyUnit::Megapascal, 1_100_000),
+		attestation_instruction(&alloy, &lab.pubkey(), &payer.pubkey(), 1, PropertyKind::Hardness, PropertyUnit::RockwellC, 58_500),
+	];
+	process(&mut banks_client, &payer, &instructions, &[&lab]).await.unwrap();
+
+	let account = get_account(&mut banks_client, &attestation_key(1, &lab.pubkey(), 1).0).await;
+	let attestation = Attestation::unpack(&account.data).unwrap();
+
+	assert_eq!(attestation.alloy, alloy.alloy_data);
+	assert_eq!(attestation.attester, lab.pubkey());
+	assert_eq!(attestation.kind(), Some(PropertyKind::Hardness));
+	assert_eq!(attestation.unit(), Some(PropertyUnit::RockwellC));
+	assert_eq!(attestation.value_milli, 58_500);
+	assert_eq!(attestation.document_hash, REPORT_HASH);
+	assert_eq!(attestation.timestamp, clock.unix_timestamp);
+	assert_eq!(attestation.nonce, 1);
+}
+
+#[tokio::test]
+async fn attestation_requires_registered_attester() {
+	let (mut banks_client, payer, alloy, authority, lab) = setup().await;
+	let stranger = Keypair::new();
+
+	let instruction = attestation_instruction(&alloy, &stranger.pubkey(), &payer.pubkey(), 0, PropertyKind::Elongation, PropertyUnit::Percent, 12_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::NotRegistered);
+
+	// Another lab's registry entry does not vouch for the stranger.
+	let mut instruction = attestation_instruction(&alloy, &stranger.pubkey(), &payer.pubkey(), 0, PropertyKind::Elongation, PropertyUnit::Percent, 12_000);
+	instruction.accounts[3].pubkey = registry_entry_key(ROLE_ATTESTER, &lab.pubkey()).0;
+	let result = process(&mut banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::NotRegistered);
+
+	let instruction = NftInstruction::remove_registry_entry(
+		&alloy_token_program::id(),
+		&config_key().0,
+		&authority.pubkey(),
+		&registry_entry_key(ROLE_ATTESTER, &lab.pubkey()).0,
+		ROLE_ATTESTER,
+		&lab.pubkey(),
+		&payer.pubkey(),
+	);
+	process(&mut banks_client, &payer, &[instruction], &[&authority]).await.unwrap();
+
+	let instruction = attestation_instruction(&alloy, &lab.pubkey(), &payer.pubkey(), 0, PropertyKind::Elongation, PropertyUnit::Percent, 12_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&lab]).await;
+	assert_custom_error(result, CustomError::NotRegistered);
+}
+
+#[tokio::test]
+async fn attestation_rejects_invalid_values() {
+	let (mut banks_client, payer, alloy, _, lab) = setup().await;
+
+	let instruction = attestation_instruction(&alloy, &lab.pubkey(), &payer.pubkey(), 0, PropertyKind::YieldStrength, PropertyUnit::Celsius, 850_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&lab]).await;
+	assert_custom_error(result, CustomError::InvalidAttestation);
+
+	let mut instruction = attestation_instruction(&alloy, &lab.pubkey(), &payer.pubkey(), 0, PropertyKind::HeatTreatment, PropertyUnit::Celsius, 880_000);
+	let data_len = instruction.data.len();
+	instruction.data[data_len - 32..].fill(0);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&lab]).await;
+	assert_custom_error(result, CustomError::MissingDocumentHash);
+
+	let mut instruction = attestation_instruction(&alloy, &lab.pubkey(), &payer.pubkey(), 0, PropertyKind::HeatTreatment, PropertyUnit::Celsius, 880_000);
+	instruction.accounts[1].pubkey = attestation_key(alloy.id, &lab.pubkey(), 1).0;
+	let result = process(&mut banks_client, &payer, &[instruction], &[&lab]).await;
+	assert_custom_error(result, CustomError::InvalidAttestationKey);
+}
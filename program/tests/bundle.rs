@@ -0,0 +1,199 @@
+//! Integration tests for bundle listings.
+
+mod common;
+
+use alloy_token_program::{
+	accounts::{ CancelBundleLotAccounts, CreateBundleLotAccounts, PurchaseBundleLotAccounts },
+	error::CustomError,
+	instruction::NftInstruction,
+	state::Bundle,
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	instruction::{ Instruction, InstructionError },
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	system_instruction,
+};
+
+const NONCE: u64 = 1;
+const PRICE: u64 = 3_000_000_001;
+
+/// An alloy of `quantity_grams` whose NFT is held by the shared seller.
+struct Lot {
+	id: u8,
+	mint: Pubkey,
+	token_account: Pubkey,
+}
+
+async fn create_lot(banks_client: &mut BanksClient, payer: &Keypair, seller: &Pubkey, id: u8, quantity_grams: u64) -> Lot {
+	let (mint, token_account) = create_nft(banks_client, payer, seller).await;
+	let provenance = provenance(id);
+	let uri = "https://example.com/alloys/20MnCr5.json";
+
+	let instruction = NftInstruction::create_alloy_data_accounts(
+		&alloy_token_program::id(),
+		&alloy_data_key(id).0,
+		&heat_index_key(&provenance.mill, &provenance.heat_number).0,
+		&payer.pubkey(),
+		id,
+		"20MnCr5".to_string(),
+		"ALLOY".to_string(),
+		uri.to_string(),
+		uri_hash(uri),
+		0,
+		LISTED_PRICE,
+		quantity_grams,
+		provenance,
+		&mint,
+	);
+	process(banks_client, payer, &[instruction], &[]).await.unwrap();
+
+	Lot { id, mint, token_account }
+}
+
+fn create_bundle_instruction(seller: &Pubkey, payer: &Pubkey, lots: &[Lot], ids: Vec<u8>, price: u64) -> Instruction {
+	let lot_accounts: Vec<_> = lots.iter().map(|lot| CreateBundleLotAccounts {
+		alloy_data: alloy_data_key(lot.id).0,
+		mint: lot.mint,
+		seller_token_account: lot.token_account,
+		escrow: bundle_escrow_key(lot.id).0,
+	}).collect();
+
+	NftInstruction::create_bundle(
+		&alloy_token_program::id(),
+		&bundle_key(seller, NONCE).0,
+		seller,
+		payer,
+		&lot_accounts,
+		NONCE,
+		ids,
+		price,
+	)
+}
+
+/// Three alloys of 10, 20 and 30 kg bundled by a single seller.
+async fn setup() -> (BanksClient, Keypair, Keypair, Vec<Lot>) {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let seller = Keypair::new();
+	let mut lots = Vec::new();
+
+	for (id, quantity_grams) in [(1, 10_000), (2, 20_000), (3, 30_000)] {
+		lots.push(create_lot(&mut banks_client, &payer, &seller.pubkey(), id, quantity_grams).await);
+	}
+
+	let instruction = create_bundle_instruction(&seller.pubkey(), &payer.pubkey(), &lots, vec![1, 2, 3], PRICE);
+	process(&mut banks_client, &payer, &[instruction], &[&seller]).await.unwrap();
+
+	(banks_client, payer, seller, lots)
+}
+
+#[tokio::test]
+async fn purchase_transfers_every_nft_and_splits_the_price_by_grams() {
+	let (mut banks_client, payer, seller, lots) = setup().await;
+	let buyer = Keypair::new();
+	let mut lot_accounts = Vec::new();
+
+	for lot in &lots {
+		assert_eq!(token_amount(&mut banks_client, &bundle_escrow_key(lot.id).0).await, 1);
+		assert_eq!(token_amount(&mut banks_client, &lot.token_account).await, 0);
+
+		lot_accounts.push(PurchaseBundleLotAccounts {
+			alloy_data: alloy_data_key(lot.id).0,
+			escrow: bundle_escrow_key(lot.id).0,
+			buyer_token_account: create_token_account(&mut banks_client, &payer, &lot.mint, &buyer.pubkey()).await,
+		});
+	}
+
+	let bundle = Bundle::unpack(&get_account(&mut banks_client, &bundle_key(&seller.pubkey(), NONCE).0).await.data).unwrap();
+	assert_eq!(bundle.ids(), &[1, 2, 3]);
+	assert_eq!(bundle.price, PRICE);
+
+	let mut rents = get_account(&mut banks_client, &bundle_key(&seller.pubkey(), NONCE).0).await.lamports;
+	for lot in &lots {
+		rents += get_account(&mut banks_client, &bundle_escrow_key(lot.id).0).await.lamports;
+	}
+
+	let fund = system_instruction::transfer(&payer.pubkey(), &buyer.pubkey(), PRICE + 1_000_000_000);
+	let purchase = NftInstruction::purchase_bundle(
+		&alloy_token_program::id(),
+		&bundle_key(&seller.pubkey(), NONCE).0,
+		&seller.pubkey(),
+		&buyer.pubkey(),
+		&lot_accounts,
+		NONCE,
+	);
+	process(&mut banks_client, &payer, &[fund, purchase], &[&buyer]).await.unwrap();
+
+	for lot in &lot_accounts {
+		assert_eq!(token_amount(&mut banks_client, &lot.buyer_token_account).await, 1);
+		assert!(banks_client.get_account(lot.escrow).await.unwrap().is_none());
+	}
+
+	assert!(banks_client.get_account(bundle_key(&seller.pubkey(), NONCE).0).await.unwrap().is_none());
+	assert_eq!(banks_client.get_balance(seller.pubkey()).await.unwrap(), PRICE + rents);
+	// The last alloy takes the rounding remainder.
+	assert_eq!(get_alloy_data(&mut banks_client, 1).await.last_price, 500_000_000);
+	assert_eq!(get_alloy_data(&mut banks_client, 2).await.last_price, 1_000_000_000);
+	assert_eq!(get_alloy_data(&mut banks_client, 3).await.last_price, 1_500_000_001);
+}
+
+#[tokio::test]
+async fn cancel_returns_every_nft_to_the_seller() {
+	let (mut banks_client, payer, seller, lots) = setup().await;
+	let lot_accounts: Vec<_> = lots.iter().map(|lot| CancelBundleLotAccounts {
+		escrow: bundle_escrow_key(lot.id).0,
+		seller_token_account: lot.token_account,
+	}).collect();
+
+	let cancel = NftInstruction::cancel_bundle(
+		&alloy_token_program::id(),
+		&bundle_key(&seller.pubkey(), NONCE).0,
+		&seller.pubkey(),
+		&lot_accounts,
+		NONCE,
+	);
+	process(&mut banks_client, &payer, &[cancel], &[&seller]).await.unwrap();
+
+	for lot in &lots {
+		assert_eq!(token_amount(&mut banks_client, &lot.token_account).await, 1);
+		assert!(banks_client.get_account(bundle_escrow_key(lot.id).0).await.unwrap().is_none());
+	}
+
+	assert!(banks_client.get_account(bundle_key(&seller.pubkey(), NONCE).0).await.unwrap().is_none());
+	assert_eq!(get_alloy_data(&mut banks_client, 1).await.last_price, 0);
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_bundles() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let seller = Keypair::new();
+	let mut lots = Vec::new();
+
+	for id in 1..=2 {
+		lots.push(create_lot(&mut banks_client, &payer, &seller.pubkey(), id, QUANTITY_GRAMS).await);
+	}
+
+	let single = create_bundle_instruction(&seller.pubkey(), &payer.pubkey(), &lots[..1], vec![1], PRICE);
+	let result = process(&mut banks_client, &payer, &[single], &[&seller]).await;
+	assert_custom_error(result, CustomError::InvalidBundle);
+
+	let duplicate = create_bundle_instruction(&seller.pubkey(), &payer.pubkey(), &lots, vec![1, 1], PRICE);
+	let result = process(&mut banks_client, &payer, &[duplicate], &[&seller]).await;
+	assert_custom_error(result, CustomError::InvalidBundle);
+
+	let free = create_bundle_instruction(&seller.pubkey(), &payer.pubkey(), &lots, vec![1, 2], 0);
+	let result = process(&mut banks_client, &payer, &[free], &[&seller]).await;
+	assert_custom_error(result, CustomError::InvalidBundle);
+
+	let missing_lot = create_bundle_instruction(&seller.pubkey(), &payer.pubkey(), &lots[..1], vec![1, 2], PRICE);
+	let result = process(&mut banks_client, &payer, &[missing_lot], &[&seller]).await;
+	assert_instruction_error(result, InstructionError::NotEnoughAccountKeys);
+
+	// Another seller cannot bundle alloys they do not hold.
+	let thief = Keypair::new();
+	let stolen = create_bundle_instruction(&thief.pubkey(), &payer.pubkey(), &lots, vec![1, 2], PRICE);
+	let result = process(&mut banks_client, &payer, &[stolen], &[&thief]).await;
+	assert_custom_error(result, CustomError::InvalidOwner);
+}
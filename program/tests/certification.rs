@@ -0,0 +1,151 @@
+//! Integration tests for the inspector registry and alloy certification.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	state::{ ROLE_ATTESTER, ROLE_INSPECTOR },
+};
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+	clock::Clock,
+	instruction::Instruction,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+};
+
+const CERTIFICATE_HASH: [u8; 32] = [5; 32];
+
+fn certify_instruction(alloy: &AlloyFixture, inspector: &Pubkey, certificate_hash: [u8; 32], expires_at: i64) -> Instruction {
+	NftInstruction::certify_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		inspector,
+		&registry_entry_key(ROLE_INSPECTOR, inspector).0,
+		alloy.id,
+		certificate_hash,
+		expires_at,
+	)
+}
+
+fn purchase_instruction(alloy: &AlloyFixture, buyer: &Pubkey, new_price: Option<u64>, require_certification: bool) -> Instruction {
+	NftInstruction::purchase_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		alloy.id,
+		None,
+		None,
+		None,
+		new_price,
+		require_certification,
+		buyer,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	)
+}
+
+async fn now(context: &mut ProgramTestContext) -> i64 {
+	context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+}
+
+async fn set_now(context: &mut ProgramTestContext, unix_timestamp: i64) {
+	let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+	context.set_sysvar(&Clock { unix_timestamp, ..clock });
+}
+
+/// An alloy and a registered inspector.
+async fn setup() -> (ProgramTestContext, AlloyFixture, Keypair) {
+	let mut context = program_test().start_with_context().await;
+	let payer = context.payer.insecure_clone();
+	let alloy = create_alloy(&mut context.banks_client, &payer, 1).await;
+	let authority = initialize_config(&mut context.banks_client, &payer).await;
+	let inspector = Keypair::new();
+	register(&mut context.banks_client, &payer, &authority, ROLE_INSPECTOR, &inspector.pubkey()).await;
+
+	(context, alloy, inspector)
+}
+
+#[tokio::test]
+async fn registered_inspector_certifies() {
+	let (mut context, alloy, inspector) = setup().await;
+	let payer = context.payer.insecure_clone();
+	let expires_at = now(&mut context).await + 86_400;
+
+	let instruction = certify_instruction(&alloy, &inspector.pubkey(), CERTIFICATE_HASH, expires_at);
+	process(&mut context.banks_client, &payer, &[instruction], &[&inspector]).await.unwrap();
+
+	let alloy_data = get_alloy_data(&mut context.banks_client, 1).await;
+	assert_eq!(alloy_data.inspector, inspector.pubkey());
+	assert_eq!(alloy_data.certificate_hash, CERTIFICATE_HASH);
+	assert_eq!(alloy_data.certificate_expires_at, expires_at);
+	assert!(alloy_data.is_certified(expires_at - 1));
+	assert!(!alloy_data.is_certified(expires_at));
+}
+
+#[tokio::test]
+async fn certification_requires_registered_inspector() {
+	let (mut context, alloy, _) = setup().await;
+	let payer = context.payer.insecure_clone();
+	let expires_at = now(&mut context).await + 86_400;
+
+	let stranger = Keypair::new();
+	let instruction = certify_instruction(&alloy, &stranger.pubkey(), CERTIFICATE_HASH, expires_at);
+	let result = process(&mut context.banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::NotRegistered);
+
+	// Being an attester does not make a lab an inspector.
+	let mut instruction = certify_instruction(&alloy, &stranger.pubkey(), CERTIFICATE_HASH, expires_at);
+	instruction.accounts[2].pubkey = registry_entry_key(ROLE_ATTESTER, &stranger.pubkey()).0;
+	let result = process(&mut context.banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::NotRegistered);
+}
+
+#[tokio::test]
+async fn certification_rejects_invalid_certificates() {
+	let (mut context, alloy, inspector) = setup().await;
+	let payer = context.payer.insecure_clone();
+	let now = now(&mut context).await;
+
+	let cases = [
+		([0; 32], now + 86_400, CustomError::MissingCertificateHash),
+		(CERTIFICATE_HASH, now, CustomError::InvalidCertificateExpiry),
+		(CERTIFICATE_HASH, now - 1, CustomError::InvalidCertificateExpiry),
+	];
+
+	for (certificate_hash, expires_at, expected) in cases {
+		let instruction = certify_instruction(&alloy, &inspector.pubkey(), certificate_hash, expires_at);
+		let result = process(&mut context.banks_client, &payer, &[instruction], &[&inspector]).await;
+
+		assert_custom_error(result, expected);
+	}
+}
+
+#[tokio::test]
+async fn purchase_can_require_valid_certification() {
+	let (mut context, alloy, inspector) = setup().await;
+	let payer = context.payer.insecure_clone();
+	let certified_at = now(&mut context).await;
+
+	// Each purchase relists at a different price so no two transactions are identical.
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), Some(LISTED_PRICE), true);
+	let result = process(&mut context.banks_client, &payer, &[instruction], &[]).await;
+	assert_custom_error(result, CustomError::CertificationExpired);
+
+	let instruction = certify_instruction(&alloy, &inspector.pubkey(), CERTIFICATE_HASH, certified_at + 3_600);
+	process(&mut context.banks_client, &payer, &[instruction], &[&inspector]).await.unwrap();
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), Some(LISTED_PRICE + 1), true);
+	process(&mut context.banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	set_now(&mut context, certified_at + 3_600).await;
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), Some(LISTED_PRICE + 2), true);
+	let result = process(&mut context.banks_client, &payer, &[instruction], &[]).await;
+	assert_custom_error(result, CustomError::CertificationExpired);
+
+	// Buyers who do not ask for a certificate can still buy an expired lot.
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), Some(LISTED_PRICE + 2), false);
+	process(&mut context.banks_client, &payer, &[instruction], &[]).await.unwrap();
+}
@@ -0,0 +1,400 @@
+//! Helpers shared by the `solana-program-test` suites.
+
+#![allow(dead_code)]
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::{ NftInstruction, ProvenanceArgs },
+	processor::Processor,
+	state::{
+		pad_str,
+		AlloyComposition,
+		AlloyData,
+		ATTESTATION_SEED,
+		BUNDLE_SEED,
+		COMPOSITION_SEED,
+		CONFIG_SEED,
+		CUSTODY_SEED,
+		ESCROW_SEED,
+		HEAT_SEED,
+		INSTALLMENT_SEED,
+		MAX_PROVENANCE_CODE_LENGTH,
+		PREFIX,
+		REDEMPTION_SEED,
+		REGISTRY_SEED,
+		RENTAL_SEED,
+		SWAP_SEED,
+		VAULT_SEED,
+	},
+};
+use solana_program_test::{ processor, BanksClient, ProgramTest };
+use solana_sdk::{
+	account::Account,
+	hash::hash,
+	instruction::{ Instruction, InstructionError },
+	program_pack::Pack,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	system_instruction,
+	transaction::{ Transaction, TransactionError },
+};
+
+pub const LISTED_PRICE: u64 = 1_500_000_000;
+pub const QUANTITY_GRAMS: u64 = 25_000;
+pub const MILL: &str = "DEW-SIEGEN";
+
+pub fn program_test() -> ProgramTest {
+	ProgramTest::new(
+		"alloy_token_program",
+		alloy_token_program::id(),
+		processor!(Processor::process_instruction),
+	)
+}
+
+pub fn alloy_data_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id]], &program_id)
+}
+
+pub fn composition_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], COMPOSITION_SEED.as_bytes()], &program_id)
+}
+
+pub fn config_key() -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), CONFIG_SEED.as_bytes()], &program_id)
+}
+
+pub fn registry_entry_key(role: u8, member: &Pubkey) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), REGISTRY_SEED.as_bytes(), &[role], member.as_ref()], &program_id)
+}
+
+pub fn attestation_key(id: u8, attester: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[id], ATTESTATION_SEED.as_bytes(), attester.as_ref(), &nonce.to_le_bytes()],
+		&program_id,
+	)
+}
+
+pub fn redemption_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], REDEMPTION_SEED.as_bytes()], &program_id)
+}
+
+pub fn escrow_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], REDEMPTION_SEED.as_bytes(), ESCROW_SEED.as_bytes()], &program_id)
+}
+
+pub fn vault_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], VAULT_SEED.as_bytes()], &program_id)
+}
+
+pub fn vault_escrow_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], VAULT_SEED.as_bytes(), ESCROW_SEED.as_bytes()], &program_id)
+}
+
+pub fn rental_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], RENTAL_SEED.as_bytes()], &program_id)
+}
+
+pub fn rental_escrow_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], RENTAL_SEED.as_bytes(), ESCROW_SEED.as_bytes()], &program_id)
+}
+
+pub fn installment_plan_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], INSTALLMENT_SEED.as_bytes()], &program_id)
+}
+
+pub fn installment_escrow_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], INSTALLMENT_SEED.as_bytes(), ESCROW_SEED.as_bytes()], &program_id)
+}
+
+pub fn bundle_key(seller: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), BUNDLE_SEED.as_bytes(), seller.as_ref(), &nonce.to_le_bytes()], &program_id)
+}
+
+pub fn bundle_escrow_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], BUNDLE_SEED.as_bytes(), ESCROW_SEED.as_bytes()], &program_id)
+}
+
+pub fn swap_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], SWAP_SEED.as_bytes()], &program_id)
+}
+
+pub fn swap_escrow_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], SWAP_SEED.as_bytes(), ESCROW_SEED.as_bytes()], &program_id)
+}
+
+pub fn custody_log_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], CUSTODY_SEED.as_bytes()], &program_id)
+}
+
+pub fn custody_page_key(id: u8, page: u32) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id], CUSTODY_SEED.as_bytes(), &page.to_le_bytes()], &program_id)
+}
+
+pub fn heat_index_key(mill: &str, heat_number: &str) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	let mill: [u8; MAX_PROVENANCE_CODE_LENGTH] = pad_str(mill);
+	let heat_number: [u8; MAX_PROVENANCE_CODE_LENGTH] = pad_str(heat_number);
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), HEAT_SEED.as_bytes(), &mill, &heat_number], &program_id)
+}
+
+/// Provenance from `MILL` with a heat number of its own for every alloy id.
+pub fn provenance(id: u8) -> ProvenanceArgs {
+	ProvenanceArgs {
+		mill: MILL.to_string(),
+		heat_number: format!("H{id:05}"),
+		production_date: 1_700_000_000,
+		standard: "EN 10084".to_string(),
+	}
+}
+
+/// Signs `instructions` with the payer and `signers` and processes them, returning the
+/// transaction error if any.
+pub async fn process(
+	banks_client: &mut BanksClient,
+	payer: &Keypair,
+	instructions: &[Instruction],
+	signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+	let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+
+	let mut all_signers = vec![payer];
+	all_signers.extend_from_slice(signers);
+
+	let transaction = Transaction::new_signed_with_payer(
+		instructions,
+		Some(&payer.pubkey()),
+		&all_signers,
+		recent_blockhash,
+	);
+
+	banks_client.process_transaction(transaction).await.map_err(|err| err.unwrap())
+}
+
+pub fn assert_instruction_error(result: Result<(), TransactionError>, expected: InstructionError) {
+	assert_eq!(result, Err(TransactionError::InstructionError(0, expected)));
+}
+
+pub fn assert_custom_error(result: Result<(), TransactionError>, expected: CustomError) {
+	assert_instruction_error(result, InstructionError::Custom(expected as u32));
+}
+
+pub async fn get_account(banks_client: &mut BanksClient, key: &Pubkey) -> Account {
+	banks_client.get_account(*key).await.unwrap().expect("account not found")
+}
+
+pub async fn get_alloy_data(banks_client: &mut BanksClient, id: u8) -> AlloyData {
+	let account = get_account(banks_client, &alloy_data_key(id).0).await;
+	AlloyData::unpack(&account.data).unwrap()
+}
+
+/// Token balance of an SPL token account.
+pub async fn token_amount(banks_client: &mut BanksClient, token_account: &Pubkey) -> u64 {
+	let account = get_account(banks_client, token_account).await;
+	spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+pub async fn get_composition(banks_client: &mut BanksClient, id: u8) -> AlloyComposition {
+	let account = get_account(banks_client, &composition_key(id).0).await;
+	AlloyComposition::unpack(&account.data).unwrap()
+}
+
+/// Creates an NFT mint with a token account for `owner` holding `amount` tokens, returning
+/// `(mint, token_account)`.
+pub async fn create_nft_with_amount(
+	banks_client: &mut BanksClient,
+	payer: &Keypair,
+	owner: &Pubkey,
+	amount: u64,
+) -> (Pubkey, Pubkey) {
+	let mint = Keypair::new();
+	let token_account = create_nft_with_mint(banks_client, payer, owner, &mint, amount).await;
+
+	(mint.pubkey(), token_account)
+}
+
+/// Like `create_nft_with_amount`, for a mint keypair the caller keeps. Returns the token
+/// account.
+pub async fn create_nft_with_mint(
+	banks_client: &mut BanksClient,
+	payer: &Keypair,
+	owner: &Pubkey,
+	mint: &Keypair,
+	amount: u64,
+) -> Pubkey {
+	let token_account = Keypair::new();
+	let rent = banks_client.get_rent().await.unwrap();
+
+	let mut instructions = vec![
+		system_instruction::create_account(
+			&payer.pubkey(),
+			&mint.pubkey(),
+			rent.minimum_balance(spl_token::state::Mint::LEN),
+			spl_token::state::Mint::LEN as u64,
+			&spl_token::id(),
+		),
+		spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer.pubkey(), None, 0).unwrap(),
+		system_instruction::create_account(
+			&payer.pubkey(),
+			&token_account.pubkey(),
+			rent.minimum_balance(spl_token::state::Account::LEN),
+			spl_token::state::Account::LEN as u64,
+			&spl_token::id(),
+		),
+		spl_token::instruction::initialize_account(&spl_token::id(), &token_account.pubkey(), &mint.pubkey(), owner).unwrap(),
+	];
+
+	if amount > 0 {
+		instructions.push(
+			spl_token::instruction::mint_to(&spl_token::id(), &mint.pubkey(), &token_account.pubkey(), &payer.pubkey(), &[], amount).unwrap(),
+		);
+	}
+
+	process(banks_client, payer, &instructions, &[mint, &token_account]).await.unwrap();
+
+	token_account.pubkey()
+}
+
+/// Creates a token account of `mint` for `owner`.
+pub async fn create_token_account(banks_client: &mut BanksClient, payer: &Keypair, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+	let token_account = Keypair::new();
+	let rent = banks_client.get_rent().await.unwrap();
+	let instructions = [
+		system_instruction::create_account(
+			&payer.pubkey(),
+			&token_account.pubkey(),
+			rent.minimum_balance(spl_token::state::Account::LEN),
+			spl_token::state::Account::LEN as u64,
+			&spl_token::id(),
+		),
+		spl_token::instruction::initialize_account(&spl_token::id(), &token_account.pubkey(), mint, owner).unwrap(),
+	];
+	process(banks_client, payer, &instructions, &[&token_account]).await.unwrap();
+
+	token_account.pubkey()
+}
+
+/// Creates an NFT mint and a token account for `owner` holding its single token, returning
+/// `(mint, token_account)`.
+pub async fn create_nft(banks_client: &mut BanksClient, payer: &Keypair, owner: &Pubkey) -> (Pubkey, Pubkey) {
+	create_nft_with_amount(banks_client, payer, owner, 1).await
+}
+
+/// Stand-in for the SHA-256 of the metadata document behind `uri`.
+pub fn uri_hash(uri: &str) -> [u8; 32] {
+	hash(uri.as_bytes()).to_bytes()
+}
+
+pub fn create_alloy_instruction(payer: &Pubkey, id: u8, name: &str, symbol: &str, uri: &str, mint: &Pubkey) -> Instruction {
+	create_alloy_instruction_with_provenance(payer, id, name, symbol, uri, mint, provenance(id))
+}
+
+pub fn create_alloy_instruction_with_provenance(
+	payer: &Pubkey,
+	id: u8,
+	name: &str,
+	symbol: &str,
+	uri: &str,
+	mint: &Pubkey,
+	provenance: ProvenanceArgs,
+) -> Instruction {
+	NftInstruction::create_alloy_data_accounts(
+		&alloy_token_program::id(),
+		&alloy_data_key(id).0,
+		&heat_index_key(&provenance.mill, &provenance.heat_number).0,
+		payer,
+		id,
+		name.to_string(),
+		symbol.to_string(),
+		uri.to_string(),
+		uri_hash(uri),
+		0,
+		LISTED_PRICE,
+		QUANTITY_GRAMS,
+		provenance,
+		mint,
+	)
+}
+
+/// An alloy created on-chain, backed by an NFT held by `owner`.
+pub struct AlloyFixture {
+	pub id: u8,
+	pub alloy_data: Pubkey,
+	pub mint: Pubkey,
+	pub token_account: Pubkey,
+	pub owner: Keypair,
+}
+
+pub async fn create_alloy(banks_client: &mut BanksClient, payer: &Keypair, id: u8) -> AlloyFixture {
+	let owner = Keypair::new();
+	let (mint, token_account) = create_nft(banks_client, payer, &owner.pubkey()).await;
+
+	let instruction = create_alloy_instruction(
+		&payer.pubkey(),
+		id,
+		"20MnCr5",
+		"ALLOY",
+		"https://example.com/alloys/20MnCr5.json",
+		&mint,
+	);
+	process(banks_client, payer, &[instruction], &[]).await.unwrap();
+
+	AlloyFixture {
+		id,
+		alloy_data: alloy_data_key(id).0,
+		mint,
+		token_account,
+		owner,
+	}
+}
+
+/// Initializes the program config with a fresh authority and returns it.
+pub async fn initialize_config(banks_client: &mut BanksClient, payer: &Keypair) -> Keypair {
+	let authority = Keypair::new();
+
+	let instruction = NftInstruction::initialize_config(
+		&alloy_token_program::id(),
+		&config_key().0,
+		&authority.pubkey(),
+		&payer.pubkey(),
+	);
+	process(banks_client, payer, &[instruction], &[]).await.unwrap();
+
+	authority
+}
+
+pub fn add_registry_entry_instruction(authority: &Pubkey, payer: &Pubkey, role: u8, member: &Pubkey) -> Instruction {
+	NftInstruction::add_registry_entry(
+		&alloy_token_program::id(),
+		&config_key().0,
+		authority,
+		&registry_entry_key(role, member).0,
+		role,
+		member,
+		payer,
+	)
+}
+
+/// Enrols `member` under `role`, signed by the config `authority`.
+pub async fn register(banks_client: &mut BanksClient, payer: &Keypair, authority: &Keypair, role: u8, member: &Pubkey) {
+	let instruction = add_registry_entry_instruction(&authority.pubkey(), &payer.pubkey(), role, member);
+	process(banks_client, payer, &[instruction], &[authority]).await.unwrap();
+}
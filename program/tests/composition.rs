@@ -0,0 +1,158 @@
+//! Integration tests for the per-alloy chemical composition account.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	state::{ ElementComposition, MAX_COMPOSITION_ELEMENTS },
+};
+use common::*;
+use solana_sdk::{
+	instruction::Instruction,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+};
+
+/// 20MnCr5 as specified by EN 10084, with the measured heat in between.
+fn composition_20mncr5() -> Vec<ElementComposition> {
+	vec![
+		ElementComposition::new("C", 17, 22, 20),
+		ElementComposition::new("Si", 0, 40, 25),
+		ElementComposition::new("Mn", 110, 140, 125),
+		ElementComposition::new("Cr", 100, 130, 115),
+		ElementComposition::new("Fe", 9600, 9800, 9700),
+	]
+}
+
+fn create_composition_instruction(alloy: &AlloyFixture, payer: &Pubkey, elements: Vec<ElementComposition>) -> Instruction {
+	NftInstruction::create_alloy_composition(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&composition_key(alloy.id).0,
+		alloy.id,
+		elements,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		payer,
+	)
+}
+
+fn update_composition_instruction(alloy: &AlloyFixture, elements: Vec<ElementComposition>) -> Instruction {
+	NftInstruction::update_alloy_composition(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&composition_key(alloy.id).0,
+		alloy.id,
+		elements,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	)
+}
+
+#[tokio::test]
+async fn create_alloy_composition() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = create_composition_instruction(&alloy, &payer.pubkey(), composition_20mncr5());
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let composition = get_composition(&mut banks_client, 1).await;
+	assert_eq!(composition.id, 1);
+	assert_eq!(composition.bump, composition_key(1).1);
+	assert_eq!(composition.elements(), composition_20mncr5());
+	assert_eq!(composition.element("Cr"), Some(&ElementComposition::new("Cr", 100, 130, 115)));
+	assert_eq!(composition.element("Ni"), None);
+}
+
+#[tokio::test]
+async fn update_alloy_composition_replaces_elements() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = create_composition_instruction(&alloy, &payer.pubkey(), composition_20mncr5());
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let sae_8620 = vec![
+		ElementComposition::new("C", 18, 23, 20),
+		ElementComposition::new("Ni", 40, 70, 55),
+		ElementComposition::new("Mo", 15, 25, 20),
+	];
+	let instruction = update_composition_instruction(&alloy, sae_8620.clone());
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let composition = get_composition(&mut banks_client, 1).await;
+	assert_eq!(composition.elements(), sae_8620);
+	assert!(composition.elements[sae_8620.len()..].iter().all(|element| *element == ElementComposition::default()));
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_compositions() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let cases = [
+		(vec![ElementComposition::new("Fe", 9000, 10_000, 9500), ElementComposition::new("Cr", 600, 800, 700)], CustomError::CompositionExceedsTotal),
+		(vec![ElementComposition::new("CR", 100, 130, 115)], CustomError::InvalidElementSymbol),
+		(vec![ElementComposition::new("", 100, 130, 115)], CustomError::InvalidElementSymbol),
+		(vec![ElementComposition::new("Cr", 100, 130, 115), ElementComposition::new("Cr", 0, 10, 5)], CustomError::DuplicateElement),
+		(vec![ElementComposition::new("Cr", 100, 130, 90)], CustomError::InvalidElementRange),
+		(vec![ElementComposition::new("Cr", 130, 100, 115)], CustomError::InvalidElementRange),
+		(vec![ElementComposition::new("Fe", 9000, 10_001, 9500)], CustomError::InvalidElementRange),
+		(vec![ElementComposition::new("C", 0, 1, 0); MAX_COMPOSITION_ELEMENTS + 1], CustomError::TooManyElements),
+	];
+
+	for (elements, expected) in cases {
+		let instruction = create_composition_instruction(&alloy, &payer.pubkey(), elements);
+		let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+		assert_custom_error(result, expected);
+	}
+}
+
+#[tokio::test]
+async fn create_rejects_existing_composition() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = create_composition_instruction(&alloy, &payer.pubkey(), composition_20mncr5());
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let instruction = create_composition_instruction(&alloy, &payer.pubkey(), vec![]);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::AlreadyInitialized);
+}
+
+#[tokio::test]
+async fn create_rejects_composition_pda_of_another_id() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = create_composition_instruction(&alloy, &payer.pubkey(), composition_20mncr5());
+	instruction.accounts[1].pubkey = composition_key(2).0;
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::InvalidCompositionKey);
+}
+
+#[tokio::test]
+async fn composition_requires_nft_holder() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let stranger = Keypair::new();
+
+	let mut instruction = create_composition_instruction(&alloy, &payer.pubkey(), composition_20mncr5());
+	instruction.accounts[2].pubkey = stranger.pubkey();
+	let result = process(&mut banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::InvalidOwner);
+
+	let instruction = create_composition_instruction(&alloy, &payer.pubkey(), composition_20mncr5());
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let mut instruction = update_composition_instruction(&alloy, vec![]);
+	instruction.accounts[2].pubkey = stranger.pubkey();
+	let result = process(&mut banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::InvalidOwner);
+}
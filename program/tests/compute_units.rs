@@ -0,0 +1,159 @@
+//! Compute-unit tests for the alloy instructions.
+//!
+//! Under plain `cargo test` the processor runs natively and only syscalls and CPIs are
+//! metered, so the units these runs report say nothing about the program's own cost. Tests
+//! asserting on them only run under `cargo test-sbf`; natively the stored-bump validation is
+//! pinned by behaviour instead.
+
+mod common;
+
+use alloy_token_program::{ instruction::NftInstruction, state::{ AlloyData, PREFIX } };
+use common::{ alloy_data_key, create_nft, get_account, heat_index_key, program_test, provenance, MILL };
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	account::AccountSharedData,
+	instruction::Instruction,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	transaction::Transaction,
+};
+
+async fn compute_units(banks_client: &mut BanksClient, payer: &Keypair, instruction: Instruction, signers: &[&Keypair]) -> u64 {
+	let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+
+	let mut all_signers = vec![payer];
+	all_signers.extend_from_slice(signers);
+
+	let transaction = Transaction::new_signed_with_payer(
+		&[instruction],
+		Some(&payer.pubkey()),
+		&all_signers,
+		recent_blockhash,
+	);
+
+	// Simulating takes no account locks, unlike `process_transaction_with_metadata`, which can
+	// race the previous transaction's locks and fail with `AccountInUse`.
+	let simulation = banks_client.simulate_transaction(transaction.clone()).await.unwrap();
+	simulation.result.unwrap().unwrap();
+	banks_client.process_transaction(transaction).await.unwrap();
+
+	simulation.simulation_details.unwrap().units_consumed
+}
+
+/// Creates alloy `id` backed by a fresh NFT held by `owner`, returning the owner's token
+/// account.
+async fn create_alloy(banks_client: &mut BanksClient, payer: &Keypair, owner: &Keypair, id: u8) -> Pubkey {
+	let (mint, token_account) = create_nft(banks_client, payer, &owner.pubkey()).await;
+
+	let create = NftInstruction::create_alloy_data_accounts(
+		&alloy_token_program::id(),
+		&alloy_data_key(id).0,
+		&heat_index_key(MILL, &provenance(id).heat_number).0,
+		&payer.pubkey(),
+		id,
+		"20MnCr5".to_string(),
+		"ALLOY".to_string(),
+		"https://example.com/alloys/20MnCr5.json".to_string(),
+		[7; 32],
+		0,
+		1_500_000_000,
+		25_000,
+		provenance(id),
+		&mint,
+	);
+
+	compute_units(banks_client, payer, create, &[]).await;
+
+	token_account
+}
+
+async fn update_price_units(banks_client: &mut BanksClient, payer: &Keypair, owner: &Keypair, token_account: &Pubkey, id: u8) -> u64 {
+	let update_price = NftInstruction::update_alloy_price(
+		&alloy_token_program::id(),
+		&alloy_data_key(id).0,
+		id,
+		2_000_000_000,
+		&owner.pubkey(),
+		token_account,
+	);
+
+	compute_units(banks_client, payer, update_price, &[owner]).await
+}
+
+/// Units charged for one `create_program_address` syscall, i.e. one step of the bump search
+/// `find_program_address` makes.
+const CREATE_PROGRAM_ADDRESS_UNITS: u64 = 1_500;
+
+/// Searching for the alloy PDA of `deep_id` takes several steps while the one of
+/// `shallow_id` is found at the first. Validating against the stored bump costs a single
+/// derivation either way, so `UpdateAlloyPrice` must cost the same for both; going back to
+/// `find_program_address` would make `deep_id` dearer by a step for every bump it skips.
+#[tokio::test]
+#[cfg_attr(not(feature = "test-sbf"), ignore = "compute units are only metered under cargo test-sbf")]
+async fn update_price_units_do_not_grow_with_bump_search() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let owner = Keypair::new();
+
+	let bump = |id: u8| alloy_data_key(id).1;
+	let shallow_id = (0..=u8::MAX).find(|&id| bump(id) == u8::MAX).unwrap();
+	let deep_id = (0..=u8::MAX).min_by_key(|&id| bump(id)).unwrap();
+	assert!(bump(deep_id) < u8::MAX - 2, "no alloy id needs a long enough bump search");
+
+	let shallow_token_account = create_alloy(&mut banks_client, &payer, &owner, shallow_id).await;
+	let deep_token_account = create_alloy(&mut banks_client, &payer, &owner, deep_id).await;
+
+	let shallow_units = update_price_units(&mut banks_client, &payer, &owner, &shallow_token_account, shallow_id).await;
+	let deep_units = update_price_units(&mut banks_client, &payer, &owner, &deep_token_account, deep_id).await;
+
+	assert!(
+		shallow_units.abs_diff(deep_units) < CREATE_PROGRAM_ADDRESS_UNITS,
+		"UpdateAlloyPrice took {} units for alloy {} (bump {}) but {} for alloy {} (bump {})",
+		shallow_units,
+		shallow_id,
+		bump(shallow_id),
+		deep_units,
+		deep_id,
+		bump(deep_id),
+	);
+}
+
+/// Plants a copy of alloy 1 at its PDA for the next valid bump below the canonical one, with
+/// that bump stored. Validating against the stored bump accepts the copy, whereas re-deriving
+/// the canonical PDA with `find_program_address` would reject it, so this fails if
+/// `UpdateAlloyPrice` goes back to searching for the bump.
+#[tokio::test]
+async fn update_price_validates_against_stored_bump() {
+	let mut context = program_test().start_with_context().await;
+	let payer = context.payer.insecure_clone();
+	let owner = Keypair::new();
+
+	let token_account = create_alloy(&mut context.banks_client, &payer, &owner, 1).await;
+
+	let program_id = alloy_token_program::id();
+	let (canonical_key, canonical_bump) = alloy_data_key(1);
+	let (stored_bump, planted_key) = (0..canonical_bump)
+		.rev()
+		.find_map(|bump| {
+			Pubkey::create_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[1], &[bump]], &program_id)
+				.ok()
+				.map(|key| (bump, key))
+		})
+		.unwrap();
+
+	let mut account = get_account(&mut context.banks_client, &canonical_key).await;
+	AlloyData::load_mut(&mut account.data).unwrap().bump = stored_bump;
+	context.set_account(&planted_key, &AccountSharedData::from(account));
+
+	let update_price = NftInstruction::update_alloy_price(
+		&program_id,
+		&planted_key,
+		1,
+		2_000_000_000,
+		&owner.pubkey(),
+		&token_account,
+	);
+	compute_units(&mut context.banks_client, &payer, update_price, &[&owner]).await;
+
+	let account = get_account(&mut context.banks_client, &planted_key).await;
+	assert_eq!(AlloyData::unpack(&account.data).unwrap().listed_price, 2_000_000_000);
+}
@@ -0,0 +1,123 @@
+//! Integration tests for the chain-of-custody log.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	state::{ CustodyEntry, CustodyLog, CustodyLogPage, CUSTODY_ENTRIES_PER_PAGE, CUSTODY_PAGE_DATA_SIZE, ROLE_CUSTODIAN },
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	instruction::Instruction,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+};
+
+fn record_instruction(alloy: &AlloyFixture, custodian: &Pubkey, payer: &Pubkey, page: u32, location: &str, document_hash: [u8; 32]) -> Instruction {
+	NftInstruction::record_custody(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&custody_log_key(alloy.id).0,
+		&custody_page_key(alloy.id, page).0,
+		custodian,
+		&registry_entry_key(ROLE_CUSTODIAN, custodian).0,
+		payer,
+		alloy.id,
+		location,
+		document_hash,
+	)
+}
+
+/// An alloy and two registered custodians.
+async fn setup() -> (BanksClient, Keypair, AlloyFixture, [Keypair; 2]) {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let authority = initialize_config(&mut banks_client, &payer).await;
+	let custodians = [Keypair::new(), Keypair::new()];
+
+	for custodian in &custodians {
+		register(&mut banks_client, &payer, &authority, ROLE_CUSTODIAN, &custodian.pubkey()).await;
+	}
+
+	(banks_client, payer, alloy, custodians)
+}
+
+#[tokio::test]
+async fn handoffs_are_chained_across_pages() {
+	let (mut banks_client, payer, alloy, custodians) = setup().await;
+	let entry_count = CUSTODY_ENTRIES_PER_PAGE + 1;
+
+	for index in 0..entry_count {
+		let custodian = &custodians[index % 2];
+		let page = (index / CUSTODY_ENTRIES_PER_PAGE) as u32;
+		let instruction = record_instruction(&alloy, &custodian.pubkey(), &payer.pubkey(), page, "DEHAM", [index as u8 + 1; 32]);
+
+		process(&mut banks_client, &payer, &[instruction], &[custodian]).await.unwrap();
+	}
+
+	let log = CustodyLog::unpack(&get_account(&mut banks_client, &custody_log_key(1).0).await.data).unwrap();
+	assert_eq!(log.alloy, alloy.alloy_data);
+	assert_eq!(log.entry_count as usize, entry_count);
+	assert_eq!(log.current_custodian, custodians[0].pubkey());
+
+	let first_page = CustodyLogPage::unpack(&get_account(&mut banks_client, &custody_page_key(1, 0).0).await.data).unwrap();
+	let second_page = CustodyLogPage::unpack(&get_account(&mut banks_client, &custody_page_key(1, 1).0).await.data).unwrap();
+	let entries: Vec<_> = first_page.entries().iter().chain(second_page.entries()).collect();
+
+	assert_eq!(second_page.page, 1);
+	assert_eq!(entries.len(), entry_count);
+	assert_eq!(entries[0].from, Pubkey::default());
+	assert_eq!(entries[0].location(), "DEHAM");
+
+	for (index, pair) in entries.windows(2).enumerate() {
+		assert_eq!(pair[1].from, pair[0].to);
+		assert_eq!(pair[1].to, custodians[(index + 1) % 2].pubkey());
+		assert_eq!(pair[1].document_hash, [index as u8 + 2; 32]);
+	}
+}
+
+#[tokio::test]
+async fn custody_requires_registered_custodian() {
+	let (mut banks_client, payer, alloy, _) = setup().await;
+	let stranger = Keypair::new();
+
+	let instruction = record_instruction(&alloy, &stranger.pubkey(), &payer.pubkey(), 0, "DEHAM", [1; 32]);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&stranger]).await;
+
+	assert_custom_error(result, CustomError::NotRegistered);
+}
+
+#[tokio::test]
+async fn custody_rejects_invalid_entries() {
+	let (mut banks_client, payer, alloy, custodians) = setup().await;
+	let custodian = &custodians[0];
+
+	let cases = [
+		(0, "", [1; 32], CustomError::InvalidLocationCode),
+		(0, "deham", [1; 32], CustomError::InvalidLocationCode),
+		(0, "WAREHOUSE", [1; 32], CustomError::InvalidLocationCode),
+		(0, "DEHAM", [0; 32], CustomError::MissingDocumentHash),
+		(1, "DEHAM", [1; 32], CustomError::InvalidCustodyLogKey),
+	];
+
+	for (page, location, document_hash, expected) in cases {
+		let instruction = record_instruction(&alloy, &custodian.pubkey(), &payer.pubkey(), page, location, document_hash);
+		let result = process(&mut banks_client, &payer, &[instruction], &[custodian]).await;
+
+		assert_custom_error(result, expected);
+	}
+}
+
+#[test]
+fn full_custody_page_rejects_entries() {
+	let mut page = CustodyLogPage::unpack(&[0; CUSTODY_PAGE_DATA_SIZE]).unwrap();
+
+	for _ in 0..CUSTODY_ENTRIES_PER_PAGE {
+		page.push(CustodyEntry::default()).unwrap();
+	}
+
+	assert_eq!(page.push(CustodyEntry::default()), Err(CustomError::CustodyPageFull.into()));
+	assert_eq!(page.entries().len(), CUSTODY_ENTRIES_PER_PAGE);
+}
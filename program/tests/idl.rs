@@ -0,0 +1,116 @@
+//! Keeps the checked-in IDL in sync with the program.
+
+use alloy_token_program::{
+	error::CustomError,
+	idl::{ idl, idl_json },
+	instruction::NftInstruction,
+	state::{
+		ATTESTATION_DATA_SIZE,
+		BUNDLE_DATA_SIZE,
+		COMPOSITION_DATA_SIZE,
+		CONFIG_DATA_SIZE,
+		CUSTODY_LOG_DATA_SIZE,
+		CUSTODY_PAGE_DATA_SIZE,
+		HEAT_INDEX_DATA_SIZE,
+		INSTALLMENT_PLAN_DATA_SIZE,
+		VAULT_DATA_SIZE,
+		MAX_DATA_SIZE,
+		REDEMPTION_DATA_SIZE,
+		REGISTRY_ENTRY_DATA_SIZE,
+		RENTAL_DATA_SIZE,
+		SWAP_DATA_SIZE,
+	},
+};
+use serde_json::Value;
+
+const IDL_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/idl/alloy_token_program.json");
+
+#[test]
+fn checked_in_idl_is_up_to_date() {
+	let checked_in = std::fs::read_to_string(IDL_PATH).unwrap();
+
+	assert!(
+		checked_in == idl_json(),
+		"{} is out of date, regenerate it with `cargo run --features idl --bin idl > idl/alloy_token_program.json`",
+		IDL_PATH,
+	);
+}
+
+#[test]
+fn idl_lists_every_instruction_the_program_decodes() {
+	let idl = idl();
+	let discriminants: Vec<u64> = idl["instructions"].as_array().unwrap()
+		.iter()
+		.map(|instruction| instruction["discriminant"]["value"].as_u64().unwrap())
+		.collect();
+
+	for discriminator in 0..=u8::MAX {
+		let decodes = (0..=u8::MAX).any(|version| {
+			NftInstruction::unpack(&[discriminator, version]) != Err(CustomError::UnknownInstruction.into())
+		});
+
+		assert_eq!(decodes, discriminants.contains(&(discriminator as u64)), "discriminator {}", discriminator);
+	}
+}
+
+fn type_size(idl: &Value, ty: &Value) -> usize {
+	match ty {
+		Value::String(name) => match name.as_str() {
+			"u8" => 1,
+			"u16" => 2,
+			"u32" => 4,
+			"u64" | "i64" => 8,
+			"publicKey" => 32,
+			other => panic!("unexpected type {}", other),
+		},
+		Value::Object(object) if object.contains_key("defined") => {
+			let defined = idl["types"].as_array().unwrap()
+				.iter()
+				.find(|ty| ty["name"] == object["defined"])
+				.unwrap();
+
+			struct_size(idl, defined)
+		},
+		Value::Object(object) => {
+			let array = object["array"].as_array().unwrap();
+			type_size(idl, &array[0]) * array[1].as_u64().unwrap() as usize
+		},
+		other => panic!("unexpected type {}", other),
+	}
+}
+
+fn struct_size(idl: &Value, definition: &Value) -> usize {
+	definition["type"]["fields"].as_array().unwrap()
+		.iter()
+		.map(|field| type_size(idl, &field["type"]))
+		.sum()
+}
+
+#[test]
+fn idl_account_layouts_match_account_sizes() {
+	let idl = idl();
+	let expected = [
+		("AlloyData", MAX_DATA_SIZE),
+		("AlloyComposition", COMPOSITION_DATA_SIZE),
+		("ProgramConfig", CONFIG_DATA_SIZE),
+		("RegistryEntry", REGISTRY_ENTRY_DATA_SIZE),
+		("Attestation", ATTESTATION_DATA_SIZE),
+		("Redemption", REDEMPTION_DATA_SIZE),
+		("CustodyLog", CUSTODY_LOG_DATA_SIZE),
+		("CustodyLogPage", CUSTODY_PAGE_DATA_SIZE),
+		("HeatIndex", HEAT_INDEX_DATA_SIZE),
+		("Vault", VAULT_DATA_SIZE),
+		("Rental", RENTAL_DATA_SIZE),
+		("InstallmentPlan", INSTALLMENT_PLAN_DATA_SIZE),
+		("Bundle", BUNDLE_DATA_SIZE),
+		("Swap", SWAP_DATA_SIZE),
+	];
+	let accounts = idl["accounts"].as_array().unwrap();
+
+	assert_eq!(accounts.len(), expected.len());
+
+	for (account, (name, size)) in accounts.iter().zip(expected) {
+		assert_eq!(account["name"], name);
+		assert_eq!(struct_size(&idl, account), size, "{}", name);
+	}
+}
@@ -0,0 +1,202 @@
+//! Integration tests for installment purchases.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	state::InstallmentPlan,
+};
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+	clock::Clock,
+	instruction::Instruction,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	system_instruction,
+};
+
+const TOTAL_PRICE: u64 = 3_000_000_001;
+const PAYMENTS: u8 = 3;
+const INTERVAL: i64 = 30 * 86_400;
+
+struct Plan {
+	alloy: AlloyFixture,
+	buyer: Keypair,
+	buyer_token_account: Pubkey,
+	first_due_at: i64,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_plan_instruction(
+	alloy: &AlloyFixture,
+	payer: &Pubkey,
+	buyer: &Pubkey,
+	total_price: u64,
+	payments: u8,
+	first_due_at: i64,
+	forfeit_bps: u16,
+) -> Instruction {
+	NftInstruction::create_installment_plan(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&installment_plan_key(alloy.id).0,
+		&installment_escrow_key(alloy.id).0,
+		&alloy.mint,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		payer,
+		alloy.id,
+		buyer,
+		total_price,
+		payments,
+		first_due_at,
+		INTERVAL,
+		forfeit_bps,
+	)
+}
+
+fn pay_instruction(plan: &Plan) -> Instruction {
+	NftInstruction::pay_installment(
+		&alloy_token_program::id(),
+		&plan.alloy.alloy_data,
+		&installment_plan_key(plan.alloy.id).0,
+		&installment_escrow_key(plan.alloy.id).0,
+		&plan.alloy.owner.pubkey(),
+		&plan.buyer.pubkey(),
+		&plan.buyer_token_account,
+		plan.alloy.id,
+	)
+}
+
+fn reclaim_instruction(plan: &Plan) -> Instruction {
+	NftInstruction::reclaim_installment_plan(
+		&alloy_token_program::id(),
+		&installment_plan_key(plan.alloy.id).0,
+		&installment_escrow_key(plan.alloy.id).0,
+		&plan.alloy.owner.pubkey(),
+		&plan.alloy.token_account,
+		&plan.buyer.pubkey(),
+		plan.alloy.id,
+	)
+}
+
+async fn now(context: &mut ProgramTestContext) -> i64 {
+	context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+}
+
+async fn set_now(context: &mut ProgramTestContext, unix_timestamp: i64) {
+	let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+	context.set_sysvar(&Clock { unix_timestamp, ..clock });
+}
+
+/// Pays the next installment. Every payment tops the buyer up by a different amount first,
+/// so no two payment transactions are identical.
+async fn pay(context: &mut ProgramTestContext, plan: &Plan, top_up: u64) -> Result<(), solana_sdk::transaction::TransactionError> {
+	let payer = context.payer.insecure_clone();
+	let top_up = system_instruction::transfer(&payer.pubkey(), &plan.buyer.pubkey(), top_up);
+
+	process(&mut context.banks_client, &payer, &[top_up, pay_instruction(plan)], &[&plan.buyer]).await
+}
+
+/// An alloy escrowed in an installment plan for a funded buyer.
+async fn setup(forfeit_bps: u16) -> (ProgramTestContext, Plan) {
+	let mut context = program_test().start_with_context().await;
+	let payer = context.payer.insecure_clone();
+	let alloy = create_alloy(&mut context.banks_client, &payer, 1).await;
+	let buyer = Keypair::new();
+	let buyer_token_account = create_token_account(&mut context.banks_client, &payer, &alloy.mint, &buyer.pubkey()).await;
+	let first_due_at = now(&mut context).await + INTERVAL;
+
+	// Enough to pay off the plan and stay rent exempt.
+	let fund = system_instruction::transfer(&payer.pubkey(), &buyer.pubkey(), TOTAL_PRICE + 1_000_000_000);
+	let instruction = create_plan_instruction(&alloy, &payer.pubkey(), &buyer.pubkey(), TOTAL_PRICE, PAYMENTS, first_due_at, forfeit_bps);
+	process(&mut context.banks_client, &payer, &[fund, instruction], &[&alloy.owner]).await.unwrap();
+
+	(context, Plan { alloy, buyer, buyer_token_account, first_due_at })
+}
+
+#[tokio::test]
+async fn final_installment_releases_the_nft_and_pays_the_seller() {
+	let (mut context, plan) = setup(0).await;
+
+	assert_eq!(token_amount(&mut context.banks_client, &installment_escrow_key(1).0).await, 1);
+	assert_eq!(token_amount(&mut context.banks_client, &plan.alloy.token_account).await, 0);
+
+	pay(&mut context, &plan, 1).await.unwrap();
+	// Paying ahead of schedule is fine; each deadline only bounds the latest payment.
+	pay(&mut context, &plan, 2).await.unwrap();
+
+	let state = InstallmentPlan::unpack(&get_account(&mut context.banks_client, &installment_plan_key(1).0).await.data).unwrap();
+	assert_eq!(state.payments_made, 2);
+	assert_eq!(state.paid, 2 * (TOTAL_PRICE / 3));
+	assert_eq!(state.next_due_at(), plan.first_due_at + 2 * INTERVAL);
+
+	let plan_lamports = get_account(&mut context.banks_client, &installment_plan_key(1).0).await.lamports;
+	let escrow_lamports = get_account(&mut context.banks_client, &installment_escrow_key(1).0).await.lamports;
+	set_now(&mut context, plan.first_due_at + 2 * INTERVAL).await;
+	pay(&mut context, &plan, 3).await.unwrap();
+
+	assert_eq!(token_amount(&mut context.banks_client, &plan.buyer_token_account).await, 1);
+	assert!(context.banks_client.get_account(installment_plan_key(1).0).await.unwrap().is_none());
+	assert!(context.banks_client.get_account(installment_escrow_key(1).0).await.unwrap().is_none());
+	// The last payment settles the rounding remainder.
+	assert_eq!(
+		context.banks_client.get_balance(plan.alloy.owner.pubkey()).await.unwrap(),
+		plan_lamports + escrow_lamports + TOTAL_PRICE - 2 * (TOTAL_PRICE / 3),
+	);
+	assert_eq!(get_alloy_data(&mut context.banks_client, 1).await.last_price, TOTAL_PRICE);
+}
+
+#[tokio::test]
+async fn missed_deadline_lets_the_seller_reclaim_the_nft() {
+	let (mut context, plan) = setup(2_500).await;
+	let payer = context.payer.insecure_clone();
+
+	pay(&mut context, &plan, 1).await.unwrap();
+
+	let result = process(&mut context.banks_client, &payer, &[reclaim_instruction(&plan)], &[&plan.alloy.owner]).await;
+	assert_custom_error(result, CustomError::InstallmentNotOverdue);
+
+	set_now(&mut context, plan.first_due_at + INTERVAL + 1).await;
+
+	let result = process(&mut context.banks_client, &payer, &[pay_instruction(&plan)], &[&plan.buyer]).await;
+	assert_custom_error(result, CustomError::InstallmentOverdue);
+
+	let buyer_balance = context.banks_client.get_balance(plan.buyer.pubkey()).await.unwrap();
+	// The transfer keeps the transaction distinct from the rejected one above.
+	let transfer = system_instruction::transfer(&payer.pubkey(), &plan.alloy.owner.pubkey(), 1_000_000);
+	process(&mut context.banks_client, &payer, &[transfer, reclaim_instruction(&plan)], &[&plan.alloy.owner]).await.unwrap();
+
+	assert_eq!(token_amount(&mut context.banks_client, &plan.alloy.token_account).await, 1);
+	assert!(context.banks_client.get_account(installment_plan_key(1).0).await.unwrap().is_none());
+	assert_eq!(context.banks_client.get_balance(plan.buyer.pubkey()).await.unwrap(), buyer_balance + TOTAL_PRICE / 3 * 3 / 4);
+}
+
+#[tokio::test]
+async fn installment_plan_rejects_invalid_terms_and_strangers() {
+	let (mut context, plan) = setup(0).await;
+	let payer = context.payer.insecure_clone();
+	let alloy = create_alloy(&mut context.banks_client, &payer, 2).await;
+	let now = now(&mut context).await;
+
+	let cases = [
+		(TOTAL_PRICE, 0, now + INTERVAL, 0),
+		(2, 3, now + INTERVAL, 0),
+		(TOTAL_PRICE, PAYMENTS, now, 0),
+		(TOTAL_PRICE, PAYMENTS, now + INTERVAL, 10_001),
+	];
+
+	for (total_price, payments, first_due_at, forfeit_bps) in cases {
+		let instruction = create_plan_instruction(&alloy, &payer.pubkey(), &plan.buyer.pubkey(), total_price, payments, first_due_at, forfeit_bps);
+		let result = process(&mut context.banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+		assert_custom_error(result, CustomError::InvalidInstallmentTerms);
+	}
+
+	let stranger = Keypair::new();
+	let mut instruction = pay_instruction(&plan);
+	instruction.accounts[4].pubkey = stranger.pubkey();
+	let result = process(&mut context.banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::InvalidOwner);
+}
@@ -0,0 +1,269 @@
+//! Round-trip tests proving that the account lists built by the `NftInstruction` builders
+//! are the ones the processors parse.
+
+use alloy_token_program::{
+	accounts::{ CreateAlloyDataAccountAccounts, UpdateAlloyPriceAccounts, PurchaseAlloyAccounts, UpdateAlloyCompositionAccounts },
+	instruction::{ NftInstruction, ProvenanceArgs },
+	state::{ pad_str, AlloyComposition, AlloyData, COMPOSITION_DATA_SIZE, COMPOSITION_SEED, HEAT_SEED, PREFIX, MAX_DATA_SIZE },
+};
+use solana_sdk::{
+	account_info::AccountInfo,
+	instruction::Instruction,
+	pubkey::Pubkey,
+	system_program,
+	sysvar,
+};
+
+/// Backing storage for the `AccountInfo`s the processor side parses.
+struct TestAccount {
+	key: Pubkey,
+	owner: Pubkey,
+	lamports: u64,
+	data: Vec<u8>,
+	is_signer: bool,
+	is_writable: bool,
+}
+
+fn test_accounts(instruction: &Instruction, owner_of: impl Fn(&Pubkey) -> (Pubkey, Vec<u8>)) -> Vec<TestAccount> {
+	instruction.accounts.iter().map(|meta| {
+		let (owner, data) = owner_of(&meta.pubkey);
+
+		TestAccount {
+			key: meta.pubkey,
+			owner,
+			lamports: 1_000_000,
+			data,
+			is_signer: meta.is_signer,
+			is_writable: meta.is_writable,
+		}
+	}).collect()
+}
+
+fn account_infos(accounts: &mut [TestAccount]) -> Vec<AccountInfo<'_>> {
+	accounts.iter_mut().map(|account| AccountInfo::new(
+		&account.key,
+		account.is_signer,
+		account.is_writable,
+		&mut account.lamports,
+		&mut account.data,
+		&account.owner,
+		false,
+		0,
+	)).collect()
+}
+
+fn alloy_data_key(id: u8) -> (Pubkey, u8) {
+	let program_id = alloy_token_program::id();
+	Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref(), &[id]], &program_id)
+}
+
+fn alloy_data_bytes(id: u8) -> Vec<u8> {
+	let mut data = vec![0; MAX_DATA_SIZE];
+	let alloy_data = AlloyData::load_mut(&mut data).unwrap();
+	alloy_data.id = id;
+	alloy_data.bump = alloy_data_key(id).1;
+	data
+}
+
+fn composition_bytes(id: u8, bump: u8) -> Vec<u8> {
+	let mut data = vec![0; COMPOSITION_DATA_SIZE];
+	let composition = AlloyComposition::load_mut(&mut data).unwrap();
+	composition.id = id;
+	composition.bump = bump;
+	data
+}
+
+#[test]
+fn create_alloy_data_account_accounts_round_trip() {
+	let program_id = alloy_token_program::id();
+	let (alloy_data, bump) = alloy_data_key(1);
+	let (mill, heat_number): ([u8; 16], [u8; 16]) = (pad_str("DEW-SIEGEN"), pad_str("H00001"));
+	let (heat_index, heat_index_bump) = Pubkey::find_program_address(
+		&[PREFIX.as_bytes(), program_id.as_ref(), HEAT_SEED.as_bytes(), &mill, &heat_number],
+		&program_id,
+	);
+	let payer = Pubkey::new_unique();
+
+	let instruction = NftInstruction::create_alloy_data_accounts(
+		&program_id,
+		&alloy_data,
+		&heat_index,
+		&payer,
+		1,
+		"20MnCr5".to_string(),
+		"ALLOY".to_string(),
+		"https://example.com/alloys/20MnCr5.json".to_string(),
+		[7; 32],
+		0,
+		1_500_000_000,
+		25_000,
+		ProvenanceArgs {
+			mill: "DEW-SIEGEN".to_string(),
+			heat_number: "H00001".to_string(),
+			production_date: 1_700_000_000,
+			standard: "EN 10084".to_string(),
+		},
+		&Pubkey::new_unique(),
+	);
+
+	let mut accounts = test_accounts(&instruction, |_| (system_program::id(), vec![]));
+	let account_infos = account_infos(&mut accounts);
+
+	let (parsed, parsed_bump, parsed_heat_index_bump) = CreateAlloyDataAccountAccounts::parse(&program_id, &account_infos, 1, &mill, &heat_number).unwrap();
+
+	assert_eq!(parsed.keys(), CreateAlloyDataAccountAccounts {
+		alloy_data,
+		heat_index,
+		payer,
+		system_program: system_program::id(),
+		rent: sysvar::rent::id(),
+	});
+	assert_eq!(parsed_bump, bump);
+	assert_eq!(parsed_heat_index_bump, heat_index_bump);
+}
+
+#[test]
+fn update_alloy_price_accounts_round_trip() {
+	let program_id = alloy_token_program::id();
+	let alloy_data = alloy_data_key(1).0;
+	let owner = Pubkey::new_unique();
+	let owner_nft_token_account = Pubkey::new_unique();
+
+	let instruction = NftInstruction::update_alloy_price(
+		&program_id,
+		&alloy_data,
+		1,
+		2_000_000_000,
+		&owner,
+		&owner_nft_token_account,
+	);
+
+	let mut accounts = test_accounts(&instruction, |key| match *key {
+		key if key == alloy_data => (program_id, alloy_data_bytes(1)),
+		key if key == owner_nft_token_account => (spl_token::id(), vec![]),
+		_ => (system_program::id(), vec![]),
+	});
+	let account_infos = account_infos(&mut accounts);
+
+	let parsed = UpdateAlloyPriceAccounts::parse(&program_id, &account_infos, 1).unwrap();
+
+	assert_eq!(parsed.keys(), UpdateAlloyPriceAccounts {
+		alloy_data,
+		owner,
+		owner_nft_token_account,
+		clock: sysvar::clock::id(),
+	});
+}
+
+#[test]
+fn purchase_alloy_accounts_round_trip() {
+	let program_id = alloy_token_program::id();
+	let alloy_data = alloy_data_key(1).0;
+	let payer = Pubkey::new_unique();
+	let nft_owner = Pubkey::new_unique();
+	let nft_token_account = Pubkey::new_unique();
+
+	let instruction = NftInstruction::purchase_alloy(
+		&program_id,
+		&alloy_data,
+		1,
+		None,
+		None,
+		None,
+		Some(2_000_000_000),
+		false,
+		&payer,
+		&nft_owner,
+		&nft_token_account,
+	);
+
+	let mut accounts = test_accounts(&instruction, |key| match *key {
+		key if key == alloy_data => (program_id, alloy_data_bytes(1)),
+		key if key == nft_token_account => (spl_token::id(), vec![]),
+		_ => (system_program::id(), vec![]),
+	});
+	let account_infos = account_infos(&mut accounts);
+
+	let parsed = PurchaseAlloyAccounts::parse(&program_id, &account_infos, 1).unwrap();
+
+	assert_eq!(parsed.keys(), PurchaseAlloyAccounts {
+		alloy_data,
+		payer,
+		nft_owner,
+		nft_token_account,
+		system_program: system_program::id(),
+		clock: sysvar::clock::id(),
+	});
+}
+
+#[test]
+fn reordered_accounts_are_rejected() {
+	let program_id = alloy_token_program::id();
+	let alloy_data = alloy_data_key(1).0;
+	let payer = Pubkey::new_unique();
+	let nft_owner = Pubkey::new_unique();
+	let nft_token_account = Pubkey::new_unique();
+
+	let mut instruction = NftInstruction::purchase_alloy(
+		&program_id,
+		&alloy_data,
+		1,
+		None,
+		None,
+		None,
+		None,
+		false,
+		&payer,
+		&nft_owner,
+		&nft_token_account,
+	);
+	instruction.accounts.swap(1, 2);
+
+	let mut accounts = test_accounts(&instruction, |key| match *key {
+		key if key == alloy_data => (program_id, alloy_data_bytes(1)),
+		key if key == nft_token_account => (spl_token::id(), vec![]),
+		_ => (system_program::id(), vec![]),
+	});
+	let account_infos = account_infos(&mut accounts);
+
+	assert!(PurchaseAlloyAccounts::parse(&program_id, &account_infos, 1).is_err());
+}
+
+#[test]
+fn update_alloy_composition_accounts_round_trip() {
+	let program_id = alloy_token_program::id();
+	let alloy_data = alloy_data_key(1).0;
+	let (composition, composition_bump) = Pubkey::find_program_address(
+		&[PREFIX.as_bytes(), program_id.as_ref(), &[1], COMPOSITION_SEED.as_bytes()],
+		&program_id,
+	);
+	let authority = Pubkey::new_unique();
+	let authority_nft_token_account = Pubkey::new_unique();
+
+	let instruction = NftInstruction::update_alloy_composition(
+		&program_id,
+		&alloy_data,
+		&composition,
+		1,
+		vec![],
+		&authority,
+		&authority_nft_token_account,
+	);
+
+	let mut accounts = test_accounts(&instruction, |key| match *key {
+		key if key == alloy_data => (program_id, alloy_data_bytes(1)),
+		key if key == composition => (program_id, composition_bytes(1, composition_bump)),
+		key if key == authority_nft_token_account => (spl_token::id(), vec![]),
+		_ => (system_program::id(), vec![]),
+	});
+	let account_infos = account_infos(&mut accounts);
+
+	let parsed = UpdateAlloyCompositionAccounts::parse(&program_id, &account_infos, 1).unwrap();
+
+	assert_eq!(parsed.keys(), UpdateAlloyCompositionAccounts {
+		alloy_data,
+		composition,
+		authority,
+		authority_nft_token_account,
+	});
+}
@@ -0,0 +1,521 @@
+//! Golden-byte tests freezing the wire encoding of every instruction. A failure here means
+//! deployed clients would stop being understood: add a new discriminator or args version
+//! instead of updating the expected bytes.
+
+use alloy_token_program::{
+	attestation::{ PropertyKind, PropertyUnit },
+	error::CustomError,
+	state::ElementComposition,
+	instruction::{
+		AlloyCompositionArgs,
+		BundleArgs,
+		CertifyAlloyArgs,
+		CreateAlloyDataAccountArgs,
+		CreateAttestationArgs,
+		CreateBundleArgs,
+		CreateInstallmentPlanArgs,
+		CreateRentalArgs,
+		CreateVaultArgs,
+		InitializeConfigArgs,
+		InstallmentPlanArgs,
+		NftInstruction,
+		ProposeSwapArgs,
+		ProvenanceArgs,
+		PurchaseAlloyArgs,
+		MergeAlloysArgs,
+		RecordCustodyArgs,
+		SplitAlloyArgs,
+		SwapArgs,
+		RedemptionArgs,
+		RegistryEntryArgs,
+		RentAlloyArgs,
+		RentalArgs,
+		RequestRedemptionArgs,
+		UpdateAlloyPriceArgs,
+		VaultArgs,
+	},
+};
+use solana_sdk::pubkey::Pubkey;
+
+fn assert_encoding(instruction: NftInstruction, expected: &[u8]) {
+	assert_eq!(instruction.pack(), expected);
+	assert_eq!(NftInstruction::unpack(expected).unwrap(), instruction);
+}
+
+/// For retired args versions, whose frozen bytes must now be refused outright.
+fn assert_retired(data: &[u8]) {
+	assert_eq!(NftInstruction::unpack(data), Err(CustomError::UnknownInstruction.into()));
+}
+
+#[test]
+fn discriminators_are_stable() {
+	assert_eq!(NftInstruction::CREATE_ALLOY_DATA_ACCOUNT, 0);
+	assert_eq!(NftInstruction::UPDATE_ALLOY_PRICE, 1);
+	assert_eq!(NftInstruction::PURCHASE_ALLOY, 2);
+	assert_eq!(NftInstruction::CREATE_ALLOY_COMPOSITION, 3);
+	assert_eq!(NftInstruction::UPDATE_ALLOY_COMPOSITION, 4);
+	assert_eq!(NftInstruction::INITIALIZE_CONFIG, 5);
+	assert_eq!(NftInstruction::ADD_REGISTRY_ENTRY, 6);
+	assert_eq!(NftInstruction::REMOVE_REGISTRY_ENTRY, 7);
+	assert_eq!(NftInstruction::CREATE_ATTESTATION, 8);
+	assert_eq!(NftInstruction::CERTIFY_ALLOY, 9);
+	assert_eq!(NftInstruction::REQUEST_REDEMPTION, 10);
+	assert_eq!(NftInstruction::ACKNOWLEDGE_REDEMPTION, 11);
+	assert_eq!(NftInstruction::MARK_SHIPPED, 12);
+	assert_eq!(NftInstruction::CONFIRM_DELIVERY, 13);
+	assert_eq!(NftInstruction::CANCEL_REDEMPTION, 14);
+	assert_eq!(NftInstruction::RECORD_CUSTODY, 15);
+	assert_eq!(NftInstruction::SPLIT_ALLOY, 16);
+	assert_eq!(NftInstruction::MERGE_ALLOYS, 17);
+	assert_eq!(NftInstruction::CREATE_VAULT, 18);
+	assert_eq!(NftInstruction::REDEEM_VAULT, 19);
+	assert_eq!(NftInstruction::BUYOUT_VAULT, 20);
+	assert_eq!(NftInstruction::CLAIM_VAULT_PROCEEDS, 21);
+	assert_eq!(NftInstruction::CREATE_RENTAL, 22);
+	assert_eq!(NftInstruction::RENT_ALLOY, 23);
+	assert_eq!(NftInstruction::END_RENTAL, 24);
+	assert_eq!(NftInstruction::CREATE_INSTALLMENT_PLAN, 25);
+	assert_eq!(NftInstruction::PAY_INSTALLMENT, 26);
+	assert_eq!(NftInstruction::RECLAIM_INSTALLMENT_PLAN, 27);
+	assert_eq!(NftInstruction::CREATE_BUNDLE, 28);
+	assert_eq!(NftInstruction::PURCHASE_BUNDLE, 29);
+	assert_eq!(NftInstruction::CANCEL_BUNDLE, 30);
+	assert_eq!(NftInstruction::PROPOSE_SWAP, 31);
+	assert_eq!(NftInstruction::ACCEPT_SWAP, 32);
+	assert_eq!(NftInstruction::CANCEL_SWAP, 33);
+	assert_eq!(NftInstruction::CREATE_ALLOY_DATA_ACCOUNT_VERSION, 4);
+	assert_eq!(NftInstruction::UPDATE_ALLOY_PRICE_VERSION, 2);
+	assert_eq!(NftInstruction::PURCHASE_ALLOY_VERSION, 3);
+	assert_eq!(NftInstruction::CREATE_ALLOY_COMPOSITION_VERSION, 1);
+	assert_eq!(NftInstruction::UPDATE_ALLOY_COMPOSITION_VERSION, 1);
+	assert_eq!(NftInstruction::INITIALIZE_CONFIG_VERSION, 1);
+	assert_eq!(NftInstruction::ADD_REGISTRY_ENTRY_VERSION, 1);
+	assert_eq!(NftInstruction::REMOVE_REGISTRY_ENTRY_VERSION, 1);
+	assert_eq!(NftInstruction::CREATE_ATTESTATION_VERSION, 1);
+	assert_eq!(NftInstruction::CERTIFY_ALLOY_VERSION, 1);
+	assert_eq!(NftInstruction::REQUEST_REDEMPTION_VERSION, 1);
+	assert_eq!(NftInstruction::ACKNOWLEDGE_REDEMPTION_VERSION, 1);
+	assert_eq!(NftInstruction::MARK_SHIPPED_VERSION, 1);
+	assert_eq!(NftInstruction::CONFIRM_DELIVERY_VERSION, 1);
+	assert_eq!(NftInstruction::CANCEL_REDEMPTION_VERSION, 1);
+	assert_eq!(NftInstruction::RECORD_CUSTODY_VERSION, 1);
+	assert_eq!(NftInstruction::SPLIT_ALLOY_VERSION, 1);
+	assert_eq!(NftInstruction::MERGE_ALLOYS_VERSION, 1);
+	assert_eq!(NftInstruction::CREATE_VAULT_VERSION, 1);
+	assert_eq!(NftInstruction::REDEEM_VAULT_VERSION, 1);
+	assert_eq!(NftInstruction::BUYOUT_VAULT_VERSION, 1);
+	assert_eq!(NftInstruction::CLAIM_VAULT_PROCEEDS_VERSION, 1);
+	assert_eq!(NftInstruction::CREATE_RENTAL_VERSION, 1);
+	assert_eq!(NftInstruction::RENT_ALLOY_VERSION, 1);
+	assert_eq!(NftInstruction::END_RENTAL_VERSION, 1);
+	assert_eq!(NftInstruction::CREATE_INSTALLMENT_PLAN_VERSION, 1);
+	assert_eq!(NftInstruction::PAY_INSTALLMENT_VERSION, 1);
+	assert_eq!(NftInstruction::RECLAIM_INSTALLMENT_PLAN_VERSION, 1);
+	assert_eq!(NftInstruction::CREATE_BUNDLE_VERSION, 1);
+	assert_eq!(NftInstruction::PURCHASE_BUNDLE_VERSION, 1);
+	assert_eq!(NftInstruction::CANCEL_BUNDLE_VERSION, 1);
+	assert_eq!(NftInstruction::PROPOSE_SWAP_VERSION, 1);
+	assert_eq!(NftInstruction::ACCEPT_SWAP_VERSION, 1);
+	assert_eq!(NftInstruction::CANCEL_SWAP_VERSION, 1);
+}
+
+#[test]
+fn create_alloy_data_account_encoding() {
+	let expected = [
+		&[0, 4][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[7, 0, 0, 0], b"20MnCr5",                  // name
+		&[5, 0, 0, 0], b"ALLOY",                    // symbol
+		&[8, 0, 0, 0], b"ipfs://a",                 // uri
+		&[2; 32],                                   // uri_hash
+		&[0, 0, 0, 0, 0, 0, 0, 0],                  // last_price
+		&[0x00, 0x2f, 0x68, 0x59, 0, 0, 0, 0],      // listed_price: 1_500_000_000
+		&[1; 32],                                   // owner_address
+		&[0xa8, 0x61, 0, 0, 0, 0, 0, 0],            // quantity_grams: 25_000
+		&[3, 0, 0, 0], b"DEW",                      // provenance.mill
+		&[4, 0, 0, 0], b"H123",                     // provenance.heat_number
+		&[0x00, 0xf1, 0x53, 0x65, 0, 0, 0, 0],      // provenance.production_date: 1_700_000_000
+		&[8, 0, 0, 0], b"SAE J404",                 // provenance.standard
+	].concat();
+
+	assert_encoding(NftInstruction::CreateAlloyDataAccount(CreateAlloyDataAccountArgs {
+		id: 7,
+		name: "20MnCr5".to_string(),
+		symbol: "ALLOY".to_string(),
+		uri: "ipfs://a".to_string(),
+		uri_hash: [2; 32],
+		last_price: 0,
+		listed_price: 1_500_000_000,
+		owner_address: Pubkey::new_from_array([1; 32]),
+		quantity_grams: 25_000,
+		provenance: ProvenanceArgs {
+			mill: "DEW".to_string(),
+			heat_number: "H123".to_string(),
+			production_date: 1_700_000_000,
+			standard: "SAE J404".to_string(),
+		},
+	}), &expected);
+}
+
+#[test]
+fn create_alloy_data_account_v1_is_retired() {
+	let data = [
+		&[0, 1][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[7, 0, 0, 0], b"20MnCr5",                  // name
+		&[5, 0, 0, 0], b"ALLOY",                    // symbol
+		&[8, 0, 0, 0], b"ipfs://a",                 // uri
+		&[0, 0, 0, 0, 0, 0, 0, 0],                  // last_price
+		&[0x00, 0x2f, 0x68, 0x59, 0, 0, 0, 0],      // listed_price: 1_500_000_000
+		&[1; 32],                                   // owner_address
+	].concat();
+
+	assert_retired(&data);
+}
+
+#[test]
+fn create_alloy_data_account_v2_is_retired() {
+	let data = [
+		&[0, 2][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[7, 0, 0, 0], b"20MnCr5",                  // name
+		&[5, 0, 0, 0], b"ALLOY",                    // symbol
+		&[8, 0, 0, 0], b"ipfs://a",                 // uri
+		&[2; 32],                                   // uri_hash
+		&[0, 0, 0, 0, 0, 0, 0, 0],                  // last_price
+		&[0x00, 0x2f, 0x68, 0x59, 0, 0, 0, 0],      // listed_price: 1_500_000_000
+		&[1; 32],                                   // owner_address
+	].concat();
+
+	assert_retired(&data);
+}
+
+#[test]
+fn create_alloy_data_account_v3_is_retired() {
+	let data = [
+		&[0, 3][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[7, 0, 0, 0], b"20MnCr5",                  // name
+		&[5, 0, 0, 0], b"ALLOY",                    // symbol
+		&[8, 0, 0, 0], b"ipfs://a",                 // uri
+		&[2; 32],                                   // uri_hash
+		&[0, 0, 0, 0, 0, 0, 0, 0],                  // last_price
+		&[0x00, 0x2f, 0x68, 0x59, 0, 0, 0, 0],      // listed_price: 1_500_000_000
+		&[1; 32],                                   // owner_address
+		&[0xa8, 0x61, 0, 0, 0, 0, 0, 0],            // quantity_grams: 25_000
+	].concat();
+
+	assert_retired(&data);
+}
+
+#[test]
+fn update_alloy_price_encoding() {
+	let expected = [
+		&[1, 2][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[0x00, 0x94, 0x35, 0x77, 0, 0, 0, 0],      // price: 2_000_000_000
+	].concat();
+
+	assert_encoding(NftInstruction::UpdateAlloyPrice(UpdateAlloyPriceArgs {
+		id: 7,
+		price: 2_000_000_000,
+	}), &expected);
+}
+
+#[test]
+fn update_alloy_price_v1_is_retired() {
+	let data = [
+		&[1, 1][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[0x00, 0x94, 0x35, 0x77, 0, 0, 0, 0],      // price: 2_000_000_000
+	].concat();
+
+	assert_retired(&data);
+}
+
+#[test]
+fn purchase_alloy_encoding() {
+	let expected = [
+		&[2, 3][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[1], &[8, 0, 0, 0], b"SAE 8620",           // new_name: Some
+		&[0],                                       // new_uri: None
+		&[1], &[2; 32],                             // new_uri_hash: Some
+		&[1], &[0x00, 0x5e, 0xd0, 0xb2, 0, 0, 0, 0], // new_price: Some(3_000_000_000)
+		&[1],                                       // require_certification
+	].concat();
+
+	assert_encoding(NftInstruction::PurchaseAlloy(PurchaseAlloyArgs {
+		id: 7,
+		new_name: Some("SAE 8620".to_string()),
+		new_uri: None,
+		new_uri_hash: Some([2; 32]),
+		new_price: Some(3_000_000_000),
+		require_certification: true,
+	}), &expected);
+}
+
+#[test]
+fn purchase_alloy_without_changes_encoding() {
+	assert_encoding(NftInstruction::PurchaseAlloy(PurchaseAlloyArgs {
+		id: 7,
+		new_name: None,
+		new_uri: None,
+		new_uri_hash: None,
+		new_price: None,
+		require_certification: false,
+	}), &[2, 3, 7, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn purchase_alloy_v1_is_retired() {
+	let data = [
+		&[2, 1][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[1], &[8, 0, 0, 0], b"SAE 8620",           // new_name: Some
+		&[0],                                       // new_uri: None
+		&[1], &[0x00, 0x5e, 0xd0, 0xb2, 0, 0, 0, 0], // new_price: Some(3_000_000_000)
+	].concat();
+
+	assert_retired(&data);
+	assert_retired(&[2, 1, 7, 0, 0, 0]);
+}
+
+#[test]
+fn purchase_alloy_v2_is_retired() {
+	let data = [
+		&[2, 2][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[1], &[8, 0, 0, 0], b"SAE 8620",           // new_name: Some
+		&[0],                                       // new_uri: None
+		&[1], &[2; 32],                             // new_uri_hash: Some
+		&[1], &[0x00, 0x5e, 0xd0, 0xb2, 0, 0, 0, 0], // new_price: Some(3_000_000_000)
+	].concat();
+
+	assert_retired(&data);
+}
+
+#[test]
+fn alloy_composition_encoding() {
+	let elements = [
+		&[2, 0, 0, 0][..],                          // elements: 2
+		b"Cr", &[100, 0], &[130, 0], &[115, 0],     // symbol, min_bps, max_bps, actual_bps
+		b"C\0", &[17, 0], &[22, 0], &[20, 0],
+	].concat();
+	let args = AlloyCompositionArgs {
+		id: 7,
+		elements: vec![ElementComposition::new("Cr", 100, 130, 115), ElementComposition::new("C", 17, 22, 20)],
+	};
+
+	assert_encoding(NftInstruction::CreateAlloyComposition(args.clone()), &[&[3, 1, 7][..], &elements].concat());
+	assert_encoding(NftInstruction::UpdateAlloyComposition(args), &[&[4, 1, 7][..], &elements].concat());
+}
+
+#[test]
+fn config_and_registry_encoding() {
+	assert_encoding(NftInstruction::InitializeConfig(InitializeConfigArgs {
+		authority: Pubkey::new_from_array([1; 32]),
+	}), &[&[5, 1][..], &[1; 32]].concat());
+
+	let args = RegistryEntryArgs {
+		role: 1,
+		member: Pubkey::new_from_array([1; 32]),
+	};
+
+	assert_encoding(NftInstruction::AddRegistryEntry(args.clone()), &[&[6, 1, 1][..], &[1; 32]].concat());
+	assert_encoding(NftInstruction::RemoveRegistryEntry(args), &[&[7, 1, 1][..], &[1; 32]].concat());
+}
+
+#[test]
+fn create_attestation_encoding() {
+	let expected = [
+		&[8, 1][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[3, 0, 0, 0, 0, 0, 0, 0],                  // nonce
+		&[2],                                       // kind: Hardness
+		&[2],                                       // unit: RockwellC
+		&[0x84, 0xe4, 0, 0, 0, 0, 0, 0],            // value_milli: 58_500
+		&[2; 32],                                   // document_hash
+	].concat();
+
+	assert_encoding(NftInstruction::CreateAttestation(CreateAttestationArgs {
+		id: 7,
+		nonce: 3,
+		kind: PropertyKind::Hardness,
+		unit: PropertyUnit::RockwellC,
+		value_milli: 58_500,
+		document_hash: [2; 32],
+	}), &expected);
+}
+
+#[test]
+fn certify_alloy_encoding() {
+	let expected = [
+		&[9, 1][..],                                // discriminator, args version
+		&[7],                                       // id
+		&[2; 32],                                   // certificate_hash
+		&[0x00, 0xb9, 0x55, 0x69, 0, 0, 0, 0],      // expires_at: 1_767_225_600
+	].concat();
+
+	assert_encoding(NftInstruction::CertifyAlloy(CertifyAlloyArgs {
+		id: 7,
+		certificate_hash: [2; 32],
+		expires_at: 1_767_225_600,
+	}), &expected);
+}
+
+#[test]
+fn redemption_encoding() {
+	assert_encoding(NftInstruction::RequestRedemption(RequestRedemptionArgs {
+		id: 7,
+		shipping_hash: [4; 32],
+	}), &[&[10, 1, 7][..], &[4; 32]].concat());
+
+	assert_encoding(NftInstruction::AcknowledgeRedemption(RedemptionArgs { id: 7 }), &[11, 1, 7]);
+	assert_encoding(NftInstruction::MarkShipped(RedemptionArgs { id: 7 }), &[12, 1, 7]);
+	assert_encoding(NftInstruction::ConfirmDelivery(RedemptionArgs { id: 7 }), &[13, 1, 7]);
+	assert_encoding(NftInstruction::CancelRedemption(RedemptionArgs { id: 7 }), &[14, 1, 7]);
+}
+
+#[test]
+fn record_custody_encoding() {
+	let expected = [
+		&[15, 1][..],                               // discriminator, args version
+		&[7],                                       // id
+		&[5, 0, 0, 0], b"DEHAM",                    // location
+		&[2; 32],                                   // document_hash
+	].concat();
+
+	assert_encoding(NftInstruction::RecordCustody(RecordCustodyArgs {
+		id: 7,
+		location: "DEHAM".to_string(),
+		document_hash: [2; 32],
+	}), &expected);
+}
+
+#[test]
+fn split_and_merge_encoding() {
+	let expected = [
+		&[16, 1][..],                               // discriminator, args version
+		&[7],                                       // id
+		&[8],                                       // child_id
+		&[0x88, 0x13, 0, 0, 0, 0, 0, 0],            // quantity_grams: 5_000
+		&[0x00, 0xe1, 0xf5, 0x05, 0, 0, 0, 0],      // listed_price: 100_000_000
+	].concat();
+
+	assert_encoding(NftInstruction::SplitAlloy(SplitAlloyArgs {
+		id: 7,
+		child_id: 8,
+		quantity_grams: 5_000,
+		listed_price: 100_000_000,
+	}), &expected);
+
+	assert_encoding(NftInstruction::MergeAlloys(MergeAlloysArgs { id: 7, source_id: 8 }), &[17, 1, 7, 8]);
+}
+
+#[test]
+fn vault_encoding() {
+	let expected = [
+		&[18, 1][..],                               // discriminator, args version
+		&[7],                                       // id
+		&[0x40, 0x42, 0x0f, 0, 0, 0, 0, 0],         // share_supply: 1_000_000
+		&[0x00, 0x2f, 0x68, 0x59, 0, 0, 0, 0],      // reserve_price: 1_500_000_000
+	].concat();
+
+	assert_encoding(NftInstruction::CreateVault(CreateVaultArgs {
+		id: 7,
+		share_supply: 1_000_000,
+		reserve_price: 1_500_000_000,
+	}), &expected);
+
+	assert_encoding(NftInstruction::RedeemVault(VaultArgs { id: 7 }), &[19, 1, 7]);
+	assert_encoding(NftInstruction::BuyoutVault(VaultArgs { id: 7 }), &[20, 1, 7]);
+	assert_encoding(NftInstruction::ClaimVaultProceeds(VaultArgs { id: 7 }), &[21, 1, 7]);
+}
+
+#[test]
+fn rental_encoding() {
+	let expected = [
+		&[22, 1][..],                               // discriminator, args version
+		&[7],                                       // id
+		&[0x00, 0xe1, 0xf5, 0x05, 0, 0, 0, 0],      // price_per_day: 100_000_000
+		&[0x5a, 0x00],                              // max_days: 90
+	].concat();
+
+	assert_encoding(NftInstruction::CreateRental(CreateRentalArgs {
+		id: 7,
+		price_per_day: 100_000_000,
+		max_days: 90,
+	}), &expected);
+
+	assert_encoding(NftInstruction::RentAlloy(RentAlloyArgs { id: 7, days: 30 }), &[23, 1, 7, 30, 0]);
+	assert_encoding(NftInstruction::EndRental(RentalArgs { id: 7 }), &[24, 1, 7]);
+}
+
+#[test]
+fn installment_plan_encoding() {
+	let expected = [
+		&[25, 1][..],                               // discriminator, args version
+		&[7],                                       // id
+		&[9; 32],                                   // buyer
+		&[0x00, 0x5e, 0xd0, 0xb2, 0, 0, 0, 0],      // total_price: 3_000_000_000
+		&[3],                                       // payments
+		&[0x00, 0xf1, 0x53, 0x65, 0, 0, 0, 0],      // first_due_at: 1_700_000_000
+		&[0x00, 0x8d, 0x27, 0, 0, 0, 0, 0],         // interval: 2_592_000
+		&[0xe8, 0x03],                              // forfeit_bps: 1_000
+	].concat();
+
+	assert_encoding(NftInstruction::CreateInstallmentPlan(CreateInstallmentPlanArgs {
+		id: 7,
+		buyer: Pubkey::new_from_array([9; 32]),
+		total_price: 3_000_000_000,
+		payments: 3,
+		first_due_at: 1_700_000_000,
+		interval: 2_592_000,
+		forfeit_bps: 1_000,
+	}), &expected);
+
+	assert_encoding(NftInstruction::PayInstallment(InstallmentPlanArgs { id: 7 }), &[26, 1, 7]);
+	assert_encoding(NftInstruction::ReclaimInstallmentPlan(InstallmentPlanArgs { id: 7 }), &[27, 1, 7]);
+}
+
+#[test]
+fn bundle_encoding() {
+	let expected = [
+		&[28, 1][..],                               // discriminator, args version
+		&[42, 0, 0, 0, 0, 0, 0, 0],                 // nonce
+		&[3, 0, 0, 0, 7, 8, 9],                     // ids
+		&[0x00, 0x5e, 0xd0, 0xb2, 0, 0, 0, 0],      // price: 3_000_000_000
+	].concat();
+
+	assert_encoding(NftInstruction::CreateBundle(CreateBundleArgs {
+		nonce: 42,
+		ids: vec![7, 8, 9],
+		price: 3_000_000_000,
+	}), &expected);
+
+	assert_encoding(NftInstruction::PurchaseBundle(BundleArgs { nonce: 42 }), &[29, 1, 42, 0, 0, 0, 0, 0, 0, 0]);
+	assert_encoding(NftInstruction::CancelBundle(BundleArgs { nonce: 42 }), &[30, 1, 42, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn swap_encoding() {
+	let expected = [
+		&[31, 1][..],                               // discriminator, args version
+		&[7],                                       // id
+		&[8],                                       // wanted_id
+		&[0x00, 0x65, 0xcd, 0x1d, 0, 0, 0, 0],      // top_up: 500_000_000
+	].concat();
+
+	assert_encoding(NftInstruction::ProposeSwap(ProposeSwapArgs { id: 7, wanted_id: 8, top_up: 500_000_000 }), &expected);
+	assert_encoding(NftInstruction::AcceptSwap(SwapArgs { id: 7 }), &[32, 1, 7]);
+	assert_encoding(NftInstruction::CancelSwap(SwapArgs { id: 7 }), &[33, 1, 7]);
+}
+
+/// Args versions that were never issued must not be guessed at.
+#[test]
+fn unknown_args_versions_are_rejected() {
+	for data in [[0, 0, 7], [0, 5, 7], [2, 0, 7], [2, 4, 7], [1, 0, 7], [1, 3, 7]] {
+		assert_eq!(NftInstruction::unpack(&data), Err(CustomError::UnknownInstruction.into()));
+	}
+}
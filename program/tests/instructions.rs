@@ -0,0 +1,802 @@
+//! Offline integration tests running every instruction against the program in a
+//! `solana-program-test` bank.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::{ CreateAlloyDataAccountArgs, NftInstruction, ProvenanceArgs, PurchaseAlloyArgs },
+	state::{ HeatIndex, MAX_DATA_SIZE, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH },
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	account::Account,
+	instruction::{ Instruction, InstructionError },
+	program_pack::Pack,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	sysvar,
+};
+
+fn update_price_instruction(alloy: &AlloyFixture, new_price: u64) -> Instruction {
+	NftInstruction::update_alloy_price(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		alloy.id,
+		new_price,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	)
+}
+
+fn purchase_instruction(alloy: &AlloyFixture, buyer: &Pubkey, new_name: Option<&str>, new_uri: Option<&str>, new_price: Option<u64>) -> Instruction {
+	NftInstruction::purchase_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		alloy.id,
+		new_name.map(str::to_string),
+		new_uri.map(str::to_string),
+		new_uri.map(uri_hash),
+		new_price,
+		false,
+		buyer,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	)
+}
+
+async fn lamports(banks_client: &mut BanksClient, key: &Pubkey) -> u64 {
+	banks_client.get_balance(*key).await.unwrap()
+}
+
+/// Account data for a foreign or pre-seeded alloy data account.
+fn alloy_data_account(id: u8, owner: Pubkey) -> Account {
+	let mut data = vec![0; MAX_DATA_SIZE];
+	let alloy_data = alloy_token_program::state::AlloyData::load_mut(&mut data).unwrap();
+	alloy_data.id = id;
+	alloy_data.bump = alloy_data_key(id).1;
+
+	Account {
+		lamports: 1_000_000_000,
+		data,
+		owner,
+		executable: false,
+		rent_epoch: 0,
+	}
+}
+
+// CreateAlloyDataAccount
+
+#[tokio::test]
+async fn create_alloy_data_account() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let account = get_account(&mut banks_client, &alloy.alloy_data).await;
+	assert_eq!(account.owner, alloy_token_program::id());
+	assert_eq!(account.data.len(), MAX_DATA_SIZE);
+
+	let alloy_data = get_alloy_data(&mut banks_client, 1).await;
+	assert_eq!(alloy_data.id, 1);
+	assert_eq!(alloy_data.bump, alloy_data_key(1).1);
+	assert_eq!(alloy_data.name(), "20MnCr5");
+	assert_eq!(alloy_data.symbol(), "ALLOY");
+	assert_eq!(alloy_data.uri(), "https://example.com/alloys/20MnCr5.json");
+	assert_eq!(alloy_data.last_price, 0);
+	assert_eq!(alloy_data.listed_price, LISTED_PRICE);
+	assert_eq!(alloy_data.owner_address, alloy.mint);
+}
+
+/// Clients of retired args versions send the account list from before `heat_index`; they
+/// must be told the instruction is unknown rather than have their accounts misread.
+#[tokio::test]
+async fn create_rejects_retired_args_versions() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let uri = "https://example.com/alloys/20MnCr5.json";
+	let mint = Pubkey::new_unique();
+
+	let mut instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", "ALLOY", uri, &mint);
+	assert_eq!(instruction.accounts.remove(1).pubkey, heat_index_key(MILL, &provenance(1).heat_number).0);
+
+	let v1 = [&[0, 1][..], &borsh::to_vec(&(1u8, "20MnCr5", "ALLOY", uri, 0u64, LISTED_PRICE, mint)).unwrap()].concat();
+	let fields = (1u8, "20MnCr5", "ALLOY", uri, uri_hash(uri), 0u64, LISTED_PRICE, mint);
+	let v2 = [&[0, 2][..], &borsh::to_vec(&fields).unwrap()].concat();
+	let v3 = [&[0, 3][..], &borsh::to_vec(&(fields, QUANTITY_GRAMS)).unwrap()].concat();
+
+	for data in [v1, v2, v3] {
+		let instruction = Instruction { data, ..instruction.clone() };
+		let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+		assert_custom_error(result, CustomError::UnknownInstruction);
+	}
+
+	assert!(banks_client.get_account(alloy_data_key(1).0).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn create_accepts_max_length_fields() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let name = "N".repeat(MAX_NAME_LENGTH);
+	let symbol = "S".repeat(MAX_SYMBOL_LENGTH);
+	let uri = format!("https://{}", "u".repeat(MAX_URI_LENGTH - "https://".len()));
+
+	let instruction = create_alloy_instruction(&payer.pubkey(), 1, &name, &symbol, &uri, &Pubkey::new_unique());
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let alloy_data = get_alloy_data(&mut banks_client, 1).await;
+	assert_eq!(alloy_data.name(), name);
+	assert_eq!(alloy_data.symbol(), symbol);
+	assert_eq!(alloy_data.uri(), uri);
+}
+
+#[tokio::test]
+async fn create_rejects_long_name() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let name = "N".repeat(MAX_NAME_LENGTH + 1);
+
+	let instruction = create_alloy_instruction(&payer.pubkey(), 1, &name, "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::NameTooLong);
+}
+
+#[tokio::test]
+async fn create_rejects_long_symbol() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let symbol = "S".repeat(MAX_SYMBOL_LENGTH + 1);
+
+	let instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", &symbol, "ipfs://20MnCr5", &Pubkey::new_unique());
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::SymbolTooLong);
+}
+
+#[tokio::test]
+async fn create_rejects_long_uri() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let uri = "U".repeat(MAX_URI_LENGTH + 1);
+
+	let instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", "ALLOY", &uri, &Pubkey::new_unique());
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::UriTooLong);
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_metadata() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let cases = [
+		("", "ALLOY", "ipfs://20MnCr5", CustomError::InvalidName),
+		("20MnCr5\n", "ALLOY", "ipfs://20MnCr5", CustomError::InvalidName),
+		("20MnCr5", "", "ipfs://20MnCr5", CustomError::InvalidSymbol),
+		("20MnCr5", "alloy", "ipfs://20MnCr5", CustomError::InvalidSymbol),
+		("20MnCr5", "AL-LOY", "ipfs://20MnCr5", CustomError::InvalidSymbol),
+		("20MnCr5", "ALLOY", "ipfs://", CustomError::InvalidUri),
+		("20MnCr5", "ALLOY", "https://example.com/20 MnCr5.json", CustomError::InvalidUri),
+		("20MnCr5", "ALLOY", "http://example.com/20MnCr5.json", CustomError::UnsupportedUriScheme),
+		("20MnCr5", "ALLOY", "javascript:alert(1)", CustomError::UnsupportedUriScheme),
+	];
+
+	for (name, symbol, uri, expected) in cases {
+		let instruction = create_alloy_instruction(&payer.pubkey(), 1, name, symbol, uri, &Pubkey::new_unique());
+		let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+		assert_custom_error(result, expected);
+	}
+}
+
+#[tokio::test]
+async fn create_accepts_every_allowed_uri_scheme() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	for (id, uri) in [(1, "https://example.com/20MnCr5.json"), (2, "ipfs://bafy20mncr5"), (3, "ar://20MnCr5")] {
+		let instruction = create_alloy_instruction(&payer.pubkey(), id, "20MnCr5", "ALLOY", uri, &Pubkey::new_unique());
+		process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+		assert_eq!(get_alloy_data(&mut banks_client, id).await.uri(), uri);
+	}
+}
+
+#[tokio::test]
+async fn create_rejects_missing_uri_hash() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let mut instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	instruction.data = NftInstruction::CreateAlloyDataAccount(CreateAlloyDataAccountArgs {
+		id: 1,
+		name: "20MnCr5".to_string(),
+		symbol: "ALLOY".to_string(),
+		uri: "ipfs://20MnCr5".to_string(),
+		uri_hash: [0; 32],
+		last_price: 0,
+		listed_price: LISTED_PRICE,
+		owner_address: Pubkey::new_unique(),
+		quantity_grams: QUANTITY_GRAMS,
+		provenance: provenance(1),
+	}).pack();
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::MissingUriHash);
+}
+
+#[tokio::test]
+async fn create_rejects_pda_of_another_id() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let mut instruction = create_alloy_instruction(&payer.pubkey(), 2, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	instruction.accounts[0].pubkey = alloy_data_key(1).0;
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::InvalidAlloyDataKey);
+}
+
+#[tokio::test]
+async fn create_rejects_existing_alloy() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = create_alloy_instruction(&payer.pubkey(), 1, "SAE 8620", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::AlreadyInitialized);
+}
+
+#[tokio::test]
+async fn create_records_provenance_and_indexes_the_heat() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let alloy_data = get_alloy_data(&mut banks_client, 1).await;
+	assert_eq!(alloy_data.mill(), MILL);
+	assert_eq!(alloy_data.heat_number(), "H00001");
+	assert_eq!(alloy_data.standard(), "EN 10084");
+	assert_eq!(alloy_data.production_date, provenance(1).production_date);
+
+	let heat_index = HeatIndex::unpack(&get_account(&mut banks_client, &heat_index_key(MILL, "H00001").0).await.data).unwrap();
+	assert_eq!(heat_index.alloy, alloy.alloy_data);
+	assert_eq!(heat_index.id, 1);
+}
+
+#[tokio::test]
+async fn create_rejects_heat_tokenized_before() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = create_alloy_instruction_with_provenance(&payer.pubkey(), 2, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique(), provenance(1));
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+	assert_custom_error(result, CustomError::HeatAlreadyTokenized);
+
+	// The same heat number from another mill is a different heat.
+	let other_mill = ProvenanceArgs { mill: "SAARSTAHL".to_string(), ..provenance(1) };
+	let instruction = create_alloy_instruction_with_provenance(&payer.pubkey(), 2, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique(), other_mill);
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_provenance() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let cases = [
+		ProvenanceArgs { mill: String::new(), ..provenance(1) },
+		ProvenanceArgs { mill: "dew-siegen".to_string(), ..provenance(1) },
+		ProvenanceArgs { heat_number: "H 00001".to_string(), ..provenance(1) },
+		ProvenanceArgs { heat_number: "H00000000000000001".to_string(), ..provenance(1) },
+		ProvenanceArgs { standard: String::new(), ..provenance(1) },
+		ProvenanceArgs { standard: " SAE J404".to_string(), ..provenance(1) },
+		ProvenanceArgs { production_date: 0, ..provenance(1) },
+	];
+
+	for provenance in cases {
+		let instruction = create_alloy_instruction_with_provenance(&payer.pubkey(), 1, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique(), provenance);
+		let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+		assert_custom_error(result, CustomError::InvalidProvenance);
+	}
+}
+
+#[tokio::test]
+async fn create_rejects_readonly_alloy_data_account() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let mut instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	instruction.accounts[0].is_writable = false;
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::AccountNotWritable);
+}
+
+#[tokio::test]
+async fn create_rejects_wrong_system_program() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let mut instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	instruction.accounts[3].pubkey = spl_token::id();
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_instruction_error(result, InstructionError::IncorrectProgramId);
+}
+
+#[tokio::test]
+async fn create_rejects_wrong_rent_sysvar() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let mut instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	instruction.accounts[4].pubkey = sysvar::clock::id();
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_instruction_error(result, InstructionError::InvalidArgument);
+}
+
+#[tokio::test]
+async fn create_requires_payer_signature() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let funder = Keypair::new();
+
+	let mut instruction = create_alloy_instruction(&funder.pubkey(), 1, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+	instruction.accounts[2].is_signer = false;
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+}
+
+// UpdateAlloyPrice
+
+#[tokio::test]
+async fn update_alloy_price() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = update_price_instruction(&alloy, 2_000_000_000);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let alloy_data = get_alloy_data(&mut banks_client, 1).await;
+	assert_eq!(alloy_data.listed_price, 2_000_000_000);
+	assert_eq!(alloy_data.last_price, 0);
+}
+
+/// Version 1 clients send the account list without `clock`; they must be told the
+/// instruction is unknown rather than fail on the missing account.
+#[tokio::test]
+async fn update_price_rejects_retired_args_version() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = update_price_instruction(&alloy, 2_000_000_000);
+	assert_eq!(instruction.accounts.pop().map(|meta| meta.pubkey), Some(sysvar::clock::id()));
+	instruction.data[1] = 1;
+
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::UnknownInstruction);
+	assert_eq!(get_alloy_data(&mut banks_client, 1).await.listed_price, LISTED_PRICE);
+}
+
+#[tokio::test]
+async fn update_price_rejects_mismatched_id() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = NftInstruction::update_alloy_price(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		5,
+		2_000_000_000,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::InvalidAlloyDataKey);
+}
+
+#[tokio::test]
+async fn update_price_rejects_alloy_data_owned_by_another_program() {
+	let mut program_test = program_test();
+	program_test.add_account(alloy_data_key(9).0, alloy_data_account(9, spl_token::id()));
+	let (mut banks_client, payer, _) = program_test.start().await;
+
+	let owner = Keypair::new();
+	let (_, token_account) = create_nft(&mut banks_client, &payer, &owner.pubkey()).await;
+
+	let instruction = NftInstruction::update_alloy_price(
+		&alloy_token_program::id(),
+		&alloy_data_key(9).0,
+		9,
+		2_000_000_000,
+		&owner.pubkey(),
+		&token_account,
+	);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&owner]).await;
+
+	assert_custom_error(result, CustomError::IncorrectOwner);
+}
+
+#[tokio::test]
+async fn update_price_rejects_token_account_not_owned_by_spl_token() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	alloy.token_account = payer.pubkey();
+
+	let instruction = update_price_instruction(&alloy, 2_000_000_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::IncorrectOwner);
+}
+
+#[tokio::test]
+async fn update_price_rejects_uninitialized_token_account() {
+	let uninitialized_token_account = Pubkey::new_unique();
+	let mut program_test = program_test();
+	program_test.add_account(uninitialized_token_account, Account {
+		lamports: 1_000_000_000,
+		data: vec![0; spl_token::state::Account::LEN],
+		owner: spl_token::id(),
+		executable: false,
+		rent_epoch: 0,
+	});
+	let (mut banks_client, payer, _) = program_test.start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	alloy.token_account = uninitialized_token_account;
+
+	let instruction = update_price_instruction(&alloy, 2_000_000_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::Uninitialized);
+}
+
+#[tokio::test]
+async fn update_price_rejects_token_account_of_another_mint() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let (_, other_token_account) = create_nft(&mut banks_client, &payer, &alloy.owner.pubkey()).await;
+	alloy.token_account = other_token_account;
+
+	let instruction = update_price_instruction(&alloy, 2_000_000_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::OwnerMismatch);
+}
+
+#[tokio::test]
+async fn update_price_rejects_signer_not_holding_the_token() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	alloy.owner = Keypair::new();
+
+	let instruction = update_price_instruction(&alloy, 2_000_000_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::InvalidOwner);
+}
+
+#[tokio::test]
+async fn update_price_rejects_empty_token_account() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let owner = Keypair::new();
+	let (mint, token_account) = create_nft_with_amount(&mut banks_client, &payer, &owner.pubkey(), 0).await;
+
+	let instruction = create_alloy_instruction(&payer.pubkey(), 1, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &mint);
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let alloy = AlloyFixture { id: 1, alloy_data: alloy_data_key(1).0, mint, token_account, owner };
+	let instruction = update_price_instruction(&alloy, 2_000_000_000);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::NotEnoughTokens);
+}
+
+#[tokio::test]
+async fn update_price_requires_owner_signature() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = update_price_instruction(&alloy, 2_000_000_000);
+	instruction.accounts[1].is_signer = false;
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+}
+
+#[tokio::test]
+async fn update_price_rejects_readonly_alloy_data_account() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = update_price_instruction(&alloy, 2_000_000_000);
+	instruction.accounts[0].is_writable = false;
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::AccountNotWritable);
+}
+
+// PurchaseAlloy
+
+#[tokio::test]
+async fn purchase_alloy() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let seller_lamports = lamports(&mut banks_client, &alloy.owner.pubkey()).await;
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), Some("SAE 8620"), Some("ipfs://sae8620"), Some(3_000_000_000));
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let alloy_data = get_alloy_data(&mut banks_client, 1).await;
+	assert_eq!(alloy_data.name(), "SAE 8620");
+	assert_eq!(alloy_data.uri(), "ipfs://sae8620");
+	assert_eq!(alloy_data.uri_hash, uri_hash("ipfs://sae8620"));
+	assert_eq!(alloy_data.symbol(), "ALLOY");
+	assert_eq!(alloy_data.last_price, LISTED_PRICE);
+	assert_eq!(alloy_data.listed_price, 3_000_000_000);
+
+	assert_eq!(lamports(&mut banks_client, &alloy.owner.pubkey()).await, seller_lamports + LISTED_PRICE);
+}
+
+/// Clients of retired args versions send the account list without `clock`; they must be
+/// told the instruction is unknown rather than fail on the missing account.
+#[tokio::test]
+async fn purchase_rejects_retired_args_versions() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let seller_lamports = lamports(&mut banks_client, &alloy.owner.pubkey()).await;
+
+	let mut instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	assert_eq!(instruction.accounts.pop().map(|meta| meta.pubkey), Some(sysvar::clock::id()));
+
+	for data in [vec![2, 1, 1, 0, 0, 0], vec![2, 2, 1, 0, 0, 0, 0]] {
+		let instruction = Instruction { data, ..instruction.clone() };
+		let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+		assert_custom_error(result, CustomError::UnknownInstruction);
+	}
+
+	assert_eq!(get_alloy_data(&mut banks_client, 1).await.last_price, 0);
+	assert_eq!(lamports(&mut banks_client, &alloy.owner.pubkey()).await, seller_lamports);
+}
+
+#[tokio::test]
+async fn purchase_keeps_fields_that_are_not_replaced() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let alloy_data = get_alloy_data(&mut banks_client, 1).await;
+	assert_eq!(alloy_data.name(), "20MnCr5");
+	assert_eq!(alloy_data.uri(), "https://example.com/alloys/20MnCr5.json");
+	assert_eq!(alloy_data.uri_hash, uri_hash("https://example.com/alloys/20MnCr5.json"));
+	assert_eq!(alloy_data.last_price, LISTED_PRICE);
+	assert_eq!(alloy_data.listed_price, LISTED_PRICE);
+}
+
+#[tokio::test]
+async fn purchase_can_replace_uri_hash_alone() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	instruction.data = NftInstruction::PurchaseAlloy(PurchaseAlloyArgs {
+		id: alloy.id,
+		new_name: None,
+		new_uri: None,
+		new_uri_hash: Some([7; 32]),
+		new_price: None,
+		require_certification: false,
+	}).pack();
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let alloy_data = get_alloy_data(&mut banks_client, 1).await;
+	assert_eq!(alloy_data.uri(), "https://example.com/alloys/20MnCr5.json");
+	assert_eq!(alloy_data.uri_hash, [7; 32]);
+}
+
+#[tokio::test]
+async fn purchase_rejects_missing_uri_hash() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	for new_uri_hash in [None, Some([0; 32])] {
+		let mut instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+		instruction.data = NftInstruction::PurchaseAlloy(PurchaseAlloyArgs {
+			id: alloy.id,
+			new_name: None,
+			new_uri: Some("ipfs://sae8620".to_string()),
+			new_uri_hash,
+			new_price: None,
+			require_certification: false,
+		}).pack();
+		let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+		assert_custom_error(result, CustomError::MissingUriHash);
+	}
+}
+
+#[tokio::test]
+async fn purchase_rejects_long_name() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let name = "N".repeat(MAX_NAME_LENGTH + 1);
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), Some(&name), None, None);
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::NameTooLong);
+}
+
+#[tokio::test]
+async fn purchase_rejects_long_uri() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let uri = "U".repeat(MAX_URI_LENGTH + 1);
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), None, Some(&uri), None);
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::UriTooLong);
+}
+
+#[tokio::test]
+async fn purchase_rejects_invalid_metadata() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let cases = [
+		(Some(""), None, CustomError::InvalidName),
+		(Some("SAE\u{7}8620"), None, CustomError::InvalidName),
+		(None, Some("ipfs://sae 8620"), CustomError::InvalidUri),
+		(None, Some("ftp://example.com/sae8620.json"), CustomError::UnsupportedUriScheme),
+	];
+
+	for (new_name, new_uri, expected) in cases {
+		let instruction = purchase_instruction(&alloy, &payer.pubkey(), new_name, new_uri, None);
+		let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+		assert_custom_error(result, expected);
+	}
+}
+
+#[tokio::test]
+async fn purchase_rejects_mismatched_id() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	alloy.id = 5;
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::InvalidAlloyDataKey);
+}
+
+#[tokio::test]
+async fn purchase_rejects_alloy_data_owned_by_another_program() {
+	let mut program_test = program_test();
+	program_test.add_account(alloy_data_key(9).0, alloy_data_account(9, spl_token::id()));
+	let (mut banks_client, payer, _) = program_test.start().await;
+
+	let owner = Keypair::new();
+	let (mint, token_account) = create_nft(&mut banks_client, &payer, &owner.pubkey()).await;
+	let alloy = AlloyFixture { id: 9, alloy_data: alloy_data_key(9).0, mint, token_account, owner };
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::IncorrectOwner);
+}
+
+#[tokio::test]
+async fn purchase_rejects_token_account_of_another_mint() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let (_, other_token_account) = create_nft(&mut banks_client, &payer, &alloy.owner.pubkey()).await;
+	alloy.token_account = other_token_account;
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::OwnerMismatch);
+}
+
+#[tokio::test]
+async fn purchase_rejects_seller_not_holding_the_token() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	alloy.owner = Keypair::new();
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::InvalidOwner);
+}
+
+#[tokio::test]
+async fn purchase_rejects_token_account_not_owned_by_spl_token() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let mut alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	alloy.token_account = alloy.owner.pubkey();
+
+	let instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::IncorrectOwner);
+}
+
+#[tokio::test]
+async fn purchase_requires_payer_signature() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let buyer = Keypair::new();
+
+	let mut instruction = purchase_instruction(&alloy, &buyer.pubkey(), None, None, None);
+	instruction.accounts[1].is_signer = false;
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+}
+
+#[tokio::test]
+async fn purchase_rejects_wrong_system_program() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	instruction.accounts[4].pubkey = spl_token::id();
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_instruction_error(result, InstructionError::IncorrectProgramId);
+}
+
+#[tokio::test]
+async fn purchase_requires_writable_seller() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = purchase_instruction(&alloy, &payer.pubkey(), None, None, None);
+	instruction.accounts[2].is_writable = false;
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_custom_error(result, CustomError::AccountNotWritable);
+}
+
+// Instruction decoding
+
+#[tokio::test]
+async fn rejects_unknown_instruction() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	for data in [vec![], vec![0xff, NftInstruction::UPDATE_ALLOY_PRICE_VERSION, 0x01], vec![NftInstruction::UPDATE_ALLOY_PRICE, 0xff]] {
+		let instruction = Instruction {
+			program_id: alloy_token_program::id(),
+			accounts: vec![],
+			data,
+		};
+		let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+		assert_custom_error(result, CustomError::UnknownInstruction);
+	}
+}
+
+#[tokio::test]
+async fn rejects_malformed_instruction_args() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+
+	let instruction = Instruction {
+		program_id: alloy_token_program::id(),
+		accounts: vec![],
+		data: vec![NftInstruction::UPDATE_ALLOY_PRICE, NftInstruction::UPDATE_ALLOY_PRICE_VERSION, 0x01, 0x02],
+	};
+	let result = process(&mut banks_client, &payer, &[instruction], &[]).await;
+
+	assert_instruction_error(result, InstructionError::InvalidInstructionData);
+}
+
+#[tokio::test]
+async fn rejects_missing_accounts() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let mut instruction = update_price_instruction(&alloy, 2_000_000_000);
+	instruction.accounts.truncate(2);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_instruction_error(result, InstructionError::NotEnoughAccountKeys);
+	assert_eq!(get_alloy_data(&mut banks_client, 1).await.listed_price, LISTED_PRICE);
+}
@@ -0,0 +1,252 @@
+//! Integration tests for splitting and merging quantity-bearing lots.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::{ NftInstruction, ProvenanceArgs },
+	state::ElementComposition,
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	instruction::Instruction,
+	program_pack::Pack,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	transaction::TransactionError,
+};
+use spl_token::state::{ Account as TokenAccount, Mint };
+
+/// Fresh keypairs for the child lot's mint and its holder's token account, which split
+/// creates.
+struct ChildNft {
+	mint: Keypair,
+	token_account: Keypair,
+}
+
+impl ChildNft {
+	fn new() -> Self {
+		Self { mint: Keypair::new(), token_account: Keypair::new() }
+	}
+}
+
+fn split_instruction(alloy: &AlloyFixture, child_id: u8, child: &ChildNft, payer: &Pubkey, quantity_grams: u64) -> Instruction {
+	NftInstruction::split_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&alloy_data_key(child_id).0,
+		&composition_key(alloy.id).0,
+		&composition_key(child_id).0,
+		&child.mint.pubkey(),
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		&child.token_account.pubkey(),
+		payer,
+		alloy.id,
+		child_id,
+		quantity_grams,
+		LISTED_PRICE / 5,
+	)
+}
+
+async fn split(banks_client: &mut BanksClient, payer: &Keypair, alloy: &AlloyFixture, child_id: u8, child: &ChildNft, quantity_grams: u64) -> Result<(), TransactionError> {
+	let instruction = split_instruction(alloy, child_id, child, &payer.pubkey(), quantity_grams);
+	process(banks_client, payer, &[instruction], &[&alloy.owner, &child.mint, &child.token_account]).await
+}
+
+fn composition_instruction(alloy: &AlloyFixture, id: u8, token_account: &Pubkey, payer: &Pubkey, elements: Vec<ElementComposition>) -> Instruction {
+	NftInstruction::create_alloy_composition(
+		&alloy_token_program::id(),
+		&alloy_data_key(id).0,
+		&composition_key(id).0,
+		id,
+		elements,
+		&alloy.owner.pubkey(),
+		token_account,
+		payer,
+	)
+}
+
+fn merge_instruction(alloy: &AlloyFixture, source_id: u8, source_mint: &Pubkey, source_token_account: &Pubkey) -> Instruction {
+	NftInstruction::merge_alloys(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&alloy_data_key(source_id).0,
+		&composition_key(alloy.id).0,
+		&composition_key(source_id).0,
+		source_mint,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		source_token_account,
+		alloy.id,
+		source_id,
+	)
+}
+
+#[tokio::test]
+async fn split_moves_grams_and_composition_into_child() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let elements = vec![ElementComposition::new("Cr", 80, 130, 95), ElementComposition::new("C", 17, 22, 20)];
+
+	let instruction = NftInstruction::create_alloy_composition(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&composition_key(1).0,
+		1,
+		elements.clone(),
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		&payer.pubkey(),
+	);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let child_nft = ChildNft::new();
+	split(&mut banks_client, &payer, &alloy, 2, &child_nft, 5_000).await.unwrap();
+
+	let parent = get_alloy_data(&mut banks_client, 1).await;
+	let child = get_alloy_data(&mut banks_client, 2).await;
+	let mint = Mint::unpack(&get_account(&mut banks_client, &child_nft.mint.pubkey()).await.data).unwrap();
+	let token_account = TokenAccount::unpack(&get_account(&mut banks_client, &child_nft.token_account.pubkey()).await.data).unwrap();
+
+	assert_eq!(parent.quantity_grams, QUANTITY_GRAMS - 5_000);
+	assert_eq!(child.quantity_grams, 5_000);
+	assert_eq!(child.parent, alloy.alloy_data);
+	assert_eq!(child.owner_address, child_nft.mint.pubkey());
+	assert_eq!(child.name(), parent.name());
+	assert_eq!(child.uri_hash, parent.uri_hash);
+	assert_eq!(child.listed_price, LISTED_PRICE / 5);
+	assert_eq!(get_composition(&mut banks_client, 2).await.elements(), elements.as_slice());
+	assert_eq!((mint.supply, mint.decimals), (1, 0));
+	assert!(mint.mint_authority.is_none() && mint.freeze_authority.is_none());
+	assert_eq!((token_account.owner, token_account.amount), (alloy.owner.pubkey(), 1));
+}
+
+#[tokio::test]
+async fn split_rejects_invalid_quantities_and_existing_mints() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+
+	for quantity_grams in [0, QUANTITY_GRAMS] {
+		let result = split(&mut banks_client, &payer, &alloy, 2, &ChildNft::new(), quantity_grams).await;
+		assert_custom_error(result, CustomError::InvalidQuantity);
+	}
+
+	// Alloy 3 is backed by a mint whose keypair the holder kept; pointing the child at it
+	// would let one NFT back two alloys.
+	let registered = ChildNft::new();
+	create_nft_with_mint(&mut banks_client, &payer, &alloy.owner.pubkey(), &registered.mint, 1).await;
+	let instruction = create_alloy_instruction(&payer.pubkey(), 3, "20MnCr5", "ALLOY", "https://example.com/alloys/20MnCr5.json", &registered.mint.pubkey());
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let result = split(&mut banks_client, &payer, &alloy, 2, &registered, 5_000).await;
+	assert_custom_error(result, CustomError::AlreadyInitialized);
+	assert_eq!(get_alloy_data(&mut banks_client, 1).await.quantity_grams, QUANTITY_GRAMS);
+}
+
+#[tokio::test]
+async fn merge_returns_grams_and_burns_the_source() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let child_nft = ChildNft::new();
+	split(&mut banks_client, &payer, &alloy, 2, &child_nft, 5_000).await.unwrap();
+	let (child_mint, child_token_account) = (child_nft.mint.pubkey(), child_nft.token_account.pubkey());
+
+	let instruction = merge_instruction(&alloy, 1, &alloy.mint, &alloy.token_account);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::MergeIntoSelf);
+
+	let instruction = merge_instruction(&alloy, 2, &child_mint, &child_token_account);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let parent = get_alloy_data(&mut banks_client, 1).await;
+	let child = get_alloy_data(&mut banks_client, 2).await;
+	let mint = Mint::unpack(&get_account(&mut banks_client, &child_mint).await.data).unwrap();
+
+	assert_eq!(parent.quantity_grams, QUANTITY_GRAMS);
+	assert_eq!(child.quantity_grams, 0);
+	assert_eq!(child.merged_into, alloy.alloy_data);
+	assert_eq!(mint.supply, 0);
+
+	// Merging twice, padded with a no-op transfer so the transaction is not a duplicate.
+	let instructions = [
+		merge_instruction(&alloy, 2, &child_mint, &child_token_account),
+		solana_sdk::system_instruction::transfer(&payer.pubkey(), &alloy.owner.pubkey(), 1),
+	];
+	let result = process(&mut banks_client, &payer, &instructions, &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::AlloyMerged);
+}
+
+#[tokio::test]
+async fn merge_requires_same_grade() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let (mint, token_account) = create_nft(&mut banks_client, &payer, &alloy.owner.pubkey()).await;
+
+	let instruction = create_alloy_instruction(&payer.pubkey(), 2, "SAE 8620", "ALLOY", "https://example.com/alloys/8620.json", &mint);
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let instruction = merge_instruction(&alloy, 2, &mint, &token_account);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+
+	assert_custom_error(result, CustomError::GradeMismatch);
+}
+
+#[tokio::test]
+async fn merge_requires_same_standard_and_composition() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let owner = alloy.owner.pubkey();
+
+	// Lot 2 carries the same label but is specified under another standard.
+	let (mint, token_account) = create_nft(&mut banks_client, &payer, &owner).await;
+	let provenance = ProvenanceArgs { standard: "SAE J404".to_string(), ..provenance(2) };
+	let instruction = create_alloy_instruction_with_provenance(&payer.pubkey(), 2, "20MnCr5", "ALLOY", "https://example.com/alloys/20MnCr5.json", &mint, provenance);
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let result = process(&mut banks_client, &payer, &[merge_instruction(&alloy, 2, &mint, &token_account)], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::GradeMismatch);
+
+	// Lot 3 matches on label and standard, so only the compositions can tell them apart.
+	let (mint, token_account) = create_nft(&mut banks_client, &payer, &owner).await;
+	let instruction = create_alloy_instruction(&payer.pubkey(), 3, "20MnCr5", "ALLOY", "https://example.com/alloys/20MnCr5.json", &mint);
+	process(&mut banks_client, &payer, &[instruction], &[]).await.unwrap();
+
+	let elements = vec![ElementComposition::new("Cr", 100, 130, 110), ElementComposition::new("C", 17, 22, 20)];
+	process(&mut banks_client, &payer, &[composition_instruction(&alloy, 1, &alloy.token_account, &payer.pubkey(), elements)], &[&alloy.owner]).await.unwrap();
+
+	let result = process(&mut banks_client, &payer, &[merge_instruction(&alloy, 3, &mint, &token_account)], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::GradeMismatch);
+
+	let elements = vec![ElementComposition::new("C", 17, 22, 19), ElementComposition::new("Cr", 80, 130, 110)];
+	process(&mut banks_client, &payer, &[composition_instruction(&alloy, 3, &token_account, &payer.pubkey(), elements)], &[&alloy.owner]).await.unwrap();
+
+	// Padded with a no-op transfer so the transaction is not a duplicate of the one above.
+	let instructions = [
+		merge_instruction(&alloy, 3, &mint, &token_account),
+		solana_sdk::system_instruction::transfer(&payer.pubkey(), &owner, 1_000_000),
+	];
+	let result = process(&mut banks_client, &payer, &instructions, &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::GradeMismatch);
+
+	// The same ranges listed in another order, with different measured shares, do match.
+	let elements = vec![ElementComposition::new("C", 17, 22, 19), ElementComposition::new("Cr", 100, 130, 125)];
+	let instruction = NftInstruction::update_alloy_composition(
+		&alloy_token_program::id(),
+		&alloy_data_key(3).0,
+		&composition_key(3).0,
+		3,
+		elements,
+		&owner,
+		&token_account,
+	);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let instructions = [
+		merge_instruction(&alloy, 3, &mint, &token_account),
+		solana_sdk::system_instruction::transfer(&payer.pubkey(), &owner, 2_000_000),
+	];
+	process(&mut banks_client, &payer, &instructions, &[&alloy.owner]).await.unwrap();
+	assert_eq!(get_alloy_data(&mut banks_client, 1).await.quantity_grams, 2 * QUANTITY_GRAMS);
+}
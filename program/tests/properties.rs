@@ -0,0 +1,461 @@
+//! Property-based tests for the `AlloyData` layout and `NftInstruction` decoding.
+
+use alloy_token_program::{
+	error::CustomError,
+	composition::validate_composition,
+	attestation::{ PropertyKind, PropertyUnit },
+	instruction::{
+		AlloyCompositionArgs,
+		BundleArgs,
+		CertifyAlloyArgs,
+		CreateAlloyDataAccountArgs,
+		CreateAttestationArgs,
+		CreateBundleArgs,
+		CreateInstallmentPlanArgs,
+		CreateRentalArgs,
+		CreateVaultArgs,
+		InitializeConfigArgs,
+		InstallmentPlanArgs,
+		NftInstruction,
+		ProposeSwapArgs,
+		ProvenanceArgs,
+		PurchaseAlloyArgs,
+		MergeAlloysArgs,
+		RecordCustodyArgs,
+		SplitAlloyArgs,
+		SwapArgs,
+		RedemptionArgs,
+		RegistryEntryArgs,
+		RentAlloyArgs,
+		RentalArgs,
+		RequestRedemptionArgs,
+		UpdateAlloyPriceArgs,
+		VaultArgs,
+	},
+	metadata::{ validate_name, validate_symbol, validate_uri },
+	processor::Processor,
+	state::{
+		AlloyData,
+		ElementComposition,
+		MAX_BUNDLE_SIZE,
+		MAX_COMPOSITION_ELEMENTS,
+		MAX_DATA_SIZE,
+		MAX_LOCATION_CODE_LENGTH,
+		MAX_NAME_LENGTH,
+		MAX_PROVENANCE_CODE_LENGTH,
+		MAX_SYMBOL_LENGTH,
+		MAX_URI_LENGTH,
+		TOTAL_BPS,
+	},
+};
+use num_traits::FromPrimitive;
+use proptest::{ collection::vec, option, prelude::* };
+use solana_sdk::{
+	account_info::AccountInfo,
+	packet::PACKET_DATA_SIZE,
+	program_error::ProgramError,
+	pubkey::Pubkey,
+	signature::Keypair,
+	signer::Signer,
+	system_program,
+	transaction::Transaction,
+};
+
+/// Strings of at most `max_bytes` UTF-8 bytes, biased towards the limit and multibyte
+/// characters.
+fn text(max_bytes: usize) -> impl Strategy<Value = String> {
+	prop_oneof![
+		vec(any::<char>(), 0..=max_bytes).prop_map(move |chars| truncate_to_bytes(chars, max_bytes)),
+		"[a-z0-9 ]{0,8}",
+		Just("a".repeat(max_bytes)),
+		Just("é".repeat(max_bytes / 2)),
+		Just("€".repeat(max_bytes / 3)),
+		Just("🔩".repeat(max_bytes / 4)),
+	]
+}
+
+/// Strings one or more bytes over `max_bytes`, possibly ending in a multibyte character that
+/// straddles the limit.
+fn oversized_text(max_bytes: usize) -> impl Strategy<Value = String> {
+	(0..=max_bytes, vec(any::<char>(), 1..8)).prop_map(move |(prefix_len, chars)| {
+		let mut text = "a".repeat(prefix_len);
+		text.extend(chars);
+
+		while text.len() <= max_bytes {
+			text.push('a');
+		}
+
+		text
+	})
+}
+
+fn truncate_to_bytes(chars: Vec<char>, max_bytes: usize) -> String {
+	let mut text = String::new();
+
+	for c in chars {
+		if text.len() + c.len_utf8() > max_bytes {
+			break;
+		}
+		text.push(c);
+	}
+
+	text
+}
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+	any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn element_composition() -> impl Strategy<Value = ElementComposition> {
+	(any::<[u8; 2]>(), any::<u16>(), any::<u16>(), any::<u16>())
+		.prop_map(|(symbol, min_bps, max_bps, actual_bps)| ElementComposition { symbol, min_bps, max_bps, actual_bps })
+}
+
+fn property_kind() -> impl Strategy<Value = PropertyKind> {
+	(0..=u8::MAX).prop_filter_map("unknown kind", PropertyKind::from_u8)
+}
+
+fn property_unit() -> impl Strategy<Value = PropertyUnit> {
+	(0..=u8::MAX).prop_filter_map("unknown unit", PropertyUnit::from_u8)
+}
+
+fn provenance() -> impl Strategy<Value = ProvenanceArgs> {
+	(text(MAX_PROVENANCE_CODE_LENGTH), text(MAX_PROVENANCE_CODE_LENGTH), any::<i64>(), text(MAX_PROVENANCE_CODE_LENGTH))
+		.prop_map(|(mill, heat_number, production_date, standard)| ProvenanceArgs { mill, heat_number, production_date, standard })
+}
+
+fn nft_instruction() -> impl Strategy<Value = NftInstruction> {
+	prop_oneof![
+		(any::<u8>(), text(MAX_NAME_LENGTH), text(MAX_SYMBOL_LENGTH), text(MAX_URI_LENGTH), any::<[u8; 32]>(), any::<u64>(), any::<u64>(), pubkey(), any::<u64>(), provenance())
+			.prop_map(|(id, name, symbol, uri, uri_hash, last_price, listed_price, owner_address, quantity_grams, provenance)| {
+				NftInstruction::CreateAlloyDataAccount(CreateAlloyDataAccountArgs {
+					id,
+					name,
+					symbol,
+					uri,
+					uri_hash,
+					last_price,
+					listed_price,
+					owner_address,
+					quantity_grams,
+					provenance,
+				})
+			}),
+		(any::<u8>(), any::<u64>())
+			.prop_map(|(id, price)| NftInstruction::UpdateAlloyPrice(UpdateAlloyPriceArgs { id, price })),
+		(any::<u8>(), option::of(text(MAX_NAME_LENGTH)), option::of(text(MAX_URI_LENGTH)), option::of(any::<[u8; 32]>()), option::of(any::<u64>()), any::<bool>())
+			.prop_map(|(id, new_name, new_uri, new_uri_hash, new_price, require_certification)| {
+				NftInstruction::PurchaseAlloy(PurchaseAlloyArgs { id, new_name, new_uri, new_uri_hash, new_price, require_certification })
+			}),
+		(any::<u8>(), vec(element_composition(), 0..=MAX_COMPOSITION_ELEMENTS))
+			.prop_map(|(id, elements)| NftInstruction::CreateAlloyComposition(AlloyCompositionArgs { id, elements })),
+		(any::<u8>(), vec(element_composition(), 0..=MAX_COMPOSITION_ELEMENTS))
+			.prop_map(|(id, elements)| NftInstruction::UpdateAlloyComposition(AlloyCompositionArgs { id, elements })),
+		pubkey().prop_map(|authority| NftInstruction::InitializeConfig(InitializeConfigArgs { authority })),
+		(any::<u8>(), pubkey()).prop_map(|(role, member)| NftInstruction::AddRegistryEntry(RegistryEntryArgs { role, member })),
+		(any::<u8>(), pubkey()).prop_map(|(role, member)| NftInstruction::RemoveRegistryEntry(RegistryEntryArgs { role, member })),
+		(any::<u8>(), any::<u64>(), property_kind(), property_unit(), any::<i64>(), any::<[u8; 32]>())
+			.prop_map(|(id, nonce, kind, unit, value_milli, document_hash)| {
+				NftInstruction::CreateAttestation(CreateAttestationArgs { id, nonce, kind, unit, value_milli, document_hash })
+			}),
+		(any::<u8>(), any::<[u8; 32]>(), any::<i64>())
+			.prop_map(|(id, certificate_hash, expires_at)| NftInstruction::CertifyAlloy(CertifyAlloyArgs { id, certificate_hash, expires_at })),
+		(any::<u8>(), any::<[u8; 32]>())
+			.prop_map(|(id, shipping_hash)| NftInstruction::RequestRedemption(RequestRedemptionArgs { id, shipping_hash })),
+		any::<u8>().prop_map(|id| NftInstruction::AcknowledgeRedemption(RedemptionArgs { id })),
+		any::<u8>().prop_map(|id| NftInstruction::MarkShipped(RedemptionArgs { id })),
+		any::<u8>().prop_map(|id| NftInstruction::ConfirmDelivery(RedemptionArgs { id })),
+		any::<u8>().prop_map(|id| NftInstruction::CancelRedemption(RedemptionArgs { id })),
+		(any::<u8>(), text(MAX_LOCATION_CODE_LENGTH), any::<[u8; 32]>())
+			.prop_map(|(id, location, document_hash)| NftInstruction::RecordCustody(RecordCustodyArgs { id, location, document_hash })),
+		(any::<u8>(), any::<u8>(), any::<u64>(), any::<u64>())
+			.prop_map(|(id, child_id, quantity_grams, listed_price)| NftInstruction::SplitAlloy(SplitAlloyArgs { id, child_id, quantity_grams, listed_price })),
+		(any::<u8>(), any::<u8>()).prop_map(|(id, source_id)| NftInstruction::MergeAlloys(MergeAlloysArgs { id, source_id })),
+		(any::<u8>(), any::<u64>(), any::<u64>())
+			.prop_map(|(id, share_supply, reserve_price)| NftInstruction::CreateVault(CreateVaultArgs { id, share_supply, reserve_price })),
+		any::<u8>().prop_map(|id| NftInstruction::RedeemVault(VaultArgs { id })),
+		any::<u8>().prop_map(|id| NftInstruction::BuyoutVault(VaultArgs { id })),
+		any::<u8>().prop_map(|id| NftInstruction::ClaimVaultProceeds(VaultArgs { id })),
+		(any::<u8>(), any::<u64>(), any::<u16>())
+			.prop_map(|(id, price_per_day, max_days)| NftInstruction::CreateRental(CreateRentalArgs { id, price_per_day, max_days })),
+		(any::<u8>(), any::<u16>()).prop_map(|(id, days)| NftInstruction::RentAlloy(RentAlloyArgs { id, days })),
+		any::<u8>().prop_map(|id| NftInstruction::EndRental(RentalArgs { id })),
+		(any::<u8>(), pubkey(), any::<u64>(), any::<u8>(), any::<i64>(), any::<i64>(), any::<u16>())
+			.prop_map(|(id, buyer, total_price, payments, first_due_at, interval, forfeit_bps)| NftInstruction::CreateInstallmentPlan(CreateInstallmentPlanArgs {
+				id,
+				buyer,
+				total_price,
+				payments,
+				first_due_at,
+				interval,
+				forfeit_bps,
+			})),
+		any::<u8>().prop_map(|id| NftInstruction::PayInstallment(InstallmentPlanArgs { id })),
+		any::<u8>().prop_map(|id| NftInstruction::ReclaimInstallmentPlan(InstallmentPlanArgs { id })),
+		(any::<u64>(), vec(any::<u8>(), 0..=MAX_BUNDLE_SIZE), any::<u64>())
+			.prop_map(|(nonce, ids, price)| NftInstruction::CreateBundle(CreateBundleArgs { nonce, ids, price })),
+		any::<u64>().prop_map(|nonce| NftInstruction::PurchaseBundle(BundleArgs { nonce })),
+		any::<u64>().prop_map(|nonce| NftInstruction::CancelBundle(BundleArgs { nonce })),
+		(any::<u8>(), any::<u8>(), any::<u64>())
+			.prop_map(|(id, wanted_id, top_up)| NftInstruction::ProposeSwap(ProposeSwapArgs { id, wanted_id, top_up })),
+		any::<u8>().prop_map(|id| NftInstruction::AcceptSwap(SwapArgs { id })),
+		any::<u8>().prop_map(|id| NftInstruction::CancelSwap(SwapArgs { id })),
+	]
+}
+
+/// Alloy data fields as they would be written by the processor.
+#[derive(Debug, Clone)]
+struct AlloyFields {
+	last_price: u64,
+	listed_price: u64,
+	owner_address: Pubkey,
+	id: u8,
+	bump: u8,
+	name: String,
+	symbol: String,
+	uri: String,
+	uri_hash: [u8; 32],
+}
+
+fn alloy_fields() -> impl Strategy<Value = AlloyFields> {
+	(any::<u64>(), any::<u64>(), pubkey(), any::<u8>(), any::<u8>(), text(MAX_NAME_LENGTH), text(MAX_SYMBOL_LENGTH), text(MAX_URI_LENGTH), any::<[u8; 32]>())
+		.prop_map(|(last_price, listed_price, owner_address, id, bump, name, symbol, uri, uri_hash)| AlloyFields {
+			last_price,
+			listed_price,
+			owner_address,
+			id,
+			bump,
+			name,
+			symbol,
+			uri,
+			uri_hash,
+		})
+}
+
+fn write_alloy_data(fields: &AlloyFields) -> Vec<u8> {
+	let mut data = vec![0; MAX_DATA_SIZE];
+	let alloy_data = AlloyData::load_mut(&mut data).unwrap();
+
+	alloy_data.last_price = fields.last_price;
+	alloy_data.listed_price = fields.listed_price;
+	alloy_data.owner_address = fields.owner_address;
+	alloy_data.id = fields.id;
+	alloy_data.bump = fields.bump;
+	alloy_data.set_name(&fields.name).unwrap();
+	alloy_data.set_symbol(&fields.symbol).unwrap();
+	alloy_data.set_uri(&fields.uri).unwrap();
+	alloy_data.uri_hash = fields.uri_hash;
+
+	data
+}
+
+proptest! {
+	#[test]
+	fn alloy_data_round_trips_through_account_data(fields in alloy_fields()) {
+		let data = write_alloy_data(&fields);
+
+		for alloy_data in [*AlloyData::load(&data).unwrap(), AlloyData::unpack(&data).unwrap()] {
+			prop_assert_eq!(alloy_data.last_price, fields.last_price);
+			prop_assert_eq!(alloy_data.listed_price, fields.listed_price);
+			prop_assert_eq!(alloy_data.owner_address, fields.owner_address);
+			prop_assert_eq!(alloy_data.id, fields.id);
+			prop_assert_eq!(alloy_data.bump, fields.bump);
+			prop_assert_eq!(alloy_data.name(), fields.name.as_str());
+			prop_assert_eq!(alloy_data.symbol(), fields.symbol.as_str());
+			prop_assert_eq!(alloy_data.uri(), fields.uri.as_str());
+			prop_assert_eq!(alloy_data.uri_hash, fields.uri_hash);
+		}
+	}
+
+	#[test]
+	fn overwriting_strings_clears_previous_bytes(fields in alloy_fields(), name in text(MAX_NAME_LENGTH), uri in text(MAX_URI_LENGTH)) {
+		let mut data = write_alloy_data(&fields);
+		let alloy_data = AlloyData::load_mut(&mut data).unwrap();
+
+		alloy_data.set_name(&name).unwrap();
+		alloy_data.set_uri(&uri).unwrap();
+
+		prop_assert_eq!(alloy_data.name(), name.as_str());
+		prop_assert_eq!(alloy_data.uri(), uri.as_str());
+		prop_assert!(alloy_data.name[name.len()..].iter().all(|byte| *byte == 0));
+		prop_assert!(alloy_data.uri[uri.len()..].iter().all(|byte| *byte == 0));
+	}
+
+	#[test]
+	fn oversized_strings_are_rejected(
+		name in oversized_text(MAX_NAME_LENGTH),
+		symbol in oversized_text(MAX_SYMBOL_LENGTH),
+		uri in oversized_text(MAX_URI_LENGTH),
+	) {
+		let mut data = vec![0; MAX_DATA_SIZE];
+		let alloy_data = AlloyData::load_mut(&mut data).unwrap();
+
+		prop_assert_eq!(alloy_data.set_name(&name), Err(CustomError::NameTooLong.into()));
+		prop_assert_eq!(alloy_data.set_symbol(&symbol), Err(CustomError::SymbolTooLong.into()));
+		prop_assert_eq!(alloy_data.set_uri(&uri), Err(CustomError::UriTooLong.into()));
+		prop_assert!(data.iter().all(|byte| *byte == 0));
+	}
+
+	#[test]
+	fn validated_metadata_always_fits_the_account(
+		name in prop_oneof![text(MAX_NAME_LENGTH), oversized_text(MAX_NAME_LENGTH)],
+		symbol in prop_oneof![text(MAX_SYMBOL_LENGTH), oversized_text(MAX_SYMBOL_LENGTH), "[A-Z0-9]{1,12}"],
+		uri in prop_oneof![text(MAX_URI_LENGTH), oversized_text(MAX_URI_LENGTH), "(https|ipfs|ar)://[a-z0-9/.]{0,200}"],
+	) {
+		let mut data = vec![0; MAX_DATA_SIZE];
+		let alloy_data = AlloyData::load_mut(&mut data).unwrap();
+
+		if validate_name(&name).is_ok() {
+			prop_assert!(alloy_data.set_name(&name).is_ok());
+			prop_assert!(!name.is_empty() && !name.chars().any(char::is_control));
+		}
+
+		if validate_symbol(&symbol).is_ok() {
+			prop_assert!(alloy_data.set_symbol(&symbol).is_ok());
+		}
+
+		if validate_uri(&uri).is_ok() {
+			prop_assert!(alloy_data.set_uri(&uri).is_ok());
+			prop_assert!(!uri.contains(char::is_whitespace));
+		}
+	}
+
+	#[test]
+	fn arbitrary_account_data_never_panics(data in vec(any::<u8>(), 0..MAX_DATA_SIZE * 2)) {
+		match AlloyData::unpack(&data) {
+			Ok(alloy_data) => {
+				prop_assert_eq!(data.len(), MAX_DATA_SIZE);
+				let _ = (alloy_data.name(), alloy_data.symbol(), alloy_data.uri());
+			},
+			Err(err) => prop_assert_eq!(err, CustomError::DataTypeMismatch.into()),
+		}
+	}
+
+	#[test]
+	fn nft_instruction_round_trips_through_pack(instruction in nft_instruction()) {
+		let data = instruction.pack();
+
+		prop_assert_eq!(data[0], instruction.discriminator());
+		prop_assert_eq!(NftInstruction::unpack(&data).unwrap(), instruction);
+	}
+
+	#[test]
+	fn truncated_or_extended_instruction_data_is_rejected(instruction in nft_instruction(), extra in vec(any::<u8>(), 1..16)) {
+		let data = instruction.pack();
+
+		for len in 0..data.len() {
+			prop_assert!(NftInstruction::unpack(&data[..len]).is_err());
+		}
+
+		let mut extended = data.clone();
+		extended.extend(extra);
+		prop_assert_eq!(NftInstruction::unpack(&extended), Err(ProgramError::InvalidInstructionData));
+	}
+
+	#[test]
+	fn validated_compositions_stay_within_total(
+		elements in vec(
+			(prop_oneof!["[A-Z][a-z]?", "[A-Za-z]{0,3}"], 0..=TOTAL_BPS, 0..=TOTAL_BPS, 0..=TOTAL_BPS + 1)
+				.prop_map(|(symbol, min_bps, max_bps, actual_bps)| ElementComposition::new(&symbol, min_bps, max_bps, actual_bps)),
+			0..=MAX_COMPOSITION_ELEMENTS + 1,
+		),
+	) {
+		if validate_composition(&elements).is_ok() {
+			prop_assert!(elements.len() <= MAX_COMPOSITION_ELEMENTS);
+			prop_assert!(elements.iter().map(|element| element.actual_bps as u32).sum::<u32>() <= TOTAL_BPS as u32);
+			prop_assert!(elements.iter().all(|element| element.min_bps <= element.actual_bps && element.actual_bps <= element.max_bps));
+		}
+	}
+
+	#[test]
+	fn purchase_transaction_fits_in_a_packet(
+		id in any::<u8>(),
+		new_name in text(MAX_NAME_LENGTH),
+		new_uri in text(MAX_URI_LENGTH),
+		new_price in any::<u64>(),
+	) {
+		let payer = Keypair::new();
+		let instruction = NftInstruction::purchase_alloy(
+			&alloy_token_program::id(),
+			&Pubkey::new_unique(),
+			id,
+			Some(new_name),
+			Some(new_uri),
+			Some([7; 32]),
+			Some(new_price),
+			true,
+			&payer.pubkey(),
+			&Pubkey::new_unique(),
+			&Pubkey::new_unique(),
+		);
+		let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+
+		prop_assert!(bincode::serialized_size(&transaction).unwrap() as usize <= PACKET_DATA_SIZE);
+	}
+
+	#[test]
+	fn create_transaction_fits_in_a_packet(
+		id in any::<u8>(),
+		name in text(MAX_NAME_LENGTH),
+		symbol in text(MAX_SYMBOL_LENGTH),
+		uri in text(MAX_URI_LENGTH),
+		provenance in provenance(),
+	) {
+		let payer = Keypair::new();
+		let instruction = NftInstruction::create_alloy_data_accounts(
+			&alloy_token_program::id(),
+			&Pubkey::new_unique(),
+			&Pubkey::new_unique(),
+			&payer.pubkey(),
+			id,
+			name,
+			symbol,
+			uri,
+			[7; 32],
+			u64::MAX,
+			u64::MAX,
+			u64::MAX,
+			provenance,
+			&Pubkey::new_unique(),
+		);
+		let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+
+		prop_assert!(bincode::serialized_size(&transaction).unwrap() as usize <= PACKET_DATA_SIZE);
+	}
+
+	#[test]
+	fn processor_rejects_random_instruction_data(instruction_data in vec(any::<u8>(), 0..512)) {
+		let result = Processor::process_instruction(&alloy_token_program::id(), &[], &instruction_data);
+
+		prop_assert!(result.is_err());
+	}
+
+	#[test]
+	fn processor_rejects_instructions_over_random_accounts(
+		instruction in nft_instruction(),
+		accounts in vec((vec(any::<u8>(), 0..MAX_DATA_SIZE + 8), any::<bool>(), any::<bool>(), 0..3u8), 0..6),
+	) {
+		let program_id = alloy_token_program::id();
+		let owners = [program_id, spl_token::id(), system_program::id()];
+
+		let mut accounts: Vec<(Pubkey, Pubkey, u64, Vec<u8>, bool, bool)> = accounts.into_iter()
+			.map(|(data, is_signer, is_writable, owner)| {
+				(Pubkey::new_unique(), owners[owner as usize], 0, data, is_signer, is_writable)
+			})
+			.collect();
+		let account_infos: Vec<AccountInfo> = accounts.iter_mut()
+			.map(|(key, owner, lamports, data, is_signer, is_writable)| {
+				AccountInfo::new(key, *is_signer, *is_writable, lamports, data, owner, false, 0)
+			})
+			.collect();
+
+		// Random keys are never the alloy data PDA, so every instruction must fail cleanly.
+		let result: Result<(), ProgramError> = Processor::process_instruction(
+			&program_id,
+			&account_infos,
+			&instruction.pack(),
+		);
+
+		prop_assert!(result.is_err());
+	}
+}
@@ -0,0 +1,224 @@
+//! Integration tests for the physical redemption workflow.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	redemption::RedemptionStatus,
+	state::{ Redemption, ROLE_CUSTODIAN },
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	instruction::Instruction,
+	program_pack::Pack,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+};
+
+const SHIPPING_HASH: [u8; 32] = [4; 32];
+
+fn request_instruction(alloy: &AlloyFixture, payer: &Pubkey, shipping_hash: [u8; 32]) -> Instruction {
+	NftInstruction::request_redemption(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&redemption_key(alloy.id).0,
+		&escrow_key(alloy.id).0,
+		&alloy.mint,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		payer,
+		alloy.id,
+		shipping_hash,
+	)
+}
+
+fn acknowledge_instruction(alloy: &AlloyFixture, custodian: &Pubkey) -> Instruction {
+	NftInstruction::acknowledge_redemption(
+		&alloy_token_program::id(),
+		&redemption_key(alloy.id).0,
+		custodian,
+		&registry_entry_key(ROLE_CUSTODIAN, custodian).0,
+		alloy.id,
+	)
+}
+
+fn mark_shipped_instruction(alloy: &AlloyFixture, custodian: &Pubkey) -> Instruction {
+	NftInstruction::mark_shipped(
+		&alloy_token_program::id(),
+		&redemption_key(alloy.id).0,
+		custodian,
+		&registry_entry_key(ROLE_CUSTODIAN, custodian).0,
+		alloy.id,
+	)
+}
+
+fn confirm_delivery_instruction(alloy: &AlloyFixture, custodian: &Pubkey) -> Instruction {
+	NftInstruction::confirm_delivery(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&redemption_key(alloy.id).0,
+		&escrow_key(alloy.id).0,
+		&alloy.mint,
+		&alloy.owner.pubkey(),
+		custodian,
+		&registry_entry_key(ROLE_CUSTODIAN, custodian).0,
+		alloy.id,
+	)
+}
+
+fn cancel_instruction(alloy: &AlloyFixture) -> Instruction {
+	NftInstruction::cancel_redemption(
+		&alloy_token_program::id(),
+		&redemption_key(alloy.id).0,
+		&escrow_key(alloy.id).0,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		alloy.id,
+	)
+}
+
+async fn get_redemption(banks_client: &mut BanksClient, id: u8) -> Redemption {
+	Redemption::unpack(&get_account(banks_client, &redemption_key(id).0).await.data).unwrap()
+}
+
+/// An alloy and a registered custodian.
+async fn setup() -> (BanksClient, Keypair, AlloyFixture, Keypair) {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let authority = initialize_config(&mut banks_client, &payer).await;
+	let custodian = Keypair::new();
+	register(&mut banks_client, &payer, &authority, ROLE_CUSTODIAN, &custodian.pubkey()).await;
+
+	(banks_client, payer, alloy, custodian)
+}
+
+#[tokio::test]
+async fn redemption_burns_the_nft_on_delivery() {
+	let (mut banks_client, payer, alloy, custodian) = setup().await;
+
+	let instruction = request_instruction(&alloy, &payer.pubkey(), SHIPPING_HASH);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let redemption = get_redemption(&mut banks_client, 1).await;
+	assert_eq!(redemption.holder, alloy.owner.pubkey());
+	assert_eq!(redemption.shipping_hash, SHIPPING_HASH);
+	assert_eq!(redemption.status(), Some(RedemptionStatus::Requested));
+	assert_eq!(token_amount(&mut banks_client, &alloy.token_account).await, 0);
+	assert_eq!(token_amount(&mut banks_client, &escrow_key(1).0).await, 1);
+
+	let instructions = [
+		acknowledge_instruction(&alloy, &custodian.pubkey()),
+		mark_shipped_instruction(&alloy, &custodian.pubkey()),
+		confirm_delivery_instruction(&alloy, &custodian.pubkey()),
+	];
+	for instruction in instructions {
+		process(&mut banks_client, &payer, &[instruction], &[&custodian]).await.unwrap();
+	}
+
+	let redemption = get_redemption(&mut banks_client, 1).await;
+	assert_eq!(redemption.status(), Some(RedemptionStatus::Delivered));
+	assert!(get_alloy_data(&mut banks_client, 1).await.is_redeemed());
+	assert!(banks_client.get_account(escrow_key(1).0).await.unwrap().is_none());
+
+	let mint = spl_token::state::Mint::unpack(&get_account(&mut banks_client, &alloy.mint).await.data).unwrap();
+	assert_eq!(mint.supply, 0);
+
+	let instruction = NftInstruction::update_alloy_price(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		alloy.id,
+		LISTED_PRICE,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::AlloyRedeemed);
+}
+
+#[tokio::test]
+async fn redemption_steps_run_in_order() {
+	let (mut banks_client, payer, alloy, custodian) = setup().await;
+
+	let instruction = request_instruction(&alloy, &payer.pubkey(), SHIPPING_HASH);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let out_of_order = [
+		mark_shipped_instruction(&alloy, &custodian.pubkey()),
+		confirm_delivery_instruction(&alloy, &custodian.pubkey()),
+	];
+	for instruction in out_of_order {
+		let result = process(&mut banks_client, &payer, &[instruction], &[&custodian]).await;
+		assert_custom_error(result, CustomError::InvalidRedemptionState);
+	}
+
+	let instruction = acknowledge_instruction(&alloy, &custodian.pubkey());
+	process(&mut banks_client, &payer, &[instruction], &[&custodian]).await.unwrap();
+
+	// Acknowledging twice, padded with a no-op transfer so the transaction is not a duplicate.
+	let instructions = [
+		acknowledge_instruction(&alloy, &custodian.pubkey()),
+		solana_sdk::system_instruction::transfer(&payer.pubkey(), &custodian.pubkey(), 1),
+	];
+	let result = process(&mut banks_client, &payer, &instructions, &[&custodian]).await;
+	assert_custom_error(result, CustomError::InvalidRedemptionState);
+}
+
+#[tokio::test]
+async fn redemption_steps_require_registered_custodian() {
+	let (mut banks_client, payer, alloy, _) = setup().await;
+	let stranger = Keypair::new();
+
+	let instruction = request_instruction(&alloy, &payer.pubkey(), SHIPPING_HASH);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let instruction = acknowledge_instruction(&alloy, &stranger.pubkey());
+	let result = process(&mut banks_client, &payer, &[instruction], &[&stranger]).await;
+
+	assert_custom_error(result, CustomError::NotRegistered);
+}
+
+#[tokio::test]
+async fn holder_cancels_until_shipped() {
+	let (mut banks_client, payer, alloy, custodian) = setup().await;
+
+	let instruction = request_instruction(&alloy, &payer.pubkey(), SHIPPING_HASH);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+	let instruction = acknowledge_instruction(&alloy, &custodian.pubkey());
+	process(&mut banks_client, &payer, &[instruction], &[&custodian]).await.unwrap();
+
+	process(&mut banks_client, &payer, &[cancel_instruction(&alloy)], &[&alloy.owner]).await.unwrap();
+
+	assert_eq!(token_amount(&mut banks_client, &alloy.token_account).await, 1);
+	assert!(banks_client.get_account(redemption_key(1).0).await.unwrap().is_none());
+	assert!(banks_client.get_account(escrow_key(1).0).await.unwrap().is_none());
+
+	// A cancelled redemption can be requested again, and is no longer cancellable once shipped.
+	let instruction = request_instruction(&alloy, &payer.pubkey(), [5; 32]);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let instructions = [
+		acknowledge_instruction(&alloy, &custodian.pubkey()),
+		mark_shipped_instruction(&alloy, &custodian.pubkey()),
+	];
+	process(&mut banks_client, &payer, &instructions, &[&custodian]).await.unwrap();
+
+	let result = process(&mut banks_client, &payer, &[cancel_instruction(&alloy)], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::InvalidRedemptionState);
+}
+
+#[tokio::test]
+async fn request_rejects_invalid_requests() {
+	let (mut banks_client, payer, alloy, _) = setup().await;
+
+	let instruction = request_instruction(&alloy, &payer.pubkey(), [0; 32]);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::MissingShippingHash);
+
+	let stranger = Keypair::new();
+	let mut instruction = request_instruction(&alloy, &payer.pubkey(), SHIPPING_HASH);
+	instruction.accounts[4].pubkey = stranger.pubkey();
+	let result = process(&mut banks_client, &payer, &[instruction], &[&stranger]).await;
+	assert_custom_error(result, CustomError::InvalidOwner);
+}
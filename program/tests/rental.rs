@@ -0,0 +1,284 @@
+//! Integration tests for renting out an alloy's use rights.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	rental::SECONDS_PER_DAY,
+	state::Rental,
+};
+use common::*;
+use spl_token::error::TokenError;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+	clock::Clock,
+	instruction::{ Instruction, InstructionError },
+	program_pack::Pack,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	system_instruction,
+};
+
+const PRICE_PER_DAY: u64 = 10_000_000;
+const MAX_DAYS: u16 = 30;
+
+fn create_rental_instruction(alloy: &AlloyFixture, payer: &Pubkey, price_per_day: u64, max_days: u16) -> Instruction {
+	NftInstruction::create_rental(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&rental_key(alloy.id).0,
+		&rental_escrow_key(alloy.id).0,
+		&alloy.mint,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		payer,
+		alloy.id,
+		price_per_day,
+		max_days,
+	)
+}
+
+fn rent_instruction(alloy: &AlloyFixture, renter: &Pubkey, days: u16) -> Instruction {
+	NftInstruction::rent_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&rental_key(alloy.id).0,
+		&rental_escrow_key(alloy.id).0,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		renter,
+		alloy.id,
+		days,
+	)
+}
+
+fn end_rental_instruction(alloy: &AlloyFixture, authority: &Pubkey) -> Instruction {
+	NftInstruction::end_rental(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&rental_key(alloy.id).0,
+		&rental_escrow_key(alloy.id).0,
+		authority,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		alloy.id,
+	)
+}
+
+fn split_instruction(alloy: &AlloyFixture, child_mint: &Pubkey, child_token_account: &Pubkey, payer: &Pubkey) -> Instruction {
+	NftInstruction::split_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&alloy_data_key(3).0,
+		&composition_key(alloy.id).0,
+		&composition_key(3).0,
+		child_mint,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		child_token_account,
+		payer,
+		alloy.id,
+		3,
+		QUANTITY_GRAMS / 2,
+		LISTED_PRICE,
+	)
+}
+
+fn merge_instruction(alloy: &AlloyFixture, source: &AlloyFixture) -> Instruction {
+	NftInstruction::merge_alloys(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&source.alloy_data,
+		&composition_key(alloy.id).0,
+		&composition_key(source.id).0,
+		&source.mint,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		&source.token_account,
+		alloy.id,
+		source.id,
+	)
+}
+
+fn update_price_instruction(alloy: &AlloyFixture, new_price: u64) -> Instruction {
+	NftInstruction::update_alloy_price(&alloy_token_program::id(), &alloy.alloy_data, alloy.id, new_price, &alloy.owner.pubkey(), &alloy.token_account)
+}
+
+async fn set_now(context: &mut ProgramTestContext, unix_timestamp: i64) {
+	let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+	context.set_sysvar(&Clock { unix_timestamp, ..clock });
+}
+
+/// An alloy offered for rent by its holder.
+async fn setup() -> (ProgramTestContext, Keypair, AlloyFixture) {
+	let mut context = program_test().start_with_context().await;
+	let payer = context.payer.insecure_clone();
+	let alloy = create_alloy(&mut context.banks_client, &payer, 1).await;
+
+	let instruction = create_rental_instruction(&alloy, &payer.pubkey(), PRICE_PER_DAY, MAX_DAYS);
+	process(&mut context.banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	(context, payer, alloy)
+}
+
+#[tokio::test]
+async fn rented_alloy_is_locked_until_the_rental_expires() {
+	let (mut context, payer, alloy) = setup().await;
+
+	process(&mut context.banks_client, &payer, &[rent_instruction(&alloy, &payer.pubkey(), 7)], &[]).await.unwrap();
+
+	let rental = Rental::unpack(&get_account(&mut context.banks_client, &rental_key(1).0).await.data).unwrap();
+	assert_eq!(rental.renter, payer.pubkey());
+	assert_eq!(rental.expires_at, rental.rented_at + 7 * SECONDS_PER_DAY);
+	assert_eq!(get_alloy_data(&mut context.banks_client, 1).await.rented_until, rental.expires_at);
+	assert_eq!(context.banks_client.get_balance(alloy.owner.pubkey()).await.unwrap(), 7 * PRICE_PER_DAY);
+
+	let result = process(&mut context.banks_client, &payer, &[update_price_instruction(&alloy, 1)], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::AlloyRented);
+
+	let purchase = NftInstruction::purchase_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		alloy.id,
+		None,
+		None,
+		None,
+		None,
+		false,
+		&payer.pubkey(),
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	);
+	// The NFT sits in the rental's escrow, so the lessor has nothing to sell.
+	let result = process(&mut context.banks_client, &payer, &[purchase], &[]).await;
+	assert_custom_error(result, CustomError::NotEnoughTokens);
+
+	let other = Keypair::new();
+	let result = process(&mut context.banks_client, &payer, &[rent_instruction(&alloy, &other.pubkey(), 1)], &[&other]).await;
+	assert_custom_error(result, CustomError::AlloyRented);
+
+	// Once the rental expires anyone may end it, which hands the NFT back to the lessor.
+	set_now(&mut context, rental.expires_at).await;
+	let stranger = Keypair::new();
+	process(&mut context.banks_client, &payer, &[end_rental_instruction(&alloy, &stranger.pubkey())], &[&stranger]).await.unwrap();
+	assert_eq!(token_amount(&mut context.banks_client, &alloy.token_account).await, 1);
+	assert!(context.banks_client.get_account(rental_escrow_key(1).0).await.unwrap().is_none());
+
+	process(&mut context.banks_client, &payer, &[update_price_instruction(&alloy, 2)], &[&alloy.owner]).await.unwrap();
+	assert_eq!(get_alloy_data(&mut context.banks_client, 1).await.listed_price, 2);
+}
+
+#[tokio::test]
+async fn rented_nft_is_escrowed_for_the_rental() {
+	let (mut context, payer, alloy) = setup().await;
+	let other_token_account = create_token_account(&mut context.banks_client, &payer, &alloy.mint, &payer.pubkey()).await;
+
+	process(&mut context.banks_client, &payer, &[rent_instruction(&alloy, &payer.pubkey(), 1)], &[]).await.unwrap();
+	assert_eq!(token_amount(&mut context.banks_client, &alloy.token_account).await, 0);
+	assert_eq!(token_amount(&mut context.banks_client, &rental_escrow_key(1).0).await, 1);
+
+	// The lessor has nothing left to transfer until the rental is ended.
+	let transfer = spl_token::instruction::transfer(&spl_token::id(), &alloy.token_account, &other_token_account, &alloy.owner.pubkey(), &[], 1).unwrap();
+	let result = process(&mut context.banks_client, &payer, &[transfer], &[&alloy.owner]).await;
+	assert_instruction_error(result, InstructionError::Custom(TokenError::InsufficientFunds as u32));
+
+	// A rental that expired without being ended keeps the NFT in escrow for the next one.
+	let rental = Rental::unpack(&get_account(&mut context.banks_client, &rental_key(1).0).await.data).unwrap();
+	set_now(&mut context, rental.expires_at).await;
+	let renter = Keypair::new();
+	let fund = system_instruction::transfer(&payer.pubkey(), &renter.pubkey(), 1_000_000_000);
+	process(&mut context.banks_client, &payer, &[fund, rent_instruction(&alloy, &renter.pubkey(), 1)], &[&renter]).await.unwrap();
+	assert_eq!(token_amount(&mut context.banks_client, &rental_escrow_key(1).0).await, 1);
+
+	process(&mut context.banks_client, &payer, &[end_rental_instruction(&alloy, &renter.pubkey())], &[&renter]).await.unwrap();
+	assert_eq!(token_amount(&mut context.banks_client, &alloy.token_account).await, 1);
+
+	let transfer = spl_token::instruction::transfer(&spl_token::id(), &alloy.token_account, &other_token_account, &alloy.owner.pubkey(), &[], 1).unwrap();
+	process(&mut context.banks_client, &payer, &[transfer], &[&alloy.owner]).await.unwrap();
+	assert_eq!(token_amount(&mut context.banks_client, &other_token_account).await, 1);
+}
+
+#[tokio::test]
+async fn rented_alloy_cannot_be_split_or_merged() {
+	let (mut context, payer, alloy) = setup().await;
+
+	// A second lot of the same grade with the same holder, so only the rental stands in the
+	// way of a merge.
+	let (mint, token_account) = create_nft(&mut context.banks_client, &payer, &alloy.owner.pubkey()).await;
+	let instruction = create_alloy_instruction(&payer.pubkey(), 2, "20MnCr5", "ALLOY", "https://example.com/alloys/20MnCr5.json", &mint);
+	process(&mut context.banks_client, &payer, &[instruction], &[]).await.unwrap();
+	let other = AlloyFixture { id: 2, alloy_data: alloy_data_key(2).0, mint, token_account, owner: alloy.owner.insecure_clone() };
+
+	process(&mut context.banks_client, &payer, &[rent_instruction(&alloy, &payer.pubkey(), 1)], &[]).await.unwrap();
+
+	let (child_mint, child_token_account) = (Keypair::new(), Keypair::new());
+	let instruction = split_instruction(&alloy, &child_mint.pubkey(), &child_token_account.pubkey(), &payer.pubkey());
+	let result = process(&mut context.banks_client, &payer, &[instruction], &[&alloy.owner, &child_mint, &child_token_account]).await;
+	assert_custom_error(result, CustomError::AlloyRented);
+
+	let result = process(&mut context.banks_client, &payer, &[merge_instruction(&alloy, &other)], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::AlloyRented);
+
+	let result = process(&mut context.banks_client, &payer, &[merge_instruction(&other, &alloy)], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::AlloyRented);
+
+	assert_eq!(get_alloy_data(&mut context.banks_client, 1).await.quantity_grams, QUANTITY_GRAMS);
+	assert_eq!(get_alloy_data(&mut context.banks_client, 2).await.quantity_grams, QUANTITY_GRAMS);
+}
+
+#[tokio::test]
+async fn renter_may_end_early_and_lessor_only_between_rentals() {
+	let (mut context, payer, alloy) = setup().await;
+	let renter = Keypair::new();
+	let fund = system_instruction::transfer(&payer.pubkey(), &renter.pubkey(), 1_000_000_000);
+	process(&mut context.banks_client, &payer, &[fund, rent_instruction(&alloy, &renter.pubkey(), MAX_DAYS)], &[&renter]).await.unwrap();
+
+	let result = process(&mut context.banks_client, &payer, &[end_rental_instruction(&alloy, &alloy.owner.pubkey())], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::AlloyRented);
+
+	let closed_rent = get_account(&mut context.banks_client, &rental_key(1).0).await.lamports
+		+ get_account(&mut context.banks_client, &rental_escrow_key(1).0).await.lamports;
+	let lessor_balance = context.banks_client.get_balance(alloy.owner.pubkey()).await.unwrap();
+	process(&mut context.banks_client, &payer, &[end_rental_instruction(&alloy, &renter.pubkey())], &[&renter]).await.unwrap();
+
+	assert!(context.banks_client.get_account(rental_key(1).0).await.unwrap().is_none());
+	assert_eq!(context.banks_client.get_balance(alloy.owner.pubkey()).await.unwrap(), lessor_balance + closed_rent);
+	process(&mut context.banks_client, &payer, &[update_price_instruction(&alloy, 1)], &[&alloy.owner]).await.unwrap();
+
+	// A fresh offer that was never taken up only closes for its lessor.
+	let instruction = create_rental_instruction(&alloy, &payer.pubkey(), PRICE_PER_DAY, MAX_DAYS);
+	process(&mut context.banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	let result = process(&mut context.banks_client, &payer, &[end_rental_instruction(&alloy, &renter.pubkey())], &[&renter]).await;
+	assert_custom_error(result, CustomError::InvalidOwner);
+
+	// Paid for by the lessor, so the transaction differs from the rejected one above.
+	process(&mut context.banks_client, &alloy.owner, &[end_rental_instruction(&alloy, &alloy.owner.pubkey())], &[]).await.unwrap();
+	assert!(context.banks_client.get_account(rental_key(1).0).await.unwrap().is_none());
+
+	// Withdrawing the offer also withdraws the rental's approval over the NFT.
+	let token_account = spl_token::state::Account::unpack(&get_account(&mut context.banks_client, &alloy.token_account).await.data).unwrap();
+	assert!(token_account.delegate.is_none());
+}
+
+#[tokio::test]
+async fn rental_rejects_invalid_terms() {
+	let mut context = program_test().start_with_context().await;
+	let payer = context.payer.insecure_clone();
+	let alloy = create_alloy(&mut context.banks_client, &payer, 1).await;
+
+	for (price_per_day, max_days) in [(0, MAX_DAYS), (PRICE_PER_DAY, 0)] {
+		let instruction = create_rental_instruction(&alloy, &payer.pubkey(), price_per_day, max_days);
+		let result = process(&mut context.banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+		assert_custom_error(result, CustomError::InvalidRentalTerms);
+	}
+
+	let instruction = create_rental_instruction(&alloy, &payer.pubkey(), PRICE_PER_DAY, MAX_DAYS);
+	process(&mut context.banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	for days in [0, MAX_DAYS + 1] {
+		let result = process(&mut context.banks_client, &payer, &[rent_instruction(&alloy, &payer.pubkey(), days)], &[]).await;
+		assert_custom_error(result, CustomError::InvalidRentalTerms);
+	}
+}
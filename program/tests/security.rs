@@ -0,0 +1,423 @@
+//! Account spoofing tests: every account slot of every instruction is swapped for an
+//! attacker-controlled account, stripped of its signer or writable flag, or pointed at a fake
+//! program, and the program must reject the result with the expected error.
+//!
+//! Each instruction runs its cases against one bank and finishes with the untouched
+//! instruction, which must succeed, so a case can only pass because of its substitution.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	state::AlloyData,
+};
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+	account::{ Account, AccountSharedData },
+	instruction::{ AccountMeta, Instruction, InstructionError },
+	program_option::COption,
+	program_pack::Pack,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	sysvar,
+	transaction::TransactionError,
+};
+use spl_token::state::{ Account as TokenAccount, AccountState };
+
+/// A program the attacker deployed, owning the accounts they forge.
+const ATTACKER_PROGRAM: Pubkey = Pubkey::new_from_array([7; 32]);
+
+/// Attacker-controlled accounts planted next to a legitimate alloy. The attacker is the
+/// transaction fee payer.
+struct Scenario {
+	alloy: AlloyFixture,
+	other_alloy: AlloyFixture,
+	attacker: Pubkey,
+	/// A copy of the alloy data owned by `ATTACKER_PROGRAM`.
+	forged_alloy_data: Pubkey,
+	/// A copy of the alloy data owned by the program but not at the alloy's PDA.
+	misplaced_alloy_data: Pubkey,
+	/// A token account owned by `ATTACKER_PROGRAM` claiming the attacker holds the alloy NFT.
+	forged_token_account: Pubkey,
+	/// A genuine token account of the attacker, for an unrelated NFT.
+	attacker_token_account: Pubkey,
+	/// A genuine token account of the attacker for the alloy NFT, holding nothing.
+	empty_token_account: Pubkey,
+}
+
+struct Case {
+	name: &'static str,
+	spoof: fn(&mut Instruction, &Scenario),
+	expected: InstructionError,
+}
+
+fn custom(error: CustomError) -> InstructionError {
+	InstructionError::Custom(error as u32)
+}
+
+fn set_account(context: &mut ProgramTestContext, owner: &Pubkey, data: Vec<u8>) -> Pubkey {
+	let key = Pubkey::new_unique();
+
+	context.set_account(&key, &AccountSharedData::from(Account {
+		lamports: 1_000_000_000,
+		data,
+		owner: *owner,
+		executable: false,
+		rent_epoch: 0,
+	}));
+
+	key
+}
+
+fn token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+	let mut data = vec![0; TokenAccount::LEN];
+
+	TokenAccount::pack(TokenAccount {
+		mint: *mint,
+		owner: *owner,
+		amount,
+		delegate: COption::None,
+		state: AccountState::Initialized,
+		is_native: COption::None,
+		delegated_amount: 0,
+		close_authority: COption::None,
+	}, &mut data).unwrap();
+
+	data
+}
+
+async fn setup() -> (ProgramTestContext, Scenario) {
+	let mut context = program_test().start_with_context().await;
+	let payer = context.payer.insecure_clone();
+	let attacker = payer.pubkey();
+
+	let alloy = create_alloy(&mut context.banks_client, &payer, 1).await;
+	let other_alloy = create_alloy(&mut context.banks_client, &payer, 2).await;
+	let alloy_data = get_account(&mut context.banks_client, &alloy.alloy_data).await.data;
+	let (_, attacker_token_account) = create_nft(&mut context.banks_client, &payer, &attacker).await;
+
+	let forged_alloy_data = set_account(&mut context, &ATTACKER_PROGRAM, alloy_data.clone());
+	let misplaced_alloy_data = set_account(&mut context, &alloy_token_program::id(), alloy_data);
+	let forged_token_account = set_account(&mut context, &ATTACKER_PROGRAM, token_account_data(&alloy.mint, &attacker, 1));
+	let empty_token_account = set_account(&mut context, &spl_token::id(), token_account_data(&alloy.mint, &attacker, 0));
+
+	let scenario = Scenario {
+		alloy,
+		other_alloy,
+		attacker,
+		forged_alloy_data,
+		misplaced_alloy_data,
+		forged_token_account,
+		attacker_token_account,
+		empty_token_account,
+	};
+
+	(context, scenario)
+}
+
+/// Runs every case, then the unmodified instruction, signing with whichever of `signers` the
+/// instruction asks for. The fee payer is always writable, so cases needing a read-only signer
+/// swap in the owner of the other alloy instead.
+async fn assert_rejects_spoofing(
+	context: &mut ProgramTestContext,
+	scenario: &Scenario,
+	instruction: Instruction,
+	signers: &[&Keypair],
+	cases: &[Case],
+) {
+	let payer = context.payer.insecure_clone();
+	let alloy_data_before = get_account(&mut context.banks_client, &scenario.alloy.alloy_data).await;
+
+	for case in cases {
+		let mut spoofed = instruction.clone();
+		(case.spoof)(&mut spoofed, scenario);
+
+		let signers = required_signers(&spoofed, signers);
+		let result = process(&mut context.banks_client, &payer, &[spoofed], &signers).await;
+
+		assert_eq!(result, Err(TransactionError::InstructionError(0, case.expected.clone())), "{}", case.name);
+	}
+
+	assert_eq!(get_account(&mut context.banks_client, &scenario.alloy.alloy_data).await, alloy_data_before);
+
+	let signers = required_signers(&instruction, signers);
+	process(&mut context.banks_client, &payer, &[instruction], &signers).await.unwrap();
+}
+
+fn required_signers<'a>(instruction: &Instruction, signers: &[&'a Keypair]) -> Vec<&'a Keypair> {
+	signers.iter()
+		.copied()
+		.filter(|signer| instruction.accounts.iter().any(|meta| meta.is_signer && meta.pubkey == signer.pubkey()))
+		.collect()
+}
+
+#[tokio::test]
+async fn create_alloy_data_account_rejects_spoofed_accounts() {
+	let (mut context, scenario) = setup().await;
+
+	let instruction = create_alloy_instruction(&scenario.attacker, 3, "20MnCr5", "ALLOY", "ipfs://20MnCr5", &Pubkey::new_unique());
+
+	let cases = [
+		Case {
+			name: "alloy data of an existing alloy",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.alloy.alloy_data,
+			expected: custom(CustomError::AlreadyInitialized),
+		},
+		Case {
+			name: "alloy data owned by the attacker's program",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.forged_alloy_data,
+			expected: custom(CustomError::AlreadyInitialized),
+		},
+		Case {
+			name: "alloy data at a key that is not the alloy's PDA",
+			spoof: |instruction, _| instruction.accounts[0].pubkey = Pubkey::new_unique(),
+			expected: custom(CustomError::InvalidAlloyDataKey),
+		},
+		Case {
+			name: "heat index of another heat",
+			spoof: |instruction, _| instruction.accounts[1].pubkey = heat_index_key(MILL, &provenance(1).heat_number).0,
+			expected: custom(CustomError::InvalidHeatIndexKey),
+		},
+		Case {
+			name: "heat index at a key that is not the heat's PDA",
+			spoof: |instruction, _| instruction.accounts[1].pubkey = Pubkey::new_unique(),
+			expected: custom(CustomError::InvalidHeatIndexKey),
+		},
+		Case {
+			name: "read-only heat index",
+			spoof: |instruction, _| instruction.accounts[1].is_writable = false,
+			expected: custom(CustomError::AccountNotWritable),
+		},
+		Case {
+			name: "read-only alloy data",
+			spoof: |instruction, _| instruction.accounts[0].is_writable = false,
+			expected: custom(CustomError::AccountNotWritable),
+		},
+		Case {
+			name: "payer did not sign",
+			spoof: |instruction, _| {
+				instruction.accounts[2].pubkey = Pubkey::new_unique();
+				instruction.accounts[2].is_signer = false;
+			},
+			expected: InstructionError::MissingRequiredSignature,
+		},
+		Case {
+			name: "read-only payer",
+			spoof: |instruction, scenario| instruction.accounts[2] = AccountMeta::new_readonly(scenario.other_alloy.owner.pubkey(), true),
+			expected: custom(CustomError::AccountNotWritable),
+		},
+		Case {
+			name: "fake system program",
+			spoof: |instruction, _| instruction.accounts[3].pubkey = ATTACKER_PROGRAM,
+			expected: InstructionError::IncorrectProgramId,
+		},
+		Case {
+			name: "fake rent sysvar",
+			spoof: |instruction, _| instruction.accounts[4].pubkey = sysvar::clock::id(),
+			expected: InstructionError::InvalidArgument,
+		},
+		Case {
+			name: "missing accounts",
+			spoof: |instruction, _| instruction.accounts.truncate(4),
+			expected: InstructionError::NotEnoughAccountKeys,
+		},
+	];
+
+	assert_rejects_spoofing(&mut context, &scenario, instruction, &[&scenario.other_alloy.owner], &cases).await;
+}
+
+#[tokio::test]
+async fn update_alloy_price_rejects_spoofed_accounts() {
+	let (mut context, scenario) = setup().await;
+	let alloy = &scenario.alloy;
+
+	let instruction = NftInstruction::update_alloy_price(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		alloy.id,
+		1,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	);
+
+	let cases = [
+		Case {
+			name: "alloy data owned by the attacker's program",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.forged_alloy_data,
+			expected: custom(CustomError::IncorrectOwner),
+		},
+		Case {
+			name: "alloy data copied away from the alloy's PDA",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.misplaced_alloy_data,
+			expected: custom(CustomError::InvalidAlloyDataKey),
+		},
+		Case {
+			name: "alloy data of another alloy",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.other_alloy.alloy_data,
+			expected: custom(CustomError::InvalidAlloyDataKey),
+		},
+		Case {
+			name: "read-only alloy data",
+			spoof: |instruction, _| instruction.accounts[0].is_writable = false,
+			expected: custom(CustomError::AccountNotWritable),
+		},
+		Case {
+			name: "owner did not sign",
+			spoof: |instruction, _| instruction.accounts[1].is_signer = false,
+			expected: InstructionError::MissingRequiredSignature,
+		},
+		Case {
+			name: "attacker signs with the holder's token account",
+			spoof: |instruction, scenario| instruction.accounts[1].pubkey = scenario.attacker,
+			expected: custom(CustomError::InvalidOwner),
+		},
+		Case {
+			name: "attacker signs with a forged token account",
+			spoof: |instruction, scenario| {
+				instruction.accounts[1].pubkey = scenario.attacker;
+				instruction.accounts[2].pubkey = scenario.forged_token_account;
+			},
+			expected: custom(CustomError::IncorrectOwner),
+		},
+		Case {
+			name: "attacker signs with a token account of another NFT",
+			spoof: |instruction, scenario| {
+				instruction.accounts[1].pubkey = scenario.attacker;
+				instruction.accounts[2].pubkey = scenario.attacker_token_account;
+			},
+			expected: custom(CustomError::OwnerMismatch),
+		},
+		Case {
+			name: "attacker signs with an empty token account of the NFT",
+			spoof: |instruction, scenario| {
+				instruction.accounts[1].pubkey = scenario.attacker;
+				instruction.accounts[2].pubkey = scenario.empty_token_account;
+			},
+			expected: custom(CustomError::NotEnoughTokens),
+		},
+		Case {
+			name: "token account of another alloy",
+			spoof: |instruction, scenario| instruction.accounts[2].pubkey = scenario.other_alloy.token_account,
+			expected: custom(CustomError::OwnerMismatch),
+		},
+		Case {
+			name: "missing accounts",
+			spoof: |instruction, _| instruction.accounts.truncate(2),
+			expected: InstructionError::NotEnoughAccountKeys,
+		},
+	];
+
+	assert_rejects_spoofing(&mut context, &scenario, instruction, &[&scenario.alloy.owner], &cases).await;
+}
+
+#[tokio::test]
+async fn purchase_alloy_rejects_spoofed_accounts() {
+	let (mut context, scenario) = setup().await;
+	let alloy = &scenario.alloy;
+
+	let instruction = NftInstruction::purchase_alloy(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		alloy.id,
+		None,
+		None,
+		None,
+		None,
+		false,
+		&scenario.attacker,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+	);
+
+	let cases = [
+		Case {
+			name: "alloy data owned by the attacker's program",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.forged_alloy_data,
+			expected: custom(CustomError::IncorrectOwner),
+		},
+		Case {
+			name: "alloy data copied away from the alloy's PDA",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.misplaced_alloy_data,
+			expected: custom(CustomError::InvalidAlloyDataKey),
+		},
+		Case {
+			name: "alloy data of another alloy",
+			spoof: |instruction, scenario| instruction.accounts[0].pubkey = scenario.other_alloy.alloy_data,
+			expected: custom(CustomError::InvalidAlloyDataKey),
+		},
+		Case {
+			name: "read-only alloy data",
+			spoof: |instruction, _| instruction.accounts[0].is_writable = false,
+			expected: custom(CustomError::AccountNotWritable),
+		},
+		Case {
+			name: "payer did not sign",
+			spoof: |instruction, _| {
+				instruction.accounts[1].pubkey = Pubkey::new_unique();
+				instruction.accounts[1].is_signer = false;
+			},
+			expected: InstructionError::MissingRequiredSignature,
+		},
+		Case {
+			name: "read-only payer",
+			spoof: |instruction, scenario| instruction.accounts[1] = AccountMeta::new_readonly(scenario.other_alloy.owner.pubkey(), true),
+			expected: custom(CustomError::AccountNotWritable),
+		},
+		Case {
+			name: "payment redirected to the attacker",
+			spoof: |instruction, scenario| instruction.accounts[2].pubkey = scenario.attacker,
+			expected: custom(CustomError::InvalidOwner),
+		},
+		Case {
+			name: "payment redirected to the attacker with a forged token account",
+			spoof: |instruction, scenario| {
+				instruction.accounts[2].pubkey = scenario.attacker;
+				instruction.accounts[3].pubkey = scenario.forged_token_account;
+			},
+			expected: custom(CustomError::IncorrectOwner),
+		},
+		Case {
+			name: "payment redirected to the attacker with a token account of another NFT",
+			spoof: |instruction, scenario| {
+				instruction.accounts[2].pubkey = scenario.attacker;
+				instruction.accounts[3].pubkey = scenario.attacker_token_account;
+			},
+			expected: custom(CustomError::OwnerMismatch),
+		},
+		Case {
+			name: "payment redirected to the attacker with an empty token account of the NFT",
+			spoof: |instruction, scenario| {
+				instruction.accounts[2].pubkey = scenario.attacker;
+				instruction.accounts[3].pubkey = scenario.empty_token_account;
+			},
+			expected: custom(CustomError::NotEnoughTokens),
+		},
+		Case {
+			name: "read-only seller",
+			spoof: |instruction, _| instruction.accounts[2].is_writable = false,
+			expected: custom(CustomError::AccountNotWritable),
+		},
+		Case {
+			name: "token account of another alloy",
+			spoof: |instruction, scenario| instruction.accounts[3].pubkey = scenario.other_alloy.token_account,
+			expected: custom(CustomError::OwnerMismatch),
+		},
+		Case {
+			name: "fake system program",
+			spoof: |instruction, _| instruction.accounts[4].pubkey = ATTACKER_PROGRAM,
+			expected: InstructionError::IncorrectProgramId,
+		},
+		Case {
+			name: "missing accounts",
+			spoof: |instruction, _| instruction.accounts.truncate(4),
+			expected: InstructionError::NotEnoughAccountKeys,
+		},
+	];
+
+	assert_rejects_spoofing(&mut context, &scenario, instruction, &[&scenario.other_alloy.owner], &cases).await;
+
+	let alloy_data: AlloyData = get_alloy_data(&mut context.banks_client, 1).await;
+	assert_eq!(alloy_data.last_price, LISTED_PRICE);
+}
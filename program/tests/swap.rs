@@ -0,0 +1,145 @@
+//! Integration tests for alloy-for-alloy swaps.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	state::Swap,
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	instruction::Instruction,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	system_instruction,
+};
+
+const TOP_UP: u64 = 500_000_000;
+
+/// Alloy 1 offered by its holder for alloy 2 plus `TOP_UP`, with token accounts for both
+/// parties to receive the other's NFT.
+struct Trade {
+	offered: AlloyFixture,
+	wanted: AlloyFixture,
+	proposer_wanted_token_account: Pubkey,
+	counterparty_offered_token_account: Pubkey,
+}
+
+fn propose_instruction(offered: &AlloyFixture, wanted_id: u8, payer: &Pubkey) -> Instruction {
+	NftInstruction::propose_swap(
+		&alloy_token_program::id(),
+		&offered.alloy_data,
+		&alloy_data_key(wanted_id).0,
+		&swap_key(offered.id).0,
+		&swap_escrow_key(offered.id).0,
+		&offered.mint,
+		&offered.owner.pubkey(),
+		&offered.token_account,
+		payer,
+		offered.id,
+		wanted_id,
+		TOP_UP,
+	)
+}
+
+fn accept_instruction(trade: &Trade, proposer_token_account: &Pubkey) -> Instruction {
+	NftInstruction::accept_swap(
+		&alloy_token_program::id(),
+		&trade.wanted.alloy_data,
+		&swap_key(trade.offered.id).0,
+		&swap_escrow_key(trade.offered.id).0,
+		&trade.offered.owner.pubkey(),
+		proposer_token_account,
+		&trade.wanted.owner.pubkey(),
+		&trade.wanted.token_account,
+		&trade.counterparty_offered_token_account,
+		trade.offered.id,
+	)
+}
+
+async fn setup() -> (BanksClient, Keypair, Trade) {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let offered = create_alloy(&mut banks_client, &payer, 1).await;
+	let wanted = create_alloy(&mut banks_client, &payer, 2).await;
+	let proposer_wanted_token_account = create_token_account(&mut banks_client, &payer, &wanted.mint, &offered.owner.pubkey()).await;
+	let counterparty_offered_token_account = create_token_account(&mut banks_client, &payer, &offered.mint, &wanted.owner.pubkey()).await;
+
+	// Enough for the top-up with the counterparty staying rent exempt.
+	let fund = system_instruction::transfer(&payer.pubkey(), &wanted.owner.pubkey(), TOP_UP + 1_000_000_000);
+	let propose = propose_instruction(&offered, wanted.id, &payer.pubkey());
+	process(&mut banks_client, &payer, &[fund, propose], &[&offered.owner]).await.unwrap();
+
+	(banks_client, payer, Trade { offered, wanted, proposer_wanted_token_account, counterparty_offered_token_account })
+}
+
+#[tokio::test]
+async fn accept_trades_both_nfts_and_the_top_up() {
+	let (mut banks_client, payer, trade) = setup().await;
+
+	let swap = Swap::unpack(&get_account(&mut banks_client, &swap_key(1).0).await.data).unwrap();
+	assert_eq!(swap.proposer, trade.offered.owner.pubkey());
+	assert_eq!(swap.wanted_id, 2);
+	assert_eq!(token_amount(&mut banks_client, &swap_escrow_key(1).0).await, 1);
+
+	let rents = get_account(&mut banks_client, &swap_key(1).0).await.lamports
+		+ get_account(&mut banks_client, &swap_escrow_key(1).0).await.lamports;
+
+	let accept = accept_instruction(&trade, &trade.proposer_wanted_token_account);
+	process(&mut banks_client, &payer, &[accept], &[&trade.wanted.owner]).await.unwrap();
+
+	assert_eq!(token_amount(&mut banks_client, &trade.proposer_wanted_token_account).await, 1);
+	assert_eq!(token_amount(&mut banks_client, &trade.wanted.token_account).await, 0);
+	assert_eq!(token_amount(&mut banks_client, &trade.counterparty_offered_token_account).await, 1);
+	assert!(banks_client.get_account(swap_key(1).0).await.unwrap().is_none());
+	assert!(banks_client.get_account(swap_escrow_key(1).0).await.unwrap().is_none());
+	assert_eq!(banks_client.get_balance(trade.offered.owner.pubkey()).await.unwrap(), TOP_UP + rents);
+}
+
+#[tokio::test]
+async fn accept_rejects_redirecting_the_wanted_nft() {
+	let (mut banks_client, payer, trade) = setup().await;
+	// A token account of the wanted mint that belongs to the counterparty, not the proposer.
+	let own_token_account = create_token_account(&mut banks_client, &payer, &trade.wanted.mint, &trade.wanted.owner.pubkey()).await;
+
+	let accept = accept_instruction(&trade, &own_token_account);
+	let result = process(&mut banks_client, &payer, &[accept], &[&trade.wanted.owner]).await;
+	assert_custom_error(result, CustomError::InvalidOwner);
+
+	assert_eq!(token_amount(&mut banks_client, &trade.wanted.token_account).await, 1);
+	assert_eq!(token_amount(&mut banks_client, &swap_escrow_key(1).0).await, 1);
+}
+
+#[tokio::test]
+async fn cancel_returns_the_nft_to_the_proposer() {
+	let (mut banks_client, payer, trade) = setup().await;
+
+	let cancel = NftInstruction::cancel_swap(
+		&alloy_token_program::id(),
+		&swap_key(1).0,
+		&swap_escrow_key(1).0,
+		&trade.offered.owner.pubkey(),
+		&trade.offered.token_account,
+		1,
+	);
+	process(&mut banks_client, &payer, &[cancel], &[&trade.offered.owner]).await.unwrap();
+
+	assert_eq!(token_amount(&mut banks_client, &trade.offered.token_account).await, 1);
+	assert!(banks_client.get_account(swap_key(1).0).await.unwrap().is_none());
+
+	// The offer is gone, so it can no longer be accepted.
+	let accept = accept_instruction(&trade, &trade.proposer_wanted_token_account);
+	assert!(process(&mut banks_client, &payer, &[accept], &[&trade.wanted.owner]).await.is_err());
+	assert_eq!(token_amount(&mut banks_client, &trade.wanted.token_account).await, 1);
+}
+
+#[tokio::test]
+async fn propose_rejects_swapping_an_alloy_for_itself() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let offered = create_alloy(&mut banks_client, &payer, 1).await;
+
+	let propose = propose_instruction(&offered, offered.id, &payer.pubkey());
+	let result = process(&mut banks_client, &payer, &[propose], &[&offered.owner]).await;
+	assert_custom_error(result, CustomError::InvalidSwap);
+}
@@ -0,0 +1,173 @@
+//! Integration tests for fractional ownership vaults.
+
+mod common;
+
+use alloy_token_program::{
+	error::CustomError,
+	instruction::NftInstruction,
+	state::Vault,
+	vault::VaultStatus,
+};
+use common::*;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+	instruction::Instruction,
+	program_pack::Pack,
+	pubkey::Pubkey,
+	signature::{ Keypair, Signer },
+	system_instruction,
+};
+use spl_token::state::Mint;
+
+const SHARE_SUPPLY: u64 = 1_000_000;
+const RESERVE_PRICE: u64 = 4_000_000_000;
+
+/// Creates an empty share mint with `mint_authority`, and a share token account for `owner`.
+async fn create_share_mint(banks_client: &mut BanksClient, payer: &Keypair, mint_authority: &Pubkey, owner: &Pubkey) -> (Pubkey, Pubkey) {
+	let share_mint = Keypair::new();
+	let rent = banks_client.get_rent().await.unwrap();
+	let instructions = [
+		system_instruction::create_account(
+			&payer.pubkey(),
+			&share_mint.pubkey(),
+			rent.minimum_balance(Mint::LEN),
+			Mint::LEN as u64,
+			&spl_token::id(),
+		),
+		spl_token::instruction::initialize_mint(&spl_token::id(), &share_mint.pubkey(), mint_authority, None, 6).unwrap(),
+	];
+	process(banks_client, payer, &instructions, &[&share_mint]).await.unwrap();
+
+	let share_token_account = create_token_account(banks_client, payer, &share_mint.pubkey(), owner).await;
+
+	(share_mint.pubkey(), share_token_account)
+}
+
+fn create_vault_instruction(alloy: &AlloyFixture, share_mint: &Pubkey, share_token_account: &Pubkey, payer: &Pubkey, share_supply: u64) -> Instruction {
+	NftInstruction::create_vault(
+		&alloy_token_program::id(),
+		&alloy.alloy_data,
+		&vault_key(alloy.id).0,
+		&vault_escrow_key(alloy.id).0,
+		&alloy.mint,
+		share_mint,
+		&alloy.owner.pubkey(),
+		&alloy.token_account,
+		share_token_account,
+		payer,
+		alloy.id,
+		share_supply,
+		RESERVE_PRICE,
+	)
+}
+
+fn claim_instruction(id: u8, share_mint: &Pubkey, holder: &Pubkey, share_token_account: &Pubkey) -> Instruction {
+	NftInstruction::claim_vault_proceeds(&alloy_token_program::id(), &vault_key(id).0, share_mint, holder, share_token_account, id)
+}
+
+/// An alloy locked in a vault, with every share held by the alloy's owner.
+async fn setup() -> (BanksClient, Keypair, AlloyFixture, Pubkey, Pubkey) {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let (share_mint, share_token_account) = create_share_mint(&mut banks_client, &payer, &vault_key(1).0, &alloy.owner.pubkey()).await;
+
+	let instruction = create_vault_instruction(&alloy, &share_mint, &share_token_account, &payer.pubkey(), SHARE_SUPPLY);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	(banks_client, payer, alloy, share_mint, share_token_account)
+}
+
+#[tokio::test]
+async fn vault_locks_the_nft_until_every_share_redeems_it() {
+	let (mut banks_client, payer, alloy, share_mint, share_token_account) = setup().await;
+
+	let vault = Vault::unpack(&get_account(&mut banks_client, &vault_key(1).0).await.data).unwrap();
+	let mint = Mint::unpack(&get_account(&mut banks_client, &share_mint).await.data).unwrap();
+
+	assert_eq!(vault.status(), Some(VaultStatus::Active));
+	assert_eq!(vault.curator, alloy.owner.pubkey());
+	assert_eq!(token_amount(&mut banks_client, &vault_escrow_key(1).0).await, 1);
+	assert_eq!(token_amount(&mut banks_client, &alloy.token_account).await, 0);
+	assert_eq!(token_amount(&mut banks_client, &share_token_account).await, SHARE_SUPPLY);
+	assert!(mint.mint_authority.is_none());
+
+	let other = Keypair::new();
+	let other_share_token_account = create_token_account(&mut banks_client, &payer, &share_mint, &other.pubkey()).await;
+	let transfer = spl_token::instruction::transfer(&spl_token::id(), &share_token_account, &other_share_token_account, &alloy.owner.pubkey(), &[], 1).unwrap();
+	process(&mut banks_client, &payer, &[transfer], &[&alloy.owner]).await.unwrap();
+
+	let redeem = NftInstruction::redeem_vault(
+		&alloy_token_program::id(),
+		&vault_key(1).0,
+		&vault_escrow_key(1).0,
+		&share_mint,
+		&alloy.owner.pubkey(),
+		&share_token_account,
+		&alloy.token_account,
+		1,
+	);
+	let result = process(&mut banks_client, &payer, std::slice::from_ref(&redeem), &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::IncompleteShares);
+
+	let transfer = spl_token::instruction::transfer(&spl_token::id(), &other_share_token_account, &share_token_account, &other.pubkey(), &[], 1).unwrap();
+	process(&mut banks_client, &payer, &[transfer, redeem], &[&alloy.owner, &other]).await.unwrap();
+
+	assert_eq!(token_amount(&mut banks_client, &alloy.token_account).await, 1);
+	assert_eq!(token_amount(&mut banks_client, &share_token_account).await, 0);
+	assert!(banks_client.get_account(vault_key(1).0).await.unwrap().is_none());
+	assert!(banks_client.get_account(vault_escrow_key(1).0).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn buyout_proceeds_are_claimed_pro_rata() {
+	let (mut banks_client, payer, alloy, share_mint, share_token_account) = setup().await;
+
+	let other = Keypair::new();
+	let other_share_token_account = create_token_account(&mut banks_client, &payer, &share_mint, &other.pubkey()).await;
+	let transfer = spl_token::instruction::transfer(&spl_token::id(), &share_token_account, &other_share_token_account, &alloy.owner.pubkey(), &[], SHARE_SUPPLY / 4).unwrap();
+	process(&mut banks_client, &payer, &[transfer], &[&alloy.owner]).await.unwrap();
+
+	// Nothing to claim before a buyout.
+	let instruction = claim_instruction(1, &share_mint, &other.pubkey(), &other_share_token_account);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&other]).await;
+	assert_custom_error(result, CustomError::InvalidVaultState);
+
+	let buyer_token_account = create_token_account(&mut banks_client, &payer, &alloy.mint, &payer.pubkey()).await;
+	let buyout = NftInstruction::buyout_vault(&alloy_token_program::id(), &vault_key(1).0, &vault_escrow_key(1).0, &payer.pubkey(), &buyer_token_account, 1);
+	process(&mut banks_client, &payer, &[buyout], &[]).await.unwrap();
+
+	let vault = Vault::unpack(&get_account(&mut banks_client, &vault_key(1).0).await.data).unwrap();
+	assert_eq!(vault.status(), Some(VaultStatus::BoughtOut));
+	assert_eq!(vault.buyer, payer.pubkey());
+	assert_eq!(vault.proceeds, RESERVE_PRICE);
+	assert_eq!(token_amount(&mut banks_client, &buyer_token_account).await, 1);
+
+	let instruction = claim_instruction(1, &share_mint, &other.pubkey(), &other_share_token_account);
+	process(&mut banks_client, &payer, &[instruction], &[&other]).await.unwrap();
+	assert_eq!(banks_client.get_balance(other.pubkey()).await.unwrap(), RESERVE_PRICE / 4);
+
+	let vault_rent = get_account(&mut banks_client, &vault_key(1).0).await.lamports - RESERVE_PRICE * 3 / 4;
+	let instruction = claim_instruction(1, &share_mint, &alloy.owner.pubkey(), &share_token_account);
+	process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await.unwrap();
+
+	assert_eq!(banks_client.get_balance(alloy.owner.pubkey()).await.unwrap(), RESERVE_PRICE * 3 / 4 + vault_rent);
+	assert!(banks_client.get_account(vault_key(1).0).await.unwrap().is_none());
+	assert_eq!(Mint::unpack(&get_account(&mut banks_client, &share_mint).await.data).unwrap().supply, 0);
+}
+
+#[tokio::test]
+async fn vault_rejects_invalid_terms_and_share_mints() {
+	let (mut banks_client, payer, _) = program_test().start().await;
+	let alloy = create_alloy(&mut banks_client, &payer, 1).await;
+	let (share_mint, share_token_account) = create_share_mint(&mut banks_client, &payer, &vault_key(1).0, &alloy.owner.pubkey()).await;
+
+	let instruction = create_vault_instruction(&alloy, &share_mint, &share_token_account, &payer.pubkey(), 0);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::InvalidVaultTerms);
+
+	// A mint anyone but the vault can mint from would let shares be diluted.
+	let (open_mint, open_token_account) = create_share_mint(&mut banks_client, &payer, &payer.pubkey(), &alloy.owner.pubkey()).await;
+	let instruction = create_vault_instruction(&alloy, &open_mint, &open_token_account, &payer.pubkey(), SHARE_SUPPLY);
+	let result = process(&mut banks_client, &payer, &[instruction], &[&alloy.owner]).await;
+	assert_custom_error(result, CustomError::InvalidShareMint);
+}